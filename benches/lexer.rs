@@ -0,0 +1,68 @@
+//! Throughput of the lexer and the parser on a generated config of a few megabytes.
+//!
+//! Run with `cargo bench --bench lexer`.
+
+use std::fmt::Write;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rotten_potato::{lexer::Lexer, workspace::SourceFile};
+
+/// The approximate size of the generated config in bytes.
+const SIZE: usize = 4 * 1024 * 1024;
+
+/// Returns a config made of `defvar`s, `defpoll`s and widgets with expressions, interpolations,
+/// comments and non-ASCII icons, repeated with different names until it reaches `size` bytes.
+fn generate_config(size: usize) -> String {
+    let mut config = String::with_capacity(size + 1024);
+
+    for idx in 0.. {
+        if config.len() >= size {
+            break;
+        }
+
+        write!(
+            config,
+            r#"; volume {idx} ✓
+(defvar volume_{idx} 50)
+(defpoll battery_{idx} :interval "10s" :initial "100" "cat /sys/class/power_supply/BAT0/capacity")
+
+(defwidget bar_{idx} [?icon]
+  (box :orientation "h" :space-evenly false :class "bar-{idx}"
+    (label :text "󰕾 ${{volume_{idx}}}% 󰁹 ${{battery_{idx}}}%")
+    {{ volume_{idx} > 50 ? "high" : "low" }}
+    {{ volume_{idx} > 50 && battery_{idx} <= 20 }}
+    (for item in [1, 2, 3]
+      (button :onclick "notify-send ${{item}}" {{ round(item * 2, 0) + 1 }}))))
+
+"#
+        )
+        .unwrap();
+    }
+
+    config
+}
+
+fn lexer(c: &mut Criterion) {
+    let config = generate_config(SIZE);
+    assert_eq!(SourceFile::parse(config.as_str()).diagnostics, Vec::new());
+
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Bytes(config.len() as u64));
+    group.sample_size(10);
+
+    group.bench_with_input(
+        BenchmarkId::new("lex", config.len()),
+        &config,
+        |b, config| b.iter(|| Lexer::new(config).map(Result::unwrap).count()),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("parse", config.len()),
+        &config,
+        |b, config| b.iter(|| SourceFile::parse(config.as_str())),
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, lexer);
+criterion_main!(benches);
@@ -0,0 +1,593 @@
+//! An arena representation of the AST for semantic passes over large configs.
+//!
+//! [`Ast::lower`] flattens the nodes of the regular AST into one `Vec`, where nodes refer to
+//! each other through copyable [`NodeId`]s instead of `Box`es, and symbols are interned to
+//! [`SymbolId`]s that compare in O(1). Files lowered with the same [`Interner`] share their ids.
+//!
+//! The arena keeps what the semantic passes need: keywords are dropped, atoms and expressions
+//! share their node kinds and the arguments of all forms are [`Node::Arg`]s.
+//!
+//! The grammar does not build into the arena. Parsing still allocates the boxed AST, which
+//! lowering then copies, so an arena costs more allocations than the AST alone. What it saves is
+//! the work of the passes over it: ids instead of pointers, symbols compared without looking at
+//! their text, and one `Vec` per file to keep around once the parsed AST is dropped.
+
+use std::ops::Index;
+
+use crate::{
+    ast::{
+        atom::Atom,
+        binary_operation::BinaryOperator,
+        comparison_operation::ComparisonOperator,
+        def_listen::DefListenArgValue,
+        def_poll::DefPollArgValue,
+        def_var::DefVarValue,
+        def_widget::DefWidgetParam,
+        def_window::DefWindowArgValue,
+        expr::PrimitiveExpr,
+        for_loop::ForLoop,
+        literal::LiteralArgValue,
+        symbol::Symbol,
+        top_level_expr::TopLevelExpr,
+        unary_operation::UnaryOperation,
+        widget_call::{WidgetCall, WidgetCallArgValue},
+        widget_node::WidgetNode,
+    },
+    spanned::Spanned,
+};
+
+pub mod interner;
+
+pub use interner::{Interner, SymbolId};
+
+/// A handle to a node of an [`Ast`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    DefListen {
+        name: Spanned<SymbolId>,
+        args: Vec<NodeId>,
+        script: Spanned<String>,
+    },
+    DefPoll {
+        name: Spanned<SymbolId>,
+        args: Vec<NodeId>,
+        script: Spanned<String>,
+    },
+    DefVar {
+        name: Spanned<SymbolId>,
+        value: NodeId,
+    },
+    DefWidget {
+        name: Spanned<SymbolId>,
+        params: Vec<NodeId>,
+        children: Vec<NodeId>,
+    },
+    DefWindow {
+        name: Spanned<SymbolId>,
+        params: Vec<NodeId>,
+        args: Vec<NodeId>,
+        content: Vec<NodeId>,
+    },
+    Include {
+        path: Spanned<String>,
+    },
+    Literal {
+        args: Vec<NodeId>,
+    },
+    /// A node the parser recovered from. Without a span of its own, it gets the one of its
+    /// parent.
+    Err,
+
+    /// `:name value`, an argument of any form.
+    Arg {
+        name: Spanned<SymbolId>,
+        value: NodeId,
+    },
+    Param {
+        name: Spanned<SymbolId>,
+        is_optional: bool,
+    },
+    WidgetCall {
+        name: Spanned<SymbolId>,
+        args: Vec<NodeId>,
+        children: Vec<NodeId>,
+    },
+    ForLoop {
+        binding: Spanned<SymbolId>,
+        iterable: NodeId,
+        body: Vec<NodeId>,
+    },
+    Children {
+        nth: Option<Spanned<usize>>,
+    },
+
+    Array {
+        items: Vec<NodeId>,
+    },
+    BinaryOperation {
+        lhs: NodeId,
+        op: BinaryOperator,
+        rhs: NodeId,
+    },
+    Bool(bool),
+    ComparisonOperation {
+        lhs: NodeId,
+        op: ComparisonOperator,
+        rhs: NodeId,
+    },
+    FunctionCall {
+        name: Spanned<SymbolId>,
+        args: Vec<NodeId>,
+    },
+    Not(NodeId),
+    Number(String),
+    Object {
+        entries: Vec<(Spanned<String>, NodeId)>,
+    },
+    StrLit(String),
+    Symbol(SymbolId),
+    TernaryOperation {
+        cond: NodeId,
+        true_branch: NodeId,
+        false_branch: NodeId,
+    },
+}
+
+impl Node {
+    /// Returns the direct children of the node in source order.
+    pub fn children(&self) -> Vec<NodeId> {
+        match self {
+            Self::DefListen { args, .. } | Self::DefPoll { args, .. } | Self::Literal { args } => {
+                args.clone()
+            }
+            Self::DefVar { value, .. } | Self::Arg { value, .. } => vec![*value],
+            Self::DefWidget {
+                params, children, ..
+            } => params.iter().chain(children).copied().collect(),
+            Self::DefWindow {
+                params,
+                args,
+                content,
+                ..
+            } => params.iter().chain(args).chain(content).copied().collect(),
+            Self::WidgetCall { args, children, .. } => {
+                args.iter().chain(children).copied().collect()
+            }
+            Self::ForLoop { iterable, body, .. } => {
+                std::iter::once(iterable).chain(body).copied().collect()
+            }
+            Self::Array { items: args } | Self::FunctionCall { args, .. } => args.clone(),
+            Self::BinaryOperation { lhs, rhs, .. } | Self::ComparisonOperation { lhs, rhs, .. } => {
+                vec![*lhs, *rhs]
+            }
+            Self::Not(value) => vec![*value],
+            Self::Object { entries } => entries.iter().map(|(_, value)| *value).collect(),
+            Self::TernaryOperation {
+                cond,
+                true_branch,
+                false_branch,
+            } => vec![*cond, *true_branch, *false_branch],
+            Self::Include { .. }
+            | Self::Err
+            | Self::Param { .. }
+            | Self::Children { .. }
+            | Self::Bool(_)
+            | Self::Number(_)
+            | Self::StrLit(_)
+            | Self::Symbol(_) => Vec::new(),
+        }
+    }
+}
+
+/// The nodes of a file. Children are stored in front of their parents, so iterating over the
+/// nodes visits them bottom-up.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ast {
+    nodes: Vec<Spanned<Node>>,
+    roots: Vec<NodeId>,
+}
+
+impl Ast {
+    /// Builds the arena of `nodes`, interning their symbols in `interner`.
+    pub fn lower(nodes: &[Spanned<TopLevelExpr<'_>>], interner: &mut Interner) -> Self {
+        let mut lowering = Lowering {
+            nodes: Vec::new(),
+            interner,
+        };
+        let roots = nodes
+            .iter()
+            .map(|node| lowering.top_level_expr(node))
+            .collect();
+
+        Self {
+            nodes: lowering.nodes,
+            roots,
+        }
+    }
+
+    /// Returns the top-level forms.
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Spanned<Node>> {
+        self.nodes.get(id.0 as usize)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &Spanned<Node>)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (NodeId(idx as u32), node))
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl Index<NodeId> for Ast {
+    type Output = Spanned<Node>;
+
+    fn index(&self, id: NodeId) -> &Self::Output {
+        &self.nodes[id.0 as usize]
+    }
+}
+
+struct Lowering<'a> {
+    nodes: Vec<Spanned<Node>>,
+    interner: &'a mut Interner,
+}
+
+impl Lowering<'_> {
+    fn push(&mut self, l: usize, node: Node, r: usize) -> NodeId {
+        let id = NodeId(u32::try_from(self.nodes.len()).expect("more than u32::MAX nodes"));
+        self.nodes.push(Spanned(l, node, r));
+        id
+    }
+
+    fn symbol(&mut self, symbol: &Spanned<Symbol<'_>>) -> Spanned<SymbolId> {
+        Spanned(symbol.0, self.interner.intern(&symbol.1 .0), symbol.2)
+    }
+
+    fn arg(&mut self, name: &Spanned<Symbol<'_>>, value: NodeId) -> NodeId {
+        let name = self.symbol(name);
+        let r = self[value].2;
+        self.push(name.0, Node::Arg { name, value }, r)
+    }
+
+    fn top_level_expr(&mut self, node: &Spanned<TopLevelExpr<'_>>) -> NodeId {
+        let Spanned(l, node, r) = node;
+        let node = match node {
+            TopLevelExpr::DefListen(def_listen) => Node::DefListen {
+                name: self.symbol(&def_listen.name),
+                args: def_listen
+                    .args
+                    .iter()
+                    .map(|arg| {
+                        let DefListenArgValue::Atom(value) = &arg.value;
+                        let value = self.atom(value);
+                        self.arg(&arg.name, value)
+                    })
+                    .collect(),
+                script: def_listen.script.clone().map(String::from),
+            },
+            TopLevelExpr::DefPoll(def_poll) => Node::DefPoll {
+                name: self.symbol(&def_poll.name),
+                args: def_poll
+                    .args
+                    .iter()
+                    .map(|arg| {
+                        let DefPollArgValue::Atom(value) = &arg.value;
+                        let value = self.atom(value);
+                        self.arg(&arg.name, value)
+                    })
+                    .collect(),
+                script: def_poll.script.clone().map(String::from),
+            },
+            TopLevelExpr::DefVar(def_var) => {
+                let DefVarValue::Atom(value) = &def_var.value;
+                Node::DefVar {
+                    name: self.symbol(&def_var.name),
+                    value: self.atom(value),
+                }
+            }
+            TopLevelExpr::DefWidget(def_widget) => Node::DefWidget {
+                name: self.symbol(&def_widget.name),
+                params: self.params(&def_widget.params),
+                children: self.widget_nodes(&def_widget.children, (*l, *r)),
+            },
+            TopLevelExpr::DefWindow(def_window) => Node::DefWindow {
+                name: self.symbol(&def_window.name),
+                params: self.params(&def_window.params),
+                args: def_window
+                    .args
+                    .iter()
+                    .map(|arg| {
+                        let value = match &arg.value {
+                            DefWindowArgValue::Atom(value) => self.atom(value),
+                            DefWindowArgValue::Symbol(value) => self.symbol_node(value),
+                            DefWindowArgValue::WidgetCall(value) => self.widget_call(value),
+                        };
+                        self.arg(&arg.name, value)
+                    })
+                    .collect(),
+                content: self.widget_nodes(&def_window.content, (*l, *r)),
+            },
+            TopLevelExpr::Err => Node::Err,
+            TopLevelExpr::Include(include) => Node::Include {
+                path: include.path.clone().map(String::from),
+            },
+            TopLevelExpr::Literal(literal) => Node::Literal {
+                args: literal
+                    .args
+                    .iter()
+                    .map(|arg| {
+                        let value = match &arg.value {
+                            LiteralArgValue::String(value) => {
+                                self.push(value.0, Node::StrLit(value.1.to_string()), value.2)
+                            }
+                            LiteralArgValue::Symbol(value) => self.symbol_node(value),
+                        };
+                        self.arg(&arg.name, value)
+                    })
+                    .collect(),
+            },
+        };
+
+        self.push(*l, node, *r)
+    }
+
+    fn params(&mut self, params: &[Spanned<DefWidgetParam<'_>>]) -> Vec<NodeId> {
+        params
+            .iter()
+            .map(|Spanned(l, param, r)| {
+                let node = Node::Param {
+                    name: self.symbol(&param.name),
+                    is_optional: param.is_optional,
+                };
+                self.push(*l, node, *r)
+            })
+            .collect()
+    }
+
+    fn widget_nodes(&mut self, nodes: &[WidgetNode<'_>], parent: (usize, usize)) -> Vec<NodeId> {
+        nodes
+            .iter()
+            .map(|node| match node {
+                WidgetNode::Atom(atom) => self.atom(atom),
+                WidgetNode::Children(Spanned(l, children, r)) => self.push(
+                    *l,
+                    Node::Children {
+                        nth: children.nth.clone(),
+                    },
+                    *r,
+                ),
+                WidgetNode::Err => self.push(parent.0, Node::Err, parent.1),
+                WidgetNode::Expr(expr) => self.expr(expr),
+                WidgetNode::ForLoop(for_loop) => self.for_loop(for_loop),
+                WidgetNode::WidgetCall(widget_call) => self.widget_call(widget_call),
+            })
+            .collect()
+    }
+
+    fn widget_call(&mut self, node: &Spanned<WidgetCall<'_>>) -> NodeId {
+        let Spanned(l, widget_call, r) = node;
+        let node = Node::WidgetCall {
+            name: self.symbol(&widget_call.name),
+            args: widget_call
+                .args
+                .iter()
+                .map(|arg| {
+                    let value = match &arg.value {
+                        WidgetCallArgValue::Atom(value) => self.atom(value),
+                        WidgetCallArgValue::Expr(value) => self.expr(value),
+                        WidgetCallArgValue::Symbol(value) => self.symbol_node(value),
+                    };
+                    self.arg(&arg.name, value)
+                })
+                .collect(),
+            children: self.widget_nodes(&widget_call.children, (*l, *r)),
+        };
+
+        self.push(*l, node, *r)
+    }
+
+    fn for_loop(&mut self, node: &Spanned<ForLoop<'_>>) -> NodeId {
+        let Spanned(l, for_loop, r) = node;
+        let node = Node::ForLoop {
+            binding: self.symbol(&for_loop.binding),
+            iterable: self.expr(&for_loop.iterable),
+            body: self.widget_nodes(&for_loop.body, (*l, *r)),
+        };
+
+        self.push(*l, node, *r)
+    }
+
+    fn expr(&mut self, node: &Spanned<PrimitiveExpr<'_>>) -> NodeId {
+        let Spanned(l, expr, r) = node;
+        let node = match expr {
+            PrimitiveExpr::Array(array) => Node::Array {
+                items: array.items.iter().map(|item| self.expr(item)).collect(),
+            },
+            PrimitiveExpr::Atom(atom) => self.atom_node(atom),
+            PrimitiveExpr::BinaryOperation(operation) => Node::BinaryOperation {
+                lhs: self.expr(&operation.lhs),
+                op: operation.op.clone(),
+                rhs: self.expr(&operation.rhs),
+            },
+            PrimitiveExpr::ComparisonOperation(operation) => Node::ComparisonOperation {
+                lhs: self.expr(&operation.lhs),
+                op: operation.op.clone(),
+                rhs: self.expr(&operation.rhs),
+            },
+            PrimitiveExpr::FunctionCall(function_call) => Node::FunctionCall {
+                name: self.symbol(&function_call.name),
+                args: function_call
+                    .args
+                    .iter()
+                    .map(|arg| self.expr(arg))
+                    .collect(),
+            },
+            PrimitiveExpr::Object(object) => Node::Object {
+                entries: object
+                    .entries
+                    .iter()
+                    .map(|entry| (entry.key.clone().map(String::from), self.expr(&entry.value)))
+                    .collect(),
+            },
+            PrimitiveExpr::Symbol(symbol) => Node::Symbol(self.interner.intern(&symbol.0)),
+            PrimitiveExpr::TernaryOperation(operation) => Node::TernaryOperation {
+                cond: self.expr(&operation.cond),
+                true_branch: self.expr(&operation.true_branch),
+                false_branch: self.expr(&operation.false_branch),
+            },
+            PrimitiveExpr::UnaryOperation(UnaryOperation::Not(value)) => {
+                Node::Not(self.expr(value))
+            }
+        };
+
+        self.push(*l, node, *r)
+    }
+
+    fn atom(&mut self, node: &Spanned<Atom<'_>>) -> NodeId {
+        let node_kind = self.atom_node(&node.1);
+        self.push(node.0, node_kind, node.2)
+    }
+
+    fn atom_node(&mut self, atom: &Atom<'_>) -> Node {
+        match atom {
+            Atom::Bool(value) => Node::Bool(*value),
+            Atom::Number(value) => Node::Number(value.to_string()),
+            Atom::StrLit(value) => Node::StrLit(value.to_string()),
+            Atom::Symbol(symbol) => Node::Symbol(self.interner.intern(&symbol.0)),
+        }
+    }
+
+    fn symbol_node(&mut self, node: &Spanned<Symbol<'_>>) -> NodeId {
+        let id = self.interner.intern(&node.1 .0);
+        self.push(node.0, Node::Symbol(id), node.2)
+    }
+}
+
+impl Index<NodeId> for Lowering<'_> {
+    type Output = Spanned<Node>;
+
+    fn index(&self, id: NodeId) -> &Self::Output {
+        &self.nodes[id.0 as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::workspace::SourceFile;
+
+    #[test]
+    fn def_var() {
+        let mut interner = Interner::new();
+        let ast = test("(defvar name 1)", &mut interner);
+
+        let name = interner.get("name").unwrap();
+        assert_eq!(ast.roots(), &[NodeId(1)]);
+        assert_eq!(
+            ast.iter().map(|(_, node)| node.clone()).collect::<Vec<_>>(),
+            vec![
+                Spanned(13, Node::Number("1".to_owned()), 14),
+                Spanned(
+                    0,
+                    Node::DefVar {
+                        name: Spanned(8, name, 12),
+                        value: NodeId(0),
+                    },
+                    15
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn symbols_are_interned() {
+        let mut interner = Interner::new();
+        let ast = test(
+            r#"(defvar volume 50)
+(defwidget bar [muted]
+  (box :visible muted
+    (label :text volume)
+    {muted ? "" : volume == 0 ? "0" : round(volume, 0)}))"#,
+            &mut interner,
+        );
+
+        let volume = interner.get("volume").unwrap();
+        let references = ast
+            .iter()
+            .filter(|(_, node)| node.1 == Node::Symbol(volume))
+            .count();
+        assert_eq!(references, 3);
+
+        let Node::DefVar { name, .. } = &ast[ast.roots()[0]].1 else {
+            panic!("expected a defvar");
+        };
+        assert_eq!(name.1, volume);
+    }
+
+    #[test]
+    fn children_come_before_parents() {
+        let mut interner = Interner::new();
+        let ast = test(
+            r#"(defwindow main [screen] :monitor screen :geometry (geometry :width "100%")
+  (bar :items items))
+(defwidget bar [?items]
+  (box
+    (for item in items (label :text item))
+    {[1, {"a": !muted}]}
+    (children :nth 0)))
+(literal :content "(box)")"#,
+            &mut interner,
+        );
+
+        let mut reached = vec![false; ast.len()];
+        let mut pending = ast.roots().to_vec();
+        while let Some(id) = pending.pop() {
+            assert!(!reached[id.0 as usize], "{id:?} has several parents");
+            reached[id.0 as usize] = true;
+
+            for child in ast[id].1.children() {
+                assert!(child < id);
+                assert!(ast[id].0 <= ast[child].0 && ast[child].2 <= ast[id].2);
+                pending.push(child);
+            }
+        }
+        assert!(reached.iter().all(|reached| *reached));
+    }
+
+    #[test]
+    fn files_share_the_interner() {
+        let mut interner = Interner::new();
+        let vars = test("(defvar volume 50)", &mut interner);
+        let bar = test("(defwidget bar [] (label :text volume))", &mut interner);
+
+        let Node::DefVar { name, .. } = &vars[vars.roots()[0]].1 else {
+            panic!("expected a defvar");
+        };
+        assert!(bar.iter().any(|(_, node)| node.1 == Node::Symbol(name.1)));
+        assert_eq!(interner.len(), 4);
+    }
+
+    fn test(inp: &str, interner: &mut Interner) -> Ast {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let file = SourceFile::parse(inp);
+        assert_eq!(file.diagnostics, Vec::new());
+        Ast::lower(&file.ast, interner)
+    }
+}
@@ -0,0 +1,78 @@
+use std::{collections::HashMap, rc::Rc};
+
+/// A handle to an interned symbol. Two ids of the same [`Interner`] are equal if and only if
+/// their strings are equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(u32);
+
+/// Stores every distinct symbol once and hands out [`SymbolId`]s for them. An interner can be
+/// shared between the arenas of several files, so that their ids are comparable.
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    ids: HashMap<Rc<str>, SymbolId>,
+    strings: Vec<Rc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `symbol`, adding it to the interner if it is new.
+    pub fn intern(&mut self, symbol: &str) -> SymbolId {
+        if let Some(id) = self.ids.get(symbol) {
+            return *id;
+        }
+
+        let id = SymbolId(
+            u32::try_from(self.strings.len()).expect("more than u32::MAX distinct symbols"),
+        );
+        let symbol: Rc<str> = symbol.into();
+        self.strings.push(symbol.clone());
+        self.ids.insert(symbol, id);
+        id
+    }
+
+    /// Returns the id of `symbol` without adding it, `None` if it was never interned.
+    pub fn get(&self, symbol: &str) -> Option<SymbolId> {
+        self.ids.get(symbol).copied()
+    }
+
+    /// Returns the string of `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was handed out by another interner.
+    pub fn resolve(&self, id: SymbolId) -> &str {
+        &self.strings[id.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn same_string_same_id() {
+        let mut interner = Interner::new();
+        let volume = interner.intern("volume");
+        let muted = interner.intern("muted");
+
+        assert_eq!(interner.intern("volume"), volume);
+        assert_ne!(volume, muted);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(muted), "muted");
+        assert_eq!(interner.get("muted"), Some(muted));
+        assert_eq!(interner.get("time"), None);
+    }
+}
@@ -1,5 +1,14 @@
+//! The syntax tree of `.yuck` files.
+//!
+//! With the `serde` feature, every node implements `Serialize` and `Deserialize`. In JSON,
+//! [`Spanned`](crate::spanned::Spanned) nodes are objects `{"start": 0, "end": 1, "node": ...}`
+//! with byte offsets, structs are objects with their field names, enums with data are tagged
+//! as `{"type": "Variant", "value": ...}` and enums without data are strings.
+
+pub mod array;
 pub mod atom;
 pub mod binary_operation;
+pub mod children;
 pub mod comparison_operation;
 pub mod def_listen;
 pub mod def_poll;
@@ -7,15 +16,99 @@ pub mod def_var;
 pub mod def_widget;
 pub mod def_window;
 pub mod expr;
+pub mod for_loop;
 pub mod function_call;
 pub mod include;
+pub mod into_owned;
+pub mod json;
 pub mod literal;
+pub mod lookup;
+pub mod object;
+pub mod scope;
+pub mod shift_spans;
 pub mod symbol;
 pub mod ternary_operation;
 pub mod top_level_expr;
 pub mod unary_operation;
+pub mod visitor;
+pub mod visitor_mut;
 pub mod widget_call;
+pub mod widget_node;
 
 mod parse_error;
 
+pub use into_owned::IntoOwned;
 pub use parse_error::*;
+pub use shift_spans::ShiftSpans;
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use expect_test::expect_file;
+    use pretty_assertions::assert_eq;
+
+    use crate::{spanned::Spanned, workspace::SourceFile};
+
+    use super::top_level_expr::TopLevelExpr;
+
+    /// The example config of the README.
+    const SOURCE: &str = r#"
+(include "pollers.yuck")
+(include "revealer.yuck")
+
+(defwidget bar []
+  (centerbox :orientation "h"
+    (box :orientation "h" :space-evenly false (workspaces) )
+    (box :orientation "h" :space-evenly false (label :text " ${time} - ") (weather) )
+    (sidestuff)
+  )
+)
+
+(defwidget bottombar []
+  (centerbox :orientation "h"
+    (box :halign "start" :orientation "h" :space-evenly false (workspaces))
+    (box :halign "end" :orientation "h" :space-evenly false)
+    (box :orientation "h" :halign "end" :space-evenly false
+      (network)
+    )
+  )
+)"#;
+
+    #[test]
+    fn json_snapshot() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let file = SourceFile::parse(SOURCE);
+        assert_eq!(file.diagnostics, Vec::new());
+
+        let json = serde_json::to_string_pretty(&file.ast).unwrap();
+        expect_file!["ast/snapshots/readme_example.json"].assert_eq(&json);
+
+        let ast: Vec<Spanned<TopLevelExpr>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(ast, file.ast);
+    }
+
+    #[test]
+    fn round_trip() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let file = SourceFile::parse(
+            r#"(defvar items "[1, 2, 3]")
+(defpoll time :interval "1s" "date")
+(deflisten music "playerctl")
+(defwindow main [screen] :monitor screen :geometry (geometry :width "100%")
+  (bar :items items))
+(defwidget bar [?items]
+  (box
+    (for item in items (label :text item))
+    {round(volume, 0) >= 50 ? [1, 2] : {"a": !muted}}
+    {muted || a * 2 - 1 != 0}
+    (children :nth 0)))
+(literal :content "(box)")"#,
+        );
+        assert_eq!(file.diagnostics, Vec::new());
+
+        let json = serde_json::to_string(&file.ast).unwrap();
+        let ast: Vec<Spanned<TopLevelExpr>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(ast, file.ast);
+    }
+}
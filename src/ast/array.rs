@@ -0,0 +1,144 @@
+use crate::spanned::Spanned;
+
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Array<'inp> {
+    pub items: Vec<Spanned<PrimitiveExpr<'inp>>>,
+}
+
+impl<'inp> Array<'inp> {
+    pub fn new(items: Vec<impl Into<Spanned<PrimitiveExpr<'inp>>>>) -> Self {
+        Self {
+            items: items.into_iter().map(|a| a.into()).collect(),
+        }
+    }
+}
+
+impl IntoOwned for Array<'_> {
+    type Owned = Array<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Array {
+            items: self.items.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for Array<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { items } = self;
+        items.shift_spans(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        ast::{atom::Atom, symbol::Symbol, ParseError},
+        lexer::{Lexer, LexicalError},
+        spanned::Spanned,
+    };
+
+    #[test]
+    fn empty() {
+        let (errs, ast) = test("[]");
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                Array::new(Vec::<Spanned<PrimitiveExpr>>::new()).into(),
+                2
+            )
+                .into())
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let (errs, ast) = test("[1, 2, 3]");
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                Array::new(vec![
+                    (1, Atom::new_number("1").into(), 2),
+                    (4, Atom::new_number("2").into(), 5),
+                    (7, Atom::new_number("3").into(), 8),
+                ])
+                .into(),
+                9
+            )
+                .into())
+        );
+    }
+
+    #[test]
+    fn trailing_comma() {
+        let (errs, ast) = test(r#"["a", "b",]"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                Array::new(vec![(1, "a".into(), 4), (6, "b".into(), 9)]).into(),
+                11
+            )
+                .into())
+        );
+    }
+
+    #[test]
+    fn nested_and_symbols() {
+        let (errs, ast) = test(r#"[[volume], true]"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                Array::new(vec![
+                    (
+                        1,
+                        Array::new(vec![(2, Symbol::new("volume").into(), 8)]).into(),
+                        9
+                    ),
+                    (11, true.into(), 15),
+                ])
+                .into(),
+                16
+            )
+                .into())
+        );
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
+    ) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::BinaryOperationParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).map_err(|e| match e {
+            lalrpop_util::ParseError::User { error } => error,
+            e => unimplemented!("this error should not happen: {:?}", e),
+        });
+
+        (errors, result)
+    }
+}
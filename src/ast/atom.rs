@@ -1,35 +1,88 @@
-use super::symbol::Symbol;
+use std::borrow::Cow;
+
+use crate::{
+    lexer::{Lexer, Mode, Tok},
+    spanned::Spanned,
+};
+
+use super::{into_owned::IntoOwned, shift_spans::ShiftSpans, symbol::Symbol};
 
 // TODO: Adjust naming, e.g.: "LiteralNumber", "LiteralString", etc.
-// TODO: Use &'inp str instead of String
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum Atom {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum Atom<'inp> {
     Bool(bool),
-    Number(String),
-    StrLit(String),
-    Symbol(Symbol),
+    Number(Cow<'inp, str>),
+    StrLit(Cow<'inp, str>),
+    Symbol(Symbol<'inp>),
 }
 
-impl Atom {
-    pub fn new_number(value: impl Into<String>) -> Self {
+impl<'inp> Atom<'inp> {
+    pub fn new_number(value: impl Into<Cow<'inp, str>>) -> Self {
         Self::Number(value.into())
     }
 }
 
-impl From<&str> for Atom {
-    fn from(value: &str) -> Self {
+/// Returns the variables referenced in the `${}` interpolations of a string literal, e.g. `volume`
+/// in `"${volume}%"`. Function names are skipped. `offset` is the position of `literal` in the
+/// source, the spans are relative to the source.
+pub fn interpolated_symbols(literal: &str, offset: usize) -> Vec<Spanned<&str>> {
+    let mut symbols = Vec::new();
+
+    let mut rest = 0;
+    while let Some(start) = literal[rest..].find("${").map(|idx| rest + idx + 2) {
+        let Some(end) = literal[start..].find('}').map(|idx| start + idx) else {
+            break;
+        };
+        rest = end;
+
+        let tokens: Vec<_> = Lexer::with_mode(&literal[start..end], Mode::Expr)
+            .map_while(Result::ok)
+            .collect();
+        for (idx, (l, tok, r)) in tokens.iter().enumerate() {
+            let is_function = matches!(tokens.get(idx + 1), Some((_, Tok::DelimiterLeftParen, _)));
+            if let (Tok::LiteralSymbol(name), false) = (tok, is_function) {
+                let offset = offset + start;
+                symbols.push((offset + l, *name, offset + r).into());
+            }
+        }
+    }
+
+    symbols
+}
+
+impl<'inp> From<&'inp str> for Atom<'inp> {
+    fn from(value: &'inp str) -> Self {
         Atom::StrLit(value.into())
     }
 }
 
-impl From<bool> for Atom {
+impl<'inp> From<bool> for Atom<'inp> {
     fn from(value: bool) -> Self {
         Atom::Bool(value)
     }
 }
 
-impl From<Symbol> for Atom {
-    fn from(value: Symbol) -> Self {
+impl<'inp> From<Symbol<'inp>> for Atom<'inp> {
+    fn from(value: Symbol<'inp>) -> Self {
         Self::Symbol(value)
     }
 }
+
+impl IntoOwned for Atom<'_> {
+    type Owned = Atom<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Bool(value) => Atom::Bool(value),
+            Self::Number(value) => Atom::Number(IntoOwned::into_owned(value)),
+            Self::StrLit(value) => Atom::StrLit(IntoOwned::into_owned(value)),
+            Self::Symbol(value) => Atom::Symbol(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for Atom<'_> {
+    fn shift_spans(&mut self, _delta: isize) {}
+}
@@ -1,8 +1,9 @@
 use crate::spanned::Spanned;
 
-use super::expr::PrimitiveExpr;
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans};
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOperator {
     And,
     Or,
@@ -14,18 +15,19 @@ pub enum BinaryOperator {
     Sub,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct BinaryOperation {
-    pub lhs: Box<Spanned<PrimitiveExpr>>,
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BinaryOperation<'inp> {
+    pub lhs: Box<Spanned<PrimitiveExpr<'inp>>>,
     pub op: BinaryOperator,
-    pub rhs: Box<Spanned<PrimitiveExpr>>,
+    pub rhs: Box<Spanned<PrimitiveExpr<'inp>>>,
 }
 
-impl BinaryOperation {
+impl<'inp> BinaryOperation<'inp> {
     pub fn new(
-        lhs: impl Into<Spanned<PrimitiveExpr>>,
+        lhs: impl Into<Spanned<PrimitiveExpr<'inp>>>,
         op: BinaryOperator,
-        rhs: impl Into<Spanned<PrimitiveExpr>>,
+        rhs: impl Into<Spanned<PrimitiveExpr<'inp>>>,
     ) -> Self {
         Self {
             lhs: Box::new(lhs.into()),
@@ -35,6 +37,26 @@ impl BinaryOperation {
     }
 }
 
+impl IntoOwned for BinaryOperation<'_> {
+    type Owned = BinaryOperation<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        BinaryOperation {
+            lhs: self.lhs.into_owned(),
+            op: self.op,
+            rhs: self.rhs.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for BinaryOperation<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { lhs, op: _, rhs } = self;
+        lhs.shift_spans(delta);
+        rhs.shift_spans(delta);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,7 +127,11 @@ mod tests {
                             BinaryOperation::new(
                                 (9, true.into(), 13),
                                 BinaryOperator::And,
-                                (0, UnaryOperation::new_not(false).into(), 0),
+                                (
+                                    17,
+                                    UnaryOperation::new_not((18, false.into(), 23)).into(),
+                                    23
+                                ),
                             )
                             .into(),
                             23
@@ -279,7 +305,7 @@ mod tests {
         inp: &str,
     ) -> (
         Vec<ParseError>,
-        Result<Spanned<PrimitiveExpr>, LexicalError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
     ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
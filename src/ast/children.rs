@@ -0,0 +1,288 @@
+use crate::spanned::Spanned;
+
+use super::{
+    atom::Atom,
+    shift_spans::ShiftSpans,
+    widget_call::{WidgetCall, WidgetCallArgValue},
+    widget_node::WidgetNode,
+    ParseError,
+};
+
+/// `(children)` or `(children :nth 1)`, splices the children passed by the caller of a
+/// `defwidget` into its body.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Children {
+    pub keyword: Spanned<()>,
+    pub nth: Option<Spanned<usize>>,
+}
+
+impl Children {
+    pub fn new(keyword: impl Into<Spanned<()>>, nth: Option<Spanned<usize>>) -> Self {
+        Self {
+            keyword: keyword.into(),
+            nth,
+        }
+    }
+
+    /// Interprets a call to the `children` widget as a placeholder. Calls to any other widget are
+    /// handed back unchanged. `Ok(None)` is returned if the placeholder is malformed, in which
+    /// case the errors have been pushed to `errors`.
+    pub(crate) fn from_widget_call<'inp>(
+        widget_call: Spanned<WidgetCall<'inp>>,
+        errors: &mut Vec<ParseError>,
+    ) -> Result<Option<Spanned<Children>>, Spanned<WidgetCall<'inp>>> {
+        if widget_call.1.name.1 .0 != "children" {
+            return Err(widget_call);
+        }
+
+        let Spanned(
+            l,
+            WidgetCall {
+                name,
+                args,
+                children,
+            },
+            r,
+        ) = widget_call;
+        let mut nth = None;
+        let mut is_valid = true;
+
+        for arg in args {
+            if arg.name.1 .0 != "nth" {
+                errors.push(ParseError::UnexpectedChildrenArg {
+                    err_span: (arg.name.0, arg.name.2 - arg.name.0).into(),
+                });
+                is_valid = false;
+                continue;
+            }
+
+            match arg.value {
+                WidgetCallArgValue::Atom(Spanned(l_val, Atom::Number(value), r_val)) => {
+                    match value.parse() {
+                        Ok(value) => nth = Some((l_val, value, r_val).into()),
+                        Err(_) => {
+                            errors.push(ParseError::InvalidChildrenNth {
+                                err_span: (l_val, r_val - l_val).into(),
+                            });
+                            is_valid = false;
+                        }
+                    }
+                }
+                WidgetCallArgValue::Atom(Spanned(l_val, _, r_val))
+                | WidgetCallArgValue::Expr(Spanned(l_val, _, r_val))
+                | WidgetCallArgValue::Symbol(Spanned(l_val, _, r_val)) => {
+                    errors.push(ParseError::InvalidChildrenNth {
+                        err_span: (l_val, r_val - l_val).into(),
+                    });
+                    is_valid = false;
+                }
+            }
+        }
+
+        if !children.is_empty() {
+            // Nodes that failed to parse have no span, so the whole call is reported if none of
+            // the nested nodes has one.
+            let spans = children.iter().filter_map(node_span);
+            let l_nodes = spans.clone().map(|(l, _)| l).min().unwrap_or(l);
+            let r_nodes = spans.map(|(_, r)| r).max().unwrap_or(r);
+            errors.push(ParseError::UnexpectedChildrenNodes {
+                err_span: (l_nodes, r_nodes - l_nodes).into(),
+            });
+            is_valid = false;
+        }
+
+        if !is_valid {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            (l, Children::new((name.0, (), name.2), nth), r).into(),
+        ))
+    }
+}
+
+/// Reports every placeholder in `nodes`, which must not be the body of a `defwidget`: there are no
+/// children to splice in anywhere else.
+pub(crate) fn validate_outside_def_widget(nodes: &[WidgetNode<'_>], errors: &mut Vec<ParseError>) {
+    let mut placeholders = Vec::new();
+    collect_placeholders(nodes, &mut placeholders);
+
+    for Spanned(l, _, r) in placeholders {
+        errors.push(ParseError::ChildrenOutsideDefWidget {
+            err_span: (*l, r - l).into(),
+        });
+    }
+}
+
+/// Collects the `(children)` placeholders in `nodes`, including those nested in widget calls and
+/// `for` loops.
+pub(crate) fn collect_placeholders<'a>(
+    nodes: &'a [WidgetNode],
+    placeholders: &mut Vec<&'a Spanned<Children>>,
+) {
+    for node in nodes {
+        match node {
+            WidgetNode::Atom(_) | WidgetNode::Err | WidgetNode::Expr(_) => {}
+            WidgetNode::Children(children) => placeholders.push(children),
+            WidgetNode::ForLoop(Spanned(_, for_loop, _)) => {
+                collect_placeholders(&for_loop.body, placeholders)
+            }
+            WidgetNode::WidgetCall(Spanned(_, widget_call, _)) => {
+                collect_placeholders(&widget_call.children, placeholders)
+            }
+        }
+    }
+}
+
+fn node_span(node: &WidgetNode) -> Option<(usize, usize)> {
+    match node {
+        WidgetNode::Atom(Spanned(l, _, r))
+        | WidgetNode::Children(Spanned(l, _, r))
+        | WidgetNode::Expr(Spanned(l, _, r))
+        | WidgetNode::ForLoop(Spanned(l, _, r))
+        | WidgetNode::WidgetCall(Spanned(l, _, r)) => Some((*l, *r)),
+        WidgetNode::Err => None,
+    }
+}
+
+impl ShiftSpans for Children {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { keyword, nth } = self;
+        keyword.shift_spans(delta);
+        nth.shift_spans(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        ast::widget_node::WidgetNode,
+        lexer::{Lexer, LexicalError},
+    };
+
+    #[test]
+    fn all() {
+        let (errs, ast) = test(r#"(box (children))"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok(vec![Spanned::from((
+                5,
+                Children::new((6, (), 14), None),
+                15
+            ))
+            .into()])
+        );
+    }
+
+    #[test]
+    fn nth() {
+        let (errs, ast) = test(r#"(box (children :nth 1))"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok(vec![Spanned::from((
+                5,
+                Children::new((6, (), 14), Some((20, 1, 21).into())),
+                22
+            ))
+            .into()])
+        );
+    }
+
+    #[test]
+    fn other_widgets_are_untouched() {
+        let (errs, ast) = test(r#"(box (child))"#);
+
+        assert_eq!(errs, Vec::new());
+        assert!(matches!(ast.as_deref(), Ok([WidgetNode::WidgetCall(_)])));
+    }
+
+    #[test]
+    fn invalid_nth() {
+        let (errs, ast) = test(r#"(box (children :nth "first"))"#);
+
+        assert_eq!(ast, Ok(vec![WidgetNode::Err]));
+        expect![[r#"
+            [
+                InvalidChildrenNth {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            20,
+                        ),
+                        length: 7,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn unexpected_arg() {
+        let (errs, ast) = test(r#"(box (children :class "foo"))"#);
+
+        assert_eq!(ast, Ok(vec![WidgetNode::Err]));
+        expect![[r#"
+            [
+                UnexpectedChildrenArg {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            16,
+                        ),
+                        length: 5,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn nested_nodes() {
+        let (errs, ast) = test(r#"(box (children (label) "x"))"#);
+
+        assert_eq!(ast, Ok(vec![WidgetNode::Err]));
+        expect![[r#"
+            [
+                UnexpectedChildrenNodes {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            15,
+                        ),
+                        length: 11,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Vec<WidgetNode<'_>>, LexicalError<'_>>,
+    ) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::WidgetCallParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).map_err(|e| match e {
+            lalrpop_util::ParseError::User { error } => error,
+            e => unimplemented!("this error should not happen: {:?}", e),
+        });
+
+        (errors, result.map(|r| r.1.children))
+    }
+}
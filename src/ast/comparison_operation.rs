@@ -1,8 +1,9 @@
 use crate::spanned::Spanned;
 
-use super::expr::PrimitiveExpr;
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans};
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ComparisonOperator {
     Eq,
     Gt,
@@ -12,18 +13,19 @@ pub enum ComparisonOperator {
     Neq,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct ComparisonOperation {
-    lhs: Box<Spanned<PrimitiveExpr>>,
-    op: ComparisonOperator,
-    rhs: Box<Spanned<PrimitiveExpr>>,
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComparisonOperation<'inp> {
+    pub lhs: Box<Spanned<PrimitiveExpr<'inp>>>,
+    pub op: ComparisonOperator,
+    pub rhs: Box<Spanned<PrimitiveExpr<'inp>>>,
 }
 
-impl ComparisonOperation {
+impl<'inp> ComparisonOperation<'inp> {
     pub fn new(
-        lhs: impl Into<Spanned<PrimitiveExpr>>,
+        lhs: impl Into<Spanned<PrimitiveExpr<'inp>>>,
         op: ComparisonOperator,
-        rhs: impl Into<Spanned<PrimitiveExpr>>,
+        rhs: impl Into<Spanned<PrimitiveExpr<'inp>>>,
     ) -> Self {
         Self {
             lhs: Box::new(lhs.into()),
@@ -33,13 +35,33 @@ impl ComparisonOperation {
     }
 }
 
+impl IntoOwned for ComparisonOperation<'_> {
+    type Owned = ComparisonOperation<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        ComparisonOperation {
+            lhs: self.lhs.into_owned(),
+            op: self.op,
+            rhs: self.rhs.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for ComparisonOperation<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { lhs, op: _, rhs } = self;
+        lhs.shift_spans(delta);
+        rhs.shift_spans(delta);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::{
-        ast::{atom::Atom, function_call::FunctionCall, ParseError},
+        ast::{atom::Atom, function_call::FunctionCall, symbol::Symbol, ParseError},
         lexer::{Lexer, LexicalError},
     };
 
@@ -181,7 +203,11 @@ mod tests {
                 ComparisonOperation::new(
                     (
                         0,
-                        FunctionCall::new("strlength", vec![Atom::from("foo").into()]).into(),
+                        FunctionCall::new(
+                            (0, Symbol::new("strlength"), 9),
+                            vec![(10, Atom::from("foo").into(), 15)]
+                        )
+                        .into(),
                         16
                     ),
                     ComparisonOperator::Gt,
@@ -194,11 +220,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn symbol_operand() {
+        let (errs, ast) = test("volume > 50");
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                ComparisonOperation::new(
+                    (0, Symbol::new("volume").into(), 6),
+                    ComparisonOperator::Gt,
+                    (9, Atom::new_number("50").into(), 11),
+                )
+                .into(),
+                11
+            )
+                .into())
+        );
+    }
+
     fn test(
         inp: &str,
     ) -> (
         Vec<ParseError>,
-        Result<Spanned<PrimitiveExpr>, LexicalError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
     ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
@@ -1,21 +1,24 @@
+use std::borrow::Cow;
+
 use crate::spanned::Spanned;
 
-use super::{atom::Atom, symbol::Symbol};
+use super::{atom::Atom, into_owned::IntoOwned, shift_spans::ShiftSpans, symbol::Symbol};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefListen {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefListen<'inp> {
     pub keyword: Spanned<()>,
-    pub name: Spanned<Symbol>,
-    pub args: Vec<DefListenArg>,
-    pub script: Spanned<String>,
+    pub name: Spanned<Symbol<'inp>>,
+    pub args: Vec<DefListenArg<'inp>>,
+    pub script: Spanned<Cow<'inp, str>>,
 }
 
-impl DefListen {
+impl<'inp> DefListen<'inp> {
     pub fn new(
         keyword: impl Into<Spanned<()>>,
-        name: impl Into<Spanned<Symbol>>,
-        args: Vec<impl Into<DefListenArg>>,
-        script: impl Into<Spanned<String>>,
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        args: Vec<impl Into<DefListenArg<'inp>>>,
+        script: impl Into<Spanned<Cow<'inp, str>>>,
     ) -> Self {
         Self {
             keyword: keyword.into(),
@@ -27,13 +30,17 @@ impl DefListen {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefListenArg {
-    pub name: Spanned<Symbol>,
-    pub value: DefListenArgValue,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefListenArg<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub value: DefListenArgValue<'inp>,
 }
 
-impl DefListenArg {
-    pub fn new(name: impl Into<Spanned<Symbol>>, value: impl Into<DefListenArgValue>) -> Self {
+impl<'inp> DefListenArg<'inp> {
+    pub fn new(
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        value: impl Into<DefListenArgValue<'inp>>,
+    ) -> Self {
         Self {
             name: name.into(),
             value: value.into(),
@@ -42,16 +49,83 @@ impl DefListenArg {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum DefListenArgValue {
-    Atom(Spanned<Atom>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum DefListenArgValue<'inp> {
+    Atom(Spanned<Atom<'inp>>),
 }
 
-impl From<Spanned<Atom>> for DefListenArgValue {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for DefListenArgValue<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
+impl IntoOwned for DefListen<'_> {
+    type Owned = DefListen<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefListen {
+            keyword: self.keyword,
+            name: self.name.into_owned(),
+            args: self.args.into_owned(),
+            script: self.script.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefListenArg<'_> {
+    type Owned = DefListenArg<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefListenArg {
+            name: self.name.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefListenArgValue<'_> {
+    type Owned = DefListenArgValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => DefListenArgValue::Atom(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for DefListen<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            keyword,
+            name,
+            args,
+            script,
+        } = self;
+        keyword.shift_spans(delta);
+        name.shift_spans(delta);
+        args.shift_spans(delta);
+        script.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefListenArg<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { name, value } = self;
+        name.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefListenArgValue<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Atom(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::expect;
@@ -154,7 +228,12 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
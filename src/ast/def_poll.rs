@@ -1,23 +1,26 @@
+use std::borrow::Cow;
+
 use crate::spanned::Spanned;
 
-use super::atom::Atom;
-use super::symbol::Symbol;
+use super::{atom::Atom, into_owned::IntoOwned};
+use super::{shift_spans::ShiftSpans, symbol::Symbol};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefPoll {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefPoll<'inp> {
     pub keyword: Spanned<()>,
-    pub name: Spanned<Symbol>,
-    pub args: Vec<DefPollArg>,
-    pub script: Spanned<String>,
+    pub name: Spanned<Symbol<'inp>>,
+    pub args: Vec<DefPollArg<'inp>>,
+    pub script: Spanned<Cow<'inp, str>>,
 }
 
-impl DefPoll {
+impl<'inp> DefPoll<'inp> {
     pub fn new(
         keyword: impl Into<Spanned<()>>,
-        name: impl Into<Spanned<Symbol>>,
+        name: impl Into<Spanned<Symbol<'inp>>>,
         // TODO: Remove impl Into, don't think it's worth
-        args: Vec<impl Into<DefPollArg>>,
-        script: impl Into<Spanned<String>>,
+        args: Vec<impl Into<DefPollArg<'inp>>>,
+        script: impl Into<Spanned<Cow<'inp, str>>>,
     ) -> Self {
         Self {
             keyword: keyword.into(),
@@ -29,13 +32,17 @@ impl DefPoll {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefPollArg {
-    pub name: Spanned<Symbol>,
-    pub value: DefPollArgValue,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefPollArg<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub value: DefPollArgValue<'inp>,
 }
 
-impl DefPollArg {
-    pub fn new(name: impl Into<Spanned<Symbol>>, value: impl Into<DefPollArgValue>) -> Self {
+impl<'inp> DefPollArg<'inp> {
+    pub fn new(
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        value: impl Into<DefPollArgValue<'inp>>,
+    ) -> Self {
         Self {
             name: name.into(),
             value: value.into(),
@@ -44,16 +51,83 @@ impl DefPollArg {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum DefPollArgValue {
-    Atom(Spanned<Atom>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum DefPollArgValue<'inp> {
+    Atom(Spanned<Atom<'inp>>),
 }
 
-impl From<Spanned<Atom>> for DefPollArgValue {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for DefPollArgValue<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
+impl IntoOwned for DefPoll<'_> {
+    type Owned = DefPoll<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefPoll {
+            keyword: self.keyword,
+            name: self.name.into_owned(),
+            args: self.args.into_owned(),
+            script: self.script.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefPollArg<'_> {
+    type Owned = DefPollArg<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefPollArg {
+            name: self.name.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefPollArgValue<'_> {
+    type Owned = DefPollArgValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => DefPollArgValue::Atom(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for DefPoll<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            keyword,
+            name,
+            args,
+            script,
+        } = self;
+        keyword.shift_spans(delta);
+        name.shift_spans(delta);
+        args.shift_spans(delta);
+        script.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefPollArg<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { name, value } = self;
+        name.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefPollArgValue<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Atom(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::expect;
@@ -168,7 +242,12 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -1,20 +1,24 @@
 use crate::spanned::Spanned;
 
-use super::atom::Atom;
+use super::into_owned::IntoOwned;
+use super::json::{self, JsonValue};
 use super::symbol::Symbol;
+use super::ParseError;
+use super::{atom::Atom, shift_spans::ShiftSpans};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefVar {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefVar<'inp> {
     pub keyword: Spanned<()>,
-    pub name: Spanned<Symbol>,
-    pub value: DefVarValue,
+    pub name: Spanned<Symbol<'inp>>,
+    pub value: DefVarValue<'inp>,
 }
 
-impl DefVar {
+impl<'inp> DefVar<'inp> {
     pub fn new(
         keyword: impl Into<Spanned<()>>,
-        name: impl Into<Spanned<Symbol>>,
-        value: impl Into<DefVarValue>,
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        value: impl Into<DefVarValue<'inp>>,
     ) -> Self {
         Self {
             keyword: keyword.into(),
@@ -22,26 +26,88 @@ impl DefVar {
             value: value.into(),
         }
     }
+
+    /// Parses the value as JSON if it is a string that looks like a JSON array or object, e.g.
+    /// `(defvar items "[1, 2, 3]")`. Returns `None` for all other values.
+    pub fn json_value(&self) -> Option<Result<JsonValue, ParseError>> {
+        let DefVarValue::Atom(Spanned(l, Atom::StrLit(value), _)) = &self.value else {
+            return None;
+        };
+
+        if !json::looks_like_json(value) {
+            return None;
+        }
+
+        // The string content starts after the opening quote.
+        Some(json::parse_json(value, l + 1))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum DefVarValue {
-    Atom(Spanned<Atom>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum DefVarValue<'inp> {
+    Atom(Spanned<Atom<'inp>>),
 }
 
-impl From<Spanned<Atom>> for DefVarValue {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for DefVarValue<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
+impl IntoOwned for DefVar<'_> {
+    type Owned = DefVar<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefVar {
+            keyword: self.keyword,
+            name: self.name.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefVarValue<'_> {
+    type Owned = DefVarValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => DefVarValue::Atom(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for DefVar<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            keyword,
+            name,
+            value,
+        } = self;
+        keyword.shift_spans(delta);
+        name.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefVarValue<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Atom(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::expect;
     use pretty_assertions::assert_eq;
 
     use crate::{
-        ast::{atom::Atom, def_var::DefVar, top_level_expr::TopLevelExpr, ParseError},
+        ast::{
+            atom::Atom, def_var::DefVar, json::JsonValue, top_level_expr::TopLevelExpr, ParseError,
+        },
         lexer::{Lexer, LexicalError},
         spanned::Spanned,
     };
@@ -116,7 +182,7 @@ mod tests {
                 DefVar::new(
                     (1, (), 7),
                     (8, "foo".into(), 11),
-                    Spanned::from((12, Atom::from("some value").into(), 24))
+                    Spanned::from((12, Atom::from("some value"), 24))
                 )
                 .into(),
                 25
@@ -125,7 +191,59 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    #[test]
+    fn json_value() {
+        let (errs, ast) = test(r#"(defvar items "[1, 2, 3]")"#);
+        assert_eq!(errs, Vec::new());
+
+        let Ok(Spanned(_, TopLevelExpr::DefVar(def_var), _)) = ast else {
+            panic!("expected a defvar, got: {:?}", ast);
+        };
+
+        assert_eq!(
+            def_var.json_value(),
+            Some(Ok(JsonValue::Array(vec![
+                JsonValue::Number("1".into()),
+                JsonValue::Number("2".into()),
+                JsonValue::Number("3".into()),
+            ])))
+        );
+    }
+
+    #[test]
+    fn json_value_malformed() {
+        let (_, ast) = test(r#"(defvar items "[1, 2")"#);
+
+        let Ok(Spanned(_, TopLevelExpr::DefVar(def_var), _)) = ast else {
+            panic!("expected a defvar, got: {:?}", ast);
+        };
+
+        assert_eq!(
+            def_var.json_value(),
+            Some(Err(ParseError::MalformedJson {
+                err_span: (20, 0).into(),
+                reason: "expected ']' or ','".into(),
+            }))
+        );
+    }
+
+    #[test]
+    fn json_value_plain_string() {
+        let (_, ast) = test(r#"(defvar foo "some value")"#);
+
+        let Ok(Spanned(_, TopLevelExpr::DefVar(def_var), _)) = ast else {
+            panic!("expected a defvar, got: {:?}", ast);
+        };
+
+        assert_eq!(def_var.json_value(), None);
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -1,21 +1,31 @@
 use crate::spanned::Spanned;
 
-use super::{symbol::Symbol, widget_call::WidgetCall};
+use super::{
+    children::{self, Children},
+    into_owned::IntoOwned,
+    scope::{self, Resolution, Scope},
+    shift_spans::ShiftSpans,
+    symbol::Symbol,
+    widget_call::WidgetCall,
+    widget_node::WidgetNode,
+    ParseError,
+};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefWidget {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefWidget<'inp> {
     pub keyword: Spanned<()>,
-    pub name: Spanned<Symbol>,
-    pub params: Vec<DefWidgetParam>,
-    pub children: Vec<DefWidgetChild>,
+    pub name: Spanned<Symbol<'inp>>,
+    pub params: Vec<Spanned<DefWidgetParam<'inp>>>,
+    pub children: Vec<WidgetNode<'inp>>,
 }
 
-impl DefWidget {
+impl<'inp> DefWidget<'inp> {
     pub fn new(
         keyword: impl Into<Spanned<()>>,
-        name: impl Into<Spanned<Symbol>>,
-        params: Vec<impl Into<DefWidgetParam>>,
-        children: Vec<impl Into<DefWidgetChild>>,
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        params: Vec<impl Into<Spanned<DefWidgetParam<'inp>>>>,
+        children: Vec<impl Into<WidgetNode<'inp>>>,
     ) -> Self {
         Self {
             keyword: keyword.into(),
@@ -24,34 +34,164 @@ impl DefWidget {
             children: children.into_iter().map(|a| a.into()).collect(),
         }
     }
+
+    /// Resolves every symbol referenced by the widget's body against its parameters. Symbols not
+    /// naming a parameter resolve to `None`.
+    pub fn resolutions(&self) -> Vec<Resolution<'_>> {
+        let mut scope = Scope::new();
+        scope.push(self.params.iter().map(|p| &p.1.name));
+
+        let mut resolutions = Vec::new();
+        scope::resolve_nodes(&self.children, &mut scope, &mut resolutions);
+
+        resolutions
+    }
+
+    /// Returns how many of the children passed by a caller end up in the widget tree, based on
+    /// the `(children)` placeholders in the body.
+    pub fn consumed_children(&self) -> ConsumedChildren {
+        let mut placeholders = Vec::new();
+        children::collect_placeholders(&self.children, &mut placeholders);
+
+        placeholders
+            .into_iter()
+            .fold(ConsumedChildren::None, |consumed, placeholder| {
+                match (consumed, &placeholder.1.nth) {
+                    (ConsumedChildren::All, _) | (_, None) => ConsumedChildren::All,
+                    (ConsumedChildren::None, Some(nth)) => ConsumedChildren::Count(nth.1 + 1),
+                    (ConsumedChildren::Count(count), Some(nth)) => {
+                        ConsumedChildren::Count(count.max(nth.1 + 1))
+                    }
+                }
+            })
+    }
+}
+
+/// How a `defwidget` uses the children passed by its callers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum ConsumedChildren {
+    /// The body contains no `(children)` placeholder.
+    None,
+    /// The body contains a `(children)` placeholder without `:nth`, so every child is used.
+    All,
+    /// Only `(children :nth n)` placeholders are used. Holds the highest index plus one.
+    Count(usize),
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefWidgetParam {
-    pub name: Spanned<Symbol>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefWidgetParam<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
     pub is_optional: bool,
 }
 
-impl DefWidgetParam {
-    pub fn new(name: impl Into<Spanned<Symbol>>, is_optional: bool) -> Self {
+impl<'inp> DefWidgetParam<'inp> {
+    pub fn new(name: impl Into<Spanned<Symbol<'inp>>>, is_optional: bool) -> Self {
         Self {
             name: name.into(),
             is_optional,
         }
     }
+
+    /// Reports every parameter whose name was already used by a preceding parameter.
+    pub(crate) fn validate_unique(
+        params: &[Spanned<DefWidgetParam<'inp>>],
+        errors: &mut Vec<ParseError>,
+    ) {
+        for (idx, Spanned(_, param, _)) in params.iter().enumerate() {
+            if params[..idx].iter().any(|p| p.1.name.1 == param.name.1) {
+                errors.push(ParseError::DuplicateParam {
+                    err_span: (param.name.0, param.name.2 - param.name.0).into(),
+                });
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum DefWidgetChild {
-    WidgetCall(Spanned<WidgetCall>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum DefWidgetChild<'inp> {
+    Children(Spanned<Children>),
+    Err,
+    WidgetCall(Spanned<WidgetCall<'inp>>),
 }
 
-impl From<Spanned<WidgetCall>> for DefWidgetChild {
-    fn from(value: Spanned<WidgetCall>) -> Self {
+impl<'inp> From<Spanned<Children>> for DefWidgetChild<'inp> {
+    fn from(value: Spanned<Children>) -> Self {
+        Self::Children(value)
+    }
+}
+
+impl<'inp> From<Spanned<WidgetCall<'inp>>> for DefWidgetChild<'inp> {
+    fn from(value: Spanned<WidgetCall<'inp>>) -> Self {
         Self::WidgetCall(value)
     }
 }
 
+impl IntoOwned for DefWidget<'_> {
+    type Owned = DefWidget<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefWidget {
+            keyword: self.keyword,
+            name: self.name.into_owned(),
+            params: self.params.into_owned(),
+            children: self.children.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefWidgetParam<'_> {
+    type Owned = DefWidgetParam<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefWidgetParam {
+            name: self.name.into_owned(),
+            is_optional: self.is_optional,
+        }
+    }
+}
+
+impl IntoOwned for DefWidgetChild<'_> {
+    type Owned = DefWidgetChild<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Children(value) => DefWidgetChild::Children(value),
+            Self::Err => DefWidgetChild::Err,
+            Self::WidgetCall(value) => DefWidgetChild::WidgetCall(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for DefWidget<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            keyword,
+            name,
+            params,
+            children,
+        } = self;
+        keyword.shift_spans(delta);
+        name.shift_spans(delta);
+        params.shift_spans(delta);
+        children.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefWidgetParam<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            name,
+            is_optional: _,
+        } = self;
+        name.shift_spans(delta);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::expect;
@@ -60,9 +200,10 @@ mod tests {
     use crate::{
         ast::{
             atom::Atom,
-            def_widget::{DefWidget, DefWidgetChild, DefWidgetParam},
+            def_widget::{ConsumedChildren, DefWidget, DefWidgetParam},
             top_level_expr::TopLevelExpr,
-            widget_call::{WidgetCall, WidgetCallArg, WidgetCallChild},
+            widget_call::{WidgetCall, WidgetCallArg},
+            widget_node::WidgetNode,
             ParseError,
         },
         lexer::{Lexer, LexicalError},
@@ -141,8 +282,8 @@ mod tests {
                 DefWidget::new(
                     (1, (), 10),
                     (11, "bar".into(), 14),
-                    vec![DefWidgetParam::new((16, "name".into(), 20), false)],
-                    Vec::<DefWidgetChild>::new()
+                    vec![(16, DefWidgetParam::new((16, "name".into(), 20), false), 20)],
+                    Vec::<WidgetNode>::new()
                 )
                 .into(),
                 22
@@ -163,8 +304,8 @@ mod tests {
                 DefWidget::new(
                     (1, (), 10),
                     (11, "bar".into(), 14),
-                    vec![DefWidgetParam::new((17, "name".into(), 21), true)],
-                    Vec::<DefWidgetChild>::new()
+                    vec![(16, DefWidgetParam::new((17, "name".into(), 21), true), 21)],
+                    Vec::<WidgetNode>::new()
                 )
                 .into(),
                 23
@@ -186,11 +327,11 @@ mod tests {
                     (1, (), 10),
                     (11, "bar".into(), 14),
                     vec![
-                        DefWidgetParam::new((16, "foo".into(), 19), false),
-                        DefWidgetParam::new((21, "name".into(), 25), true),
-                        DefWidgetParam::new((26, "bar".into(), 29), false),
+                        (16, DefWidgetParam::new((16, "foo".into(), 19), false), 19),
+                        (20, DefWidgetParam::new((21, "name".into(), 25), true), 25),
+                        (26, DefWidgetParam::new((26, "bar".into(), 29), false), 29),
                     ],
-                    Vec::<DefWidgetChild>::new()
+                    Vec::<WidgetNode>::new()
                 )
                 .into(),
                 31
@@ -218,7 +359,7 @@ mod tests {
                 DefWidget::new(
                     (1, (), 10),
                     (11, "bottombar".into(), 20),
-                    vec![DefWidgetParam::new((22, "width".into(), 27), false)],
+                    vec![(22, DefWidgetParam::new((22, "width".into(), 27), false), 27)],
                     vec![Spanned::from((
                         45,
                         WidgetCall::new(
@@ -246,7 +387,7 @@ mod tests {
                                                 Spanned::from((145, Atom::from(false), 150))
                                             )
                                         ],
-                                        Vec::<WidgetCallChild>::new(),
+                                        Vec::<WidgetNode>::new(),
                                     ),
                                     151
                                 ))
@@ -269,7 +410,7 @@ mod tests {
                                                 Spanned::from((222, Atom::from(false), 227))
                                             )
                                         ],
-                                        Vec::<WidgetCallChild>::new(),
+                                        Vec::<WidgetNode>::new(),
                                     ),
                                     228
                                 ))
@@ -286,7 +427,41 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    #[test]
+    fn consumed_children() {
+        for (inp, expected) in [
+            ("(defwidget foo [] (box))", ConsumedChildren::None),
+            ("(defwidget foo [] (children))", ConsumedChildren::All),
+            (
+                "(defwidget foo [] (box (children :nth 0) (children)))",
+                ConsumedChildren::All,
+            ),
+            (
+                "(defwidget foo [] (box (children :nth 2) (box (children :nth 0))))",
+                ConsumedChildren::Count(3),
+            ),
+            (
+                "(defwidget foo [] (box (for i in items (children :nth 1))))",
+                ConsumedChildren::Count(2),
+            ),
+        ] {
+            let (errs, ast) = test(inp);
+            assert_eq!(errs, Vec::new());
+
+            let Ok(Spanned(_, TopLevelExpr::DefWidget(def_widget), _)) = ast else {
+                panic!("expected a defwidget, got: {:?}", ast);
+            };
+
+            assert_eq!(def_widget.consumed_children(), expected, "input: {}", inp);
+        }
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -1,39 +1,81 @@
 use crate::spanned::Spanned;
 
-use super::{atom::Atom, symbol::Symbol, widget_call::WidgetCall};
+use super::{
+    atom::Atom,
+    def_widget::DefWidgetParam,
+    into_owned::IntoOwned,
+    scope::{self, Resolution, Scope},
+    shift_spans::ShiftSpans,
+    symbol::Symbol,
+    widget_call::WidgetCall,
+    widget_node::WidgetNode,
+};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefWindow {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefWindow<'inp> {
     pub keyword: Spanned<()>,
-    pub name: Spanned<Symbol>,
-    pub args: Vec<DefWindowArg>,
-    pub content: Vec<DefWindowContent>,
+    pub name: Spanned<Symbol<'inp>>,
+    pub params: Vec<Spanned<DefWidgetParam<'inp>>>,
+    pub args: Vec<DefWindowArg<'inp>>,
+    pub content: Vec<WidgetNode<'inp>>,
 }
 
-impl DefWindow {
+impl<'inp> DefWindow<'inp> {
     pub fn new(
         keyword: impl Into<Spanned<()>>,
-        name: impl Into<Spanned<Symbol>>,
-        args: Vec<impl Into<DefWindowArg>>,
-        content: Vec<impl Into<DefWindowContent>>,
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        params: Vec<impl Into<Spanned<DefWidgetParam<'inp>>>>,
+        args: Vec<impl Into<DefWindowArg<'inp>>>,
+        content: Vec<impl Into<WidgetNode<'inp>>>,
     ) -> Self {
         Self {
             keyword: keyword.into(),
             name: name.into(),
+            params: params.into_iter().map(|p| p.into()).collect(),
             args: args.into_iter().map(|a| a.into()).collect(),
             content: content.into_iter().map(|c| c.into()).collect(),
         }
     }
+
+    /// Resolves every symbol referenced by the window's arguments and content against its
+    /// parameters. Symbols not naming a parameter resolve to `None`.
+    pub fn resolutions(&self) -> Vec<Resolution<'_>> {
+        let mut scope = Scope::new();
+        scope.push(self.params.iter().map(|p| &p.1.name));
+
+        let mut resolutions = Vec::new();
+
+        for arg in &self.args {
+            match &arg.value {
+                DefWindowArgValue::Atom(_) => {}
+                DefWindowArgValue::Symbol(symbol) => {
+                    scope::resolve_symbol(symbol, &scope, &mut resolutions)
+                }
+                DefWindowArgValue::WidgetCall(Spanned(_, widget_call, _)) => {
+                    scope::resolve_widget_call(widget_call, &mut scope, &mut resolutions)
+                }
+            }
+        }
+
+        scope::resolve_nodes(&self.content, &mut scope, &mut resolutions);
+
+        resolutions
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct DefWindowArg {
-    pub name: Spanned<Symbol>,
-    pub value: DefWindowArgValue,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefWindowArg<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub value: DefWindowArgValue<'inp>,
 }
 
-impl DefWindowArg {
-    pub fn new(name: impl Into<Spanned<Symbol>>, value: impl Into<DefWindowArgValue>) -> Self {
+impl<'inp> DefWindowArg<'inp> {
+    pub fn new(
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        value: impl Into<DefWindowArgValue<'inp>>,
+    ) -> Self {
         Self {
             name: name.into(),
             value: value.into(),
@@ -42,42 +84,136 @@ impl DefWindowArg {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum DefWindowArgValue {
-    Atom(Spanned<Atom>),
-    WidgetCall(Spanned<WidgetCall>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum DefWindowArgValue<'inp> {
+    Atom(Spanned<Atom<'inp>>),
+    Symbol(Spanned<Symbol<'inp>>),
+    WidgetCall(Spanned<WidgetCall<'inp>>),
 }
 
-impl From<Spanned<Atom>> for DefWindowArgValue {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for DefWindowArgValue<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
-impl From<Spanned<WidgetCall>> for DefWindowArgValue {
-    fn from(value: Spanned<WidgetCall>) -> Self {
+impl<'inp> From<Spanned<Symbol<'inp>>> for DefWindowArgValue<'inp> {
+    fn from(value: Spanned<Symbol<'inp>>) -> Self {
+        Self::Symbol(value)
+    }
+}
+
+impl<'inp> From<Spanned<WidgetCall<'inp>>> for DefWindowArgValue<'inp> {
+    fn from(value: Spanned<WidgetCall<'inp>>) -> Self {
         Self::WidgetCall(value)
     }
 }
 
 // TODO: Rename to `DefWindowChild` for consistency
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum DefWindowContent {
-    Atom(Spanned<Atom>),
-    WidgetCall(Spanned<WidgetCall>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum DefWindowContent<'inp> {
+    Atom(Spanned<Atom<'inp>>),
+    WidgetCall(Spanned<WidgetCall<'inp>>),
 }
 
-impl From<Spanned<Atom>> for DefWindowContent {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for DefWindowContent<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
-impl From<Spanned<WidgetCall>> for DefWindowContent {
-    fn from(value: Spanned<WidgetCall>) -> Self {
+impl<'inp> From<Spanned<WidgetCall<'inp>>> for DefWindowContent<'inp> {
+    fn from(value: Spanned<WidgetCall<'inp>>) -> Self {
         Self::WidgetCall(value)
     }
 }
 
+impl IntoOwned for DefWindow<'_> {
+    type Owned = DefWindow<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefWindow {
+            keyword: self.keyword,
+            name: self.name.into_owned(),
+            params: self.params.into_owned(),
+            args: self.args.into_owned(),
+            content: self.content.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefWindowArg<'_> {
+    type Owned = DefWindowArg<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DefWindowArg {
+            name: self.name.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for DefWindowArgValue<'_> {
+    type Owned = DefWindowArgValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => DefWindowArgValue::Atom(value.into_owned()),
+            Self::Symbol(value) => DefWindowArgValue::Symbol(value.into_owned()),
+            Self::WidgetCall(value) => DefWindowArgValue::WidgetCall(value.into_owned()),
+        }
+    }
+}
+
+impl IntoOwned for DefWindowContent<'_> {
+    type Owned = DefWindowContent<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => DefWindowContent::Atom(value.into_owned()),
+            Self::WidgetCall(value) => DefWindowContent::WidgetCall(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for DefWindow<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            keyword,
+            name,
+            params,
+            args,
+            content,
+        } = self;
+        keyword.shift_spans(delta);
+        name.shift_spans(delta);
+        params.shift_spans(delta);
+        args.shift_spans(delta);
+        content.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefWindowArg<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { name, value } = self;
+        name.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for DefWindowArgValue<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Atom(value) => value.shift_spans(delta),
+            Self::Symbol(value) => value.shift_spans(delta),
+            Self::WidgetCall(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::expect;
@@ -85,7 +221,7 @@ mod tests {
 
     use super::*;
     use crate::ast::top_level_expr::TopLevelExpr;
-    use crate::ast::widget_call::{WidgetCallArg, WidgetCallChild};
+    use crate::ast::widget_call::WidgetCallArg;
     use crate::ast::ParseError;
     use crate::lexer::{Lexer, LexicalError};
     use crate::spanned::Spanned;
@@ -131,8 +267,9 @@ mod tests {
                 DefWindow::new(
                     (1, 10),
                     (11, "bar".into(), 14),
+                    Vec::<Spanned<DefWidgetParam>>::new(),
                     Vec::<DefWindowArg>::new(),
-                    Vec::<DefWindowContent>::new()
+                    Vec::<WidgetNode>::new()
                 )
                 .into(),
                 15
@@ -157,6 +294,7 @@ mod tests {
                 DefWindow::new(
                     (1, 10),
                     (11, "top".into(), 14),
+                    Vec::<Spanned<DefWidgetParam>>::new(),
                     vec![
                         DefWindowArg::new(
                             (32, "monitor".into(), 39),
@@ -167,7 +305,7 @@ mod tests {
                             Spanned::from((70, Atom::from("dock"), 76))
                         ),
                     ],
-                    Vec::<DefWindowContent>::new()
+                    Vec::<WidgetNode>::new()
                 )
                 .into(),
                 77
@@ -202,6 +340,7 @@ mod tests {
                 DefWindow::new(
                     (1, 10),
                     (11, "bar".into(), 14),
+                    Vec::<Spanned<DefWidgetParam>>::new(),
                     vec![DefWindowArg::new(
                         (32, "geometry".into(), 40),
                         Spanned::from((
@@ -230,12 +369,12 @@ mod tests {
                                         Spanned::from((203, Atom::from("top center"), 215))
                                     ),
                                 ],
-                                Vec::<WidgetCallChild>::new()
+                                Vec::<WidgetNode>::new()
                             ),
                             216
                         ))
                     )],
-                    Vec::<DefWindowContent>::new()
+                    Vec::<WidgetNode>::new()
                 )
                 .into(),
                 217
@@ -256,6 +395,7 @@ mod tests {
                 DefWindow::new(
                     (1, 10),
                     (11, "frame".into(), 16),
+                    Vec::<Spanned<DefWidgetParam>>::new(),
                     Vec::<DefWindowArg>::new(),
                     vec![Spanned::from((17, Atom::from("canvas"), 25))]
                 )
@@ -282,6 +422,7 @@ mod tests {
                 DefWindow::new(
                     (1, 10),
                     (11, "bar".into(), 14),
+                    Vec::<Spanned<DefWidgetParam>>::new(),
                     vec![DefWindowArg::new(
                         (32, "geometry".into(), 40),
                         Spanned::from((
@@ -292,7 +433,7 @@ mod tests {
                                     (52, "anchor".into(), 58),
                                     Spanned::from((59, Atom::from("top center"), 71))
                                 )],
-                                Vec::<WidgetCallChild>::new(),
+                                Vec::<WidgetNode>::new(),
                             ),
                             72
                         ))
@@ -302,7 +443,7 @@ mod tests {
                         WidgetCall::new(
                             (90, "bar".into(), 93),
                             Vec::<WidgetCallArg>::new(),
-                            Vec::<WidgetCallChild>::new()
+                            Vec::<WidgetNode>::new()
                         ),
                         94
                     ))],
@@ -314,7 +455,124 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    #[test]
+    fn params() {
+        let (errs, ast) = test(r#"(defwindow bar [monitor ?name] :monitor monitor)"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                DefWindow::new(
+                    (1, 10),
+                    (11, "bar".into(), 14),
+                    vec![
+                        (
+                            16,
+                            DefWidgetParam::new((16, "monitor".into(), 23), false),
+                            23
+                        ),
+                        (24, DefWidgetParam::new((25, "name".into(), 29), true), 29),
+                    ],
+                    vec![DefWindowArg::new(
+                        (32, "monitor".into(), 39),
+                        Spanned::from((40, Symbol::new("monitor"), 47))
+                    )],
+                    Vec::<WidgetNode>::new()
+                )
+                .into(),
+                48
+            )
+                .into())
+        );
+    }
+
+    #[test]
+    fn duplicate_params() {
+        let (errs, _) = test(r#"(defwindow bar [monitor ?monitor])"#);
+
+        expect![[r#"
+            [
+                DuplicateParam {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            25,
+                        ),
+                        length: 7,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn children_outside_def_widget() {
+        let (errs, _) = test(r#"(defwindow bar (box (children) (for x in xs (children :nth 0))))"#);
+
+        expect![[r#"
+            [
+                ChildrenOutsideDefWidget {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            20,
+                        ),
+                        length: 10,
+                    },
+                },
+                ChildrenOutsideDefWidget {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            44,
+                        ),
+                        length: 17,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn resolutions() {
+        let (_, ast) = test(
+            r#"(defwindow bar [monitor]
+                :monitor monitor
+                :geometry (geometry :width width)
+                (bar :screen monitor))"#,
+        );
+
+        let Ok(Spanned(_, TopLevelExpr::DefWindow(def_window), _)) = ast else {
+            panic!("expected a defwindow, got: {:?}", ast);
+        };
+        let param = &def_window.params[0].1.name;
+
+        assert_eq!(
+            def_window.resolutions(),
+            vec![
+                Resolution {
+                    reference: (50, Symbol::new("monitor"), 57).into(),
+                    binding: Some(param),
+                },
+                Resolution {
+                    reference: (101, Symbol::new("width"), 106).into(),
+                    binding: None,
+                },
+                Resolution {
+                    reference: (137, Symbol::new("monitor"), 144).into(),
+                    binding: Some(param),
+                },
+            ]
+        );
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -1,64 +1,123 @@
 use super::{
-    atom::Atom, binary_operation::BinaryOperation, comparison_operation::ComparisonOperation,
-    function_call::FunctionCall, symbol::Symbol, ternary_operation::TernaryOperation,
+    array::Array, atom::Atom, binary_operation::BinaryOperation,
+    comparison_operation::ComparisonOperation, function_call::FunctionCall, into_owned::IntoOwned,
+    object::Object, shift_spans::ShiftSpans, symbol::Symbol, ternary_operation::TernaryOperation,
     unary_operation::UnaryOperation,
 };
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub enum PrimitiveExpr {
-    Atom(Atom),
-    BinaryOperation(BinaryOperation),
-    ComparisonOperation(ComparisonOperation),
-    FunctionCall(FunctionCall),
-    Symbol(Symbol),
-    TernaryOperation(TernaryOperation),
-    UnaryOperation(UnaryOperation),
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum PrimitiveExpr<'inp> {
+    Array(Array<'inp>),
+    Atom(Atom<'inp>),
+    BinaryOperation(BinaryOperation<'inp>),
+    ComparisonOperation(ComparisonOperation<'inp>),
+    FunctionCall(FunctionCall<'inp>),
+    Object(Object<'inp>),
+    Symbol(Symbol<'inp>),
+    TernaryOperation(TernaryOperation<'inp>),
+    UnaryOperation(UnaryOperation<'inp>),
 }
 
-impl From<bool> for PrimitiveExpr {
+impl<'inp> From<bool> for PrimitiveExpr<'inp> {
     fn from(value: bool) -> Self {
         Atom::from(value).into()
     }
 }
 
-impl From<&str> for PrimitiveExpr {
-    fn from(value: &str) -> Self {
+impl<'inp> From<&'inp str> for PrimitiveExpr<'inp> {
+    fn from(value: &'inp str) -> Self {
         Self::Atom(Atom::from(value))
     }
 }
 
-impl From<Atom> for PrimitiveExpr {
-    fn from(value: Atom) -> Self {
+impl<'inp> From<Array<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: Array<'inp>) -> Self {
+        Self::Array(value)
+    }
+}
+
+impl<'inp> From<Atom<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: Atom<'inp>) -> Self {
         Self::Atom(value)
     }
 }
 
-impl From<BinaryOperation> for PrimitiveExpr {
-    fn from(value: BinaryOperation) -> Self {
+impl<'inp> From<BinaryOperation<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: BinaryOperation<'inp>) -> Self {
         Self::BinaryOperation(value)
     }
 }
 
-impl From<ComparisonOperation> for PrimitiveExpr {
-    fn from(value: ComparisonOperation) -> Self {
+impl<'inp> From<ComparisonOperation<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: ComparisonOperation<'inp>) -> Self {
         Self::ComparisonOperation(value)
     }
 }
 
-impl From<FunctionCall> for PrimitiveExpr {
-    fn from(value: FunctionCall) -> Self {
+impl<'inp> From<FunctionCall<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: FunctionCall<'inp>) -> Self {
         Self::FunctionCall(value)
     }
 }
 
-impl From<TernaryOperation> for PrimitiveExpr {
-    fn from(value: TernaryOperation) -> Self {
+impl<'inp> From<Object<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: Object<'inp>) -> Self {
+        Self::Object(value)
+    }
+}
+
+impl<'inp> From<Symbol<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: Symbol<'inp>) -> Self {
+        Self::Symbol(value)
+    }
+}
+
+impl<'inp> From<TernaryOperation<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: TernaryOperation<'inp>) -> Self {
         Self::TernaryOperation(value)
     }
 }
 
-impl From<UnaryOperation> for PrimitiveExpr {
-    fn from(value: UnaryOperation) -> Self {
+impl<'inp> From<UnaryOperation<'inp>> for PrimitiveExpr<'inp> {
+    fn from(value: UnaryOperation<'inp>) -> Self {
         Self::UnaryOperation(value)
     }
 }
+
+impl IntoOwned for PrimitiveExpr<'_> {
+    type Owned = PrimitiveExpr<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Array(value) => PrimitiveExpr::Array(value.into_owned()),
+            Self::Atom(value) => PrimitiveExpr::Atom(value.into_owned()),
+            Self::BinaryOperation(value) => PrimitiveExpr::BinaryOperation(value.into_owned()),
+            Self::ComparisonOperation(value) => {
+                PrimitiveExpr::ComparisonOperation(value.into_owned())
+            }
+            Self::FunctionCall(value) => PrimitiveExpr::FunctionCall(value.into_owned()),
+            Self::Object(value) => PrimitiveExpr::Object(value.into_owned()),
+            Self::Symbol(value) => PrimitiveExpr::Symbol(value.into_owned()),
+            Self::TernaryOperation(value) => PrimitiveExpr::TernaryOperation(value.into_owned()),
+            Self::UnaryOperation(value) => PrimitiveExpr::UnaryOperation(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for PrimitiveExpr<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Array(value) => value.shift_spans(delta),
+            Self::Atom(value) => value.shift_spans(delta),
+            Self::BinaryOperation(value) => value.shift_spans(delta),
+            Self::ComparisonOperation(value) => value.shift_spans(delta),
+            Self::FunctionCall(value) => value.shift_spans(delta),
+            Self::Object(value) => value.shift_spans(delta),
+            Self::Symbol(value) => value.shift_spans(delta),
+            Self::TernaryOperation(value) => value.shift_spans(delta),
+            Self::UnaryOperation(value) => value.shift_spans(delta),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::spanned::Spanned;
+
+use super::{
+    expr::PrimitiveExpr,
+    into_owned::IntoOwned,
+    scope::{self, Resolution, Scope},
+    shift_spans::ShiftSpans,
+    symbol::Symbol,
+    widget_node::WidgetNode,
+};
+
+/// `(for entry in items (label :text entry))`
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForLoop<'inp> {
+    pub keyword: Spanned<()>,
+    pub binding: Spanned<Symbol<'inp>>,
+    pub iterable: Spanned<PrimitiveExpr<'inp>>,
+    pub body: Vec<WidgetNode<'inp>>,
+}
+
+impl<'inp> ForLoop<'inp> {
+    pub fn new(
+        keyword: impl Into<Spanned<()>>,
+        binding: impl Into<Spanned<Symbol<'inp>>>,
+        iterable: impl Into<Spanned<PrimitiveExpr<'inp>>>,
+        body: Vec<WidgetNode<'inp>>,
+    ) -> Self {
+        Self {
+            keyword: keyword.into(),
+            binding: binding.into(),
+            iterable: iterable.into(),
+            body,
+        }
+    }
+
+    /// Returns every symbol in the body that refers to the loop's binding, including `${}`
+    /// interpolations in strings. References shadowed by a nested loop using the same name are
+    /// not included.
+    pub fn binding_references(&self) -> Vec<Spanned<Symbol<'_>>> {
+        let mut scope = Scope::new();
+        scope.push([&self.binding]);
+
+        let mut resolutions = Vec::new();
+        scope::resolve_nodes(&self.body, &mut scope, &mut resolutions);
+
+        resolutions
+            .into_iter()
+            .filter(|r| r.binding.is_some_and(|b| std::ptr::eq(b, &self.binding)))
+            .map(|Resolution { reference, .. }| reference)
+            .collect()
+    }
+}
+
+impl IntoOwned for ForLoop<'_> {
+    type Owned = ForLoop<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        ForLoop {
+            keyword: self.keyword,
+            binding: self.binding.into_owned(),
+            iterable: self.iterable.into_owned(),
+            body: self.body.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for ForLoop<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            keyword,
+            binding,
+            iterable,
+            body,
+        } = self;
+        keyword.shift_spans(delta);
+        binding.shift_spans(delta);
+        iterable.shift_spans(delta);
+        body.shift_spans(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        ast::{
+            atom::Atom,
+            widget_call::{WidgetCall, WidgetCallArg},
+            ParseError,
+        },
+        lexer::{Lexer, LexicalError},
+    };
+
+    #[test]
+    fn simple() {
+        let (errs, ast) = test(r#"(for entry in items (label :text entry))"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok(Spanned::from((
+                0,
+                ForLoop::new(
+                    (1, (), 4),
+                    (5, "entry".into(), 10),
+                    (14, Symbol::new("items").into(), 19),
+                    vec![Spanned::from((
+                        20,
+                        WidgetCall::new(
+                            (21, "label".into(), 26),
+                            vec![WidgetCallArg::new(
+                                (28, "text".into(), 32),
+                                Spanned::from((33, Symbol::new("entry"), 38))
+                            )],
+                            Vec::new(),
+                        ),
+                        39
+                    ))
+                    .into()],
+                ),
+                40
+            ))
+            .into())
+        );
+    }
+
+    #[test]
+    fn array_iterable() {
+        let (errs, ast) = test(r#"(for i in [1, 2] "item")"#);
+
+        assert_eq!(errs, Vec::new());
+        let Ok(WidgetNode::ForLoop(Spanned(_, for_loop, _))) = ast else {
+            panic!("expected a for loop, got: {:?}", ast);
+        };
+        assert_eq!(for_loop.iterable.0, 10);
+        assert_eq!(for_loop.iterable.2, 16);
+        assert_eq!(
+            for_loop.body,
+            vec![Spanned::from((17, Atom::from("item"), 23)).into()]
+        );
+    }
+
+    #[test]
+    fn binding_references() {
+        let (_, ast) = test(
+            r#"(for x in xs
+                (box :a x
+                    (for x in ys (label :text x))
+                    (label :text y :b x)))"#,
+        );
+
+        let Ok(WidgetNode::ForLoop(Spanned(_, for_loop, _))) = ast else {
+            panic!("expected a for loop, got: {:?}", ast);
+        };
+
+        assert_eq!(
+            for_loop.binding_references(),
+            vec![
+                Spanned::from((37, Symbol::new("x"), 38)),
+                Spanned::from((127, Symbol::new("x"), 128)),
+            ]
+        );
+    }
+
+    #[test]
+    fn expr_iterable() {
+        let (errs, ast) = test(r#"(for e in {items} (label :text e))"#);
+
+        assert_eq!(errs, Vec::new());
+        let Ok(WidgetNode::ForLoop(Spanned(_, for_loop, _))) = ast else {
+            panic!("expected a for loop, got: {:?}", ast);
+        };
+        assert_eq!(
+            for_loop.iterable,
+            Spanned::from((10, Symbol::new("items").into(), 17))
+        );
+        assert_eq!(
+            for_loop.binding_references(),
+            vec![Spanned::from((31, Symbol::new("e"), 32))]
+        );
+    }
+
+    #[test]
+    fn binding_references_in_strings() {
+        let (_, ast) = test(r#"(for x in xs (label :text "${x}: ${y} ${round(x, 0)}"))"#);
+
+        let Ok(WidgetNode::ForLoop(Spanned(_, for_loop, _))) = ast else {
+            panic!("expected a for loop, got: {:?}", ast);
+        };
+
+        assert_eq!(
+            for_loop.binding_references(),
+            vec![
+                Spanned::from((29, Symbol::new("x"), 30)),
+                Spanned::from((46, Symbol::new("x"), 47)),
+            ]
+        );
+    }
+
+    #[test]
+    fn binding_references_in_exprs() {
+        let (_, ast) = test(r#"(for x in xs {x * 2 > length(x) ? [x] : y})"#);
+
+        let Ok(WidgetNode::ForLoop(Spanned(_, for_loop, _))) = ast else {
+            panic!("expected a for loop, got: {:?}", ast);
+        };
+
+        assert_eq!(
+            for_loop.binding_references(),
+            vec![
+                Spanned::from((14, Symbol::new("x"), 15)),
+                Spanned::from((29, Symbol::new("x"), 30)),
+                Spanned::from((35, Symbol::new("x"), 36)),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_binding() {
+        let (errs, ast) = test(r#"(for)"#);
+
+        assert_eq!(ast, Ok(WidgetNode::Err));
+        expect![[r#"
+            [
+                ExpectedForLoopBinding {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            0,
+                        ),
+                        length: 5,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn missing_in() {
+        let (errs, ast) = test(r#"(for x items)"#);
+
+        assert_eq!(ast, Ok(WidgetNode::Err));
+        expect![[r#"
+            [
+                ExpectedForLoopIn {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            7,
+                        ),
+                        length: 5,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn missing_iterable() {
+        let (errs, ast) = test(r#"(for x in)"#);
+
+        assert_eq!(ast, Ok(WidgetNode::Err));
+        expect![[r#"
+            [
+                ExpectedForLoopIterable {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            0,
+                        ),
+                        length: 10,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    #[test]
+    fn missing_body() {
+        let (errs, ast) = test(r#"(for x in xs)"#);
+
+        assert_eq!(ast, Ok(WidgetNode::Err));
+        expect![[r#"
+            [
+                ExpectedForLoopBody {
+                    err_span: SourceSpan {
+                        offset: SourceOffset(
+                            0,
+                        ),
+                        length: 13,
+                    },
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&errs);
+    }
+
+    fn test(inp: &str) -> (Vec<ParseError>, Result<WidgetNode<'_>, LexicalError<'_>>) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::ForLoopParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).map_err(|e| match e {
+            lalrpop_util::ParseError::User { error } => error,
+            e => unimplemented!("this error should not happen: {:?}", e),
+        });
+
+        (errors, result)
+    }
+}
@@ -1,20 +1,45 @@
-use super::{expr::PrimitiveExpr, symbol::Symbol};
+use crate::spanned::Spanned;
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct FunctionCall {
-    pub name: Symbol,
-    pub args: Vec<PrimitiveExpr>,
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans, symbol::Symbol};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionCall<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub args: Vec<Spanned<PrimitiveExpr<'inp>>>,
 }
 
-impl FunctionCall {
-    pub fn new(name: impl Into<Symbol>, args: Vec<PrimitiveExpr>) -> Self {
+impl<'inp> FunctionCall<'inp> {
+    pub fn new(
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        args: Vec<impl Into<Spanned<PrimitiveExpr<'inp>>>>,
+    ) -> Self {
         Self {
             name: name.into(),
-            args,
+            args: args.into_iter().map(|a| a.into()).collect(),
+        }
+    }
+}
+
+impl IntoOwned for FunctionCall<'_> {
+    type Owned = FunctionCall<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        FunctionCall {
+            name: self.name.into_owned(),
+            args: self.args.into_owned(),
         }
     }
 }
 
+impl ShiftSpans for FunctionCall<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { name, args } = self;
+        name.shift_spans(delta);
+        args.shift_spans(delta);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -35,7 +60,11 @@ mod tests {
             ast,
             Ok((
                 0,
-                FunctionCall::new(Symbol::new("strlength"), vec!["value".into()]).into(),
+                FunctionCall::new(
+                    (0, Symbol::new("strlength"), 9),
+                    vec![(10, "value".into(), 17)]
+                )
+                .into(),
                 18
             )
                 .into())
@@ -52,11 +81,11 @@ mod tests {
             Ok((
                 0,
                 FunctionCall::new(
-                    Symbol::new("substring"),
+                    (0, Symbol::new("substring"), 9),
                     vec![
-                        "yuck".into(),
-                        Atom::new_number("1").into(),
-                        Atom::new_number("3").into()
+                        (10, "yuck".into(), 16),
+                        (18, Atom::new_number("1").into(), 19),
+                        (21, Atom::new_number("3").into(), 22)
                     ]
                 )
                 .into(),
@@ -76,8 +105,16 @@ mod tests {
             Ok((
                 0,
                 FunctionCall::new(
-                    Symbol::new("strlength"),
-                    vec![FunctionCall::new(Symbol::new("trim"), vec!["foo".into()]).into()]
+                    (0, Symbol::new("strlength"), 9),
+                    vec![(
+                        10,
+                        FunctionCall::new(
+                            (10, Symbol::new("trim"), 14),
+                            vec![(15, "foo".into(), 20)]
+                        )
+                        .into(),
+                        21
+                    )]
                 )
                 .into(),
                 22
@@ -86,11 +123,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn symbol_args() {
+        let (errs, ast) = test(r#"substring(name, 0, max)"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                FunctionCall::new(
+                    (0, Symbol::new("substring"), 9),
+                    vec![
+                        (10, Symbol::new("name").into(), 14),
+                        (16, Atom::new_number("0").into(), 17),
+                        (19, Symbol::new("max").into(), 22)
+                    ]
+                )
+                .into(),
+                23
+            )
+                .into())
+        );
+    }
+
     fn test(
         inp: &str,
     ) -> (
         Vec<ParseError>,
-        Result<Spanned<PrimitiveExpr>, LexicalError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
     ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
@@ -1,13 +1,18 @@
+use std::borrow::Cow;
+
 use crate::spanned::Spanned;
 
+use super::{into_owned::IntoOwned, shift_spans::ShiftSpans};
+
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct Include {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Include<'inp> {
     pub keyword: Spanned<()>,
-    pub path: Spanned<String>,
+    pub path: Spanned<Cow<'inp, str>>,
 }
 
-impl Include {
-    pub fn new(keyword: impl Into<Spanned<()>>, path: impl Into<Spanned<String>>) -> Self {
+impl<'inp> Include<'inp> {
+    pub fn new(keyword: impl Into<Spanned<()>>, path: impl Into<Spanned<Cow<'inp, str>>>) -> Self {
         Self {
             keyword: keyword.into(),
             path: path.into(),
@@ -15,6 +20,25 @@ impl Include {
     }
 }
 
+impl IntoOwned for Include<'_> {
+    type Owned = Include<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Include {
+            keyword: self.keyword,
+            path: self.path.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for Include<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { keyword, path } = self;
+        keyword.shift_spans(delta);
+        path.shift_spans(delta);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::expect;
@@ -87,7 +111,12 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+
+use crate::spanned::Spanned;
+
+/// Converts a node borrowing from the source into one that owns its strings, e.g. to keep the
+/// syntax tree after the source has been dropped.
+pub trait IntoOwned {
+    type Owned: 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl IntoOwned for Cow<'_, str> {
+    type Owned = Cow<'static, str>;
+
+    fn into_owned(self) -> Cow<'static, str> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Spanned<T> {
+    type Owned = Spanned<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Box::new((*self).into_owned())
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ast::{atom::Atom, def_var::DefVarValue, top_level_expr::TopLevelExpr};
+
+    #[test]
+    fn strings_borrow_from_the_source() {
+        let source = String::from(r#"(defvar name "value") (include "a.yuck")"#);
+        let ast = test(&source);
+
+        let TopLevelExpr::DefVar(def_var) = &ast[0].1 else {
+            panic!("expected a defvar: {:?}", ast[0]);
+        };
+        assert!(matches!(def_var.name.1 .0, Cow::Borrowed("name")));
+        assert!(matches!(
+            &def_var.value,
+            DefVarValue::Atom(Spanned(_, Atom::StrLit(Cow::Borrowed("value")), _))
+        ));
+
+        let TopLevelExpr::Include(include) = &ast[1].1 else {
+            panic!("expected an include: {:?}", ast[1]);
+        };
+        assert!(matches!(include.path.1, Cow::Borrowed("a.yuck")));
+    }
+
+    #[test]
+    fn owned_tree_outlives_the_source() {
+        let inp = r#"(defwidget bar [] (label :text "${a + 1}"))"#;
+        let source = String::from(inp);
+        let owned: Vec<Spanned<TopLevelExpr<'static>>> = test(&source).into_owned();
+        drop(source);
+
+        assert_eq!(owned, test(inp));
+    }
+
+    fn test(inp: &str) -> Vec<Spanned<TopLevelExpr<'_>>> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let (ast, diagnostics) = crate::parse_top_level(inp);
+        assert_eq!(diagnostics, Vec::new());
+        ast
+    }
+}
@@ -0,0 +1,353 @@
+use super::ParseError;
+
+/// Structured data parsed out of a JSON encoded string, e.g. the value of
+/// `(defvar items "[1, 2, 3]")`.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum JsonValue {
+    Array(Vec<JsonValue>),
+    Bool(bool),
+    Null,
+    Number(String),
+    Object(Vec<(String, JsonValue)>),
+    String(String),
+}
+
+/// Returns whether the value looks like it is intended to be a JSON array or object.
+pub fn looks_like_json(value: &str) -> bool {
+    let trimmed = value.trim_start();
+
+    trimmed.starts_with('[') || trimmed.starts_with('{')
+}
+
+/// Parses `inp` as JSON. `offset` is the position of `inp` in the original source and is used
+/// to calculate the spans of the reported errors.
+pub fn parse_json(inp: &str, offset: usize) -> Result<JsonValue, ParseError> {
+    let mut parser = JsonParser {
+        inp,
+        offset,
+        idx: 0,
+    };
+
+    parser.skip_whitespace();
+    let value = parser.value()?;
+    parser.skip_whitespace();
+
+    if parser.idx < inp.len() {
+        return Err(parser.err("unexpected trailing characters"));
+    }
+
+    Ok(value)
+}
+
+struct JsonParser<'inp> {
+    inp: &'inp str,
+    offset: usize,
+    idx: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self) -> Result<JsonValue, ParseError> {
+        match self.peek() {
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some('"') => self.string().map(JsonValue::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) if self.eat_keyword("true") => Ok(JsonValue::Bool(true)),
+            Some(_) if self.eat_keyword("false") => Ok(JsonValue::Bool(false)),
+            Some(_) if self.eat_keyword("null") => Ok(JsonValue::Null),
+            Some(_) => Err(self.err("expected a value")),
+            None => Err(self.err("unexpected end of input")),
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, ParseError> {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        let mut items = Vec::new();
+
+        if self.eat(']') {
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            self.skip_whitespace();
+            items.push(self.value()?);
+            self.skip_whitespace();
+
+            if self.eat(']') {
+                return Ok(JsonValue::Array(items));
+            }
+            if !self.eat(',') {
+                return Err(self.err("expected ']' or ','"));
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, ParseError> {
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        let mut entries = Vec::new();
+
+        if self.eat('}') {
+            return Ok(JsonValue::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.err("expected a string key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+
+            if self.eat('}') {
+                return Ok(JsonValue::Object(entries));
+            }
+            if !self.eat(',') {
+                return Err(self.err("expected '}' or ','"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+
+        let mut value = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.err("unterminated string")),
+                Some('"') => {
+                    self.idx += 1;
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.idx += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.idx += 1;
+                            value.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.err("invalid escape sequence")),
+                    };
+                    self.idx += 1;
+                    value.push(escaped);
+                }
+                Some(c) if c.is_control() => {
+                    return Err(self.err("control characters must be escaped"))
+                }
+                Some(c) => {
+                    self.idx += c.len_utf8();
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.hex4()?;
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.err("invalid unicode escape"));
+        }
+
+        if !self.inp[self.idx..].starts_with("\\u") {
+            return Err(self.err("expected a low surrogate"));
+        }
+        self.idx += 2;
+
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.err("expected a low surrogate"));
+        }
+
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| self.err("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let digits = self
+            .inp
+            .get(self.idx..self.idx + 4)
+            .filter(|d| d.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.err("expected four hex digits"))?;
+        self.idx += 4;
+
+        Ok(u32::from_str_radix(digits, 16).expect("the digits were checked to be hex digits"))
+    }
+
+    fn number(&mut self) -> Result<JsonValue, ParseError> {
+        let start = self.idx;
+
+        self.eat('-');
+        if !self.eat('0') && self.eat_digits() == 0 {
+            return Err(self.err("expected a digit"));
+        }
+        if self.eat('.') && self.eat_digits() == 0 {
+            return Err(self.err("expected a digit"));
+        }
+        if self.eat('e') || self.eat('E') {
+            if !self.eat('+') {
+                self.eat('-');
+            }
+            if self.eat_digits() == 0 {
+                return Err(self.err("expected a digit"));
+            }
+        }
+
+        Ok(JsonValue::Number(self.inp[start..self.idx].to_owned()))
+    }
+
+    fn eat_digits(&mut self) -> usize {
+        let count = self.inp[self.idx..]
+            .bytes()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        self.idx += count;
+
+        count
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.inp[self.idx..].starts_with(keyword) {
+            self.idx += keyword.len();
+            return true;
+        }
+
+        false
+    }
+
+    fn skip_whitespace(&mut self) {
+        let count = self.inp[self.idx..]
+            .bytes()
+            .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+            .count();
+        self.idx += count;
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.inp[self.idx..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.idx += c.len_utf8();
+            return true;
+        }
+
+        false
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            return Ok(());
+        }
+
+        Err(self.err(match c {
+            '[' => "expected '['",
+            '{' => "expected '{'",
+            ':' => "expected ':'",
+            '"' => "expected '\"'",
+            _ => "unexpected character",
+        }))
+    }
+
+    fn err(&self, reason: &str) -> ParseError {
+        let len = self.peek().map(char::len_utf8).unwrap_or(0);
+
+        ParseError::MalformedJson {
+            err_span: (self.offset + self.idx, len).into(),
+            reason: reason.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn detects_json() {
+        assert!(looks_like_json("[1,2,3]"));
+        assert!(looks_like_json(r#"  {"a": 1}"#));
+        assert!(!looks_like_json("some value"));
+        assert!(!looks_like_json(""));
+    }
+
+    #[test]
+    fn array() {
+        assert_eq!(
+            parse_json("[1, -2.5e3, true, null]", 0),
+            Ok(JsonValue::Array(vec![
+                JsonValue::Number("1".into()),
+                JsonValue::Number("-2.5e3".into()),
+                JsonValue::Bool(true),
+                JsonValue::Null,
+            ]))
+        );
+    }
+
+    #[test]
+    fn object() {
+        assert_eq!(
+            parse_json(r#"{"name": "café \"x\"", "tags": [], "nested": {}}"#, 0),
+            Ok(JsonValue::Object(vec![
+                ("name".into(), JsonValue::String("café \"x\"".into())),
+                ("tags".into(), JsonValue::Array(Vec::new())),
+                ("nested".into(), JsonValue::Object(Vec::new())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn surrogate_pair() {
+        assert_eq!(
+            parse_json(r#"["\ud83d\ude00"]"#, 0),
+            Ok(JsonValue::Array(vec![JsonValue::String("😀".into())]))
+        );
+    }
+
+    #[test]
+    fn errors_are_offset() {
+        assert_eq!(
+            parse_json("[1, 2", 10),
+            Err(ParseError::MalformedJson {
+                err_span: (15, 0).into(),
+                reason: "expected ']' or ','".into(),
+            })
+        );
+        assert_eq!(
+            parse_json(r#"{"a" 1}"#, 3),
+            Err(ParseError::MalformedJson {
+                err_span: (8, 1).into(),
+                reason: "expected ':'".into(),
+            })
+        );
+        assert_eq!(
+            parse_json("[1] x", 0),
+            Err(ParseError::MalformedJson {
+                err_span: (4, 1).into(),
+                reason: "unexpected trailing characters".into(),
+            })
+        );
+    }
+}
@@ -1,15 +1,18 @@
+use std::borrow::Cow;
+
 use crate::spanned::Spanned;
 
-use super::symbol::Symbol;
+use super::{into_owned::IntoOwned, shift_spans::ShiftSpans, symbol::Symbol};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct Literal {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Literal<'inp> {
     pub keyword: Spanned<()>,
-    pub args: Vec<LiteralArg>,
+    pub args: Vec<LiteralArg<'inp>>,
 }
 
-impl Literal {
-    pub fn new(keyword: impl Into<Spanned<()>>, args: Vec<impl Into<LiteralArg>>) -> Self {
+impl<'inp> Literal<'inp> {
+    pub fn new(keyword: impl Into<Spanned<()>>, args: Vec<impl Into<LiteralArg<'inp>>>) -> Self {
         Self {
             keyword: keyword.into(),
             args: args.into_iter().map(|a| a.into()).collect(),
@@ -18,13 +21,17 @@ impl Literal {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct LiteralArg {
-    pub name: Spanned<Symbol>,
-    pub value: LiteralArgValue,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LiteralArg<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub value: LiteralArgValue<'inp>,
 }
 
-impl LiteralArg {
-    pub fn new(name: impl Into<Spanned<Symbol>>, value: impl Into<LiteralArgValue>) -> Self {
+impl<'inp> LiteralArg<'inp> {
+    pub fn new(
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        value: impl Into<LiteralArgValue<'inp>>,
+    ) -> Self {
         Self {
             name: name.into(),
             value: value.into(),
@@ -33,23 +40,83 @@ impl LiteralArg {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum LiteralArgValue {
-    String(Spanned<String>),
-    Symbol(Spanned<Symbol>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum LiteralArgValue<'inp> {
+    String(Spanned<Cow<'inp, str>>),
+    Symbol(Spanned<Symbol<'inp>>),
 }
 
-impl From<Spanned<String>> for LiteralArgValue {
-    fn from(value: Spanned<String>) -> Self {
+impl<'inp> From<Spanned<Cow<'inp, str>>> for LiteralArgValue<'inp> {
+    fn from(value: Spanned<Cow<'inp, str>>) -> Self {
         Self::String(value)
     }
 }
 
-impl From<Spanned<Symbol>> for LiteralArgValue {
-    fn from(value: Spanned<Symbol>) -> Self {
+impl<'inp> From<Spanned<Symbol<'inp>>> for LiteralArgValue<'inp> {
+    fn from(value: Spanned<Symbol<'inp>>) -> Self {
         Self::Symbol(value)
     }
 }
 
+impl IntoOwned for Literal<'_> {
+    type Owned = Literal<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Literal {
+            keyword: self.keyword,
+            args: self.args.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for LiteralArg<'_> {
+    type Owned = LiteralArg<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        LiteralArg {
+            name: self.name.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for LiteralArgValue<'_> {
+    type Owned = LiteralArgValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::String(value) => LiteralArgValue::String(value.into_owned()),
+            Self::Symbol(value) => LiteralArgValue::Symbol(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for Literal<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { keyword, args } = self;
+        keyword.shift_spans(delta);
+        args.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for LiteralArg<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { name, value } = self;
+        name.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for LiteralArgValue<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::String(value) => value.shift_spans(delta),
+            Self::Symbol(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -74,7 +141,7 @@ mod tests {
                     (1, 8),
                     vec![LiteralArg::new(
                         (10, "content".into(), 17),
-                        Spanned::from((18, "(button 'foo')".to_owned(), 34))
+                        Spanned::from((18, Cow::Borrowed("(button 'foo')"), 34))
                     )]
                 )
                 .into(),
@@ -107,7 +174,12 @@ mod tests {
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<TopLevelExpr>, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -0,0 +1,429 @@
+use std::ops::Range;
+
+use crate::spanned::Spanned;
+
+use super::{
+    array::Array,
+    atom::Atom,
+    binary_operation::BinaryOperation,
+    children::Children,
+    comparison_operation::ComparisonOperation,
+    def_listen::{DefListen, DefListenArg},
+    def_poll::{DefPoll, DefPollArg},
+    def_var::DefVar,
+    def_widget::{DefWidget, DefWidgetParam},
+    def_window::{DefWindow, DefWindowArg},
+    for_loop::ForLoop,
+    function_call::FunctionCall,
+    include::Include,
+    literal::{Literal, LiteralArg},
+    object::{Object, ObjectEntry},
+    symbol::Symbol,
+    ternary_operation::TernaryOperation,
+    top_level_expr::TopLevelExpr,
+    unary_operation::UnaryOperation,
+    visitor::{self, Visitor},
+    widget_call::{WidgetCall, WidgetCallArg},
+};
+
+/// A reference to any node of the AST.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeRef<'ast> {
+    Array(&'ast Array<'ast>),
+    Atom(&'ast Atom<'ast>),
+    BinaryOperation(&'ast BinaryOperation<'ast>),
+    Children(&'ast Children),
+    ComparisonOperation(&'ast ComparisonOperation<'ast>),
+    DefListen(&'ast DefListen<'ast>),
+    DefListenArg(&'ast DefListenArg<'ast>),
+    DefPoll(&'ast DefPoll<'ast>),
+    DefPollArg(&'ast DefPollArg<'ast>),
+    DefVar(&'ast DefVar<'ast>),
+    DefWidget(&'ast DefWidget<'ast>),
+    DefWidgetParam(&'ast DefWidgetParam<'ast>),
+    DefWindow(&'ast DefWindow<'ast>),
+    DefWindowArg(&'ast DefWindowArg<'ast>),
+    ForLoop(&'ast ForLoop<'ast>),
+    FunctionCall(&'ast FunctionCall<'ast>),
+    Include(&'ast Include<'ast>),
+    Literal(&'ast Literal<'ast>),
+    LiteralArg(&'ast LiteralArg<'ast>),
+    Object(&'ast Object<'ast>),
+    ObjectEntry(&'ast ObjectEntry<'ast>),
+    Symbol(&'ast Symbol<'ast>),
+    TernaryOperation(&'ast TernaryOperation<'ast>),
+    UnaryOperation(&'ast UnaryOperation<'ast>),
+    WidgetCall(&'ast WidgetCall<'ast>),
+    WidgetCallArg(&'ast WidgetCallArg<'ast>),
+}
+
+impl<'ast> NodeRef<'ast> {
+    /// Returns `None` for [`TopLevelExpr::Err`].
+    pub fn from_top_level_expr(node: &'ast Spanned<TopLevelExpr<'ast>>) -> Option<Spanned<Self>> {
+        let mut collector = DirectChildren {
+            children: Vec::new(),
+        };
+        collector.visit_top_level_expr(node.as_ref());
+
+        collector.children.pop()
+    }
+
+    /// Returns the direct children of `node` in source order. Argument names such as `:text` are
+    /// returned as symbols, the spans of arguments and object entries start at their name.
+    pub fn children(node: &Spanned<Self>) -> Vec<Spanned<Self>> {
+        let mut collector = DirectChildren {
+            children: Vec::new(),
+        };
+        let Spanned(l, node, r) = *node;
+
+        match node {
+            Self::Array(array) => visitor::walk_array(&mut collector, Spanned(l, array, r)),
+            Self::Atom(atom) => visitor::walk_atom(&mut collector, Spanned(l, atom, r)),
+            Self::BinaryOperation(operation) => {
+                visitor::walk_binary_operation(&mut collector, Spanned(l, operation, r))
+            }
+            Self::Children(children) => {
+                visitor::walk_children(&mut collector, Spanned(l, children, r))
+            }
+            Self::ComparisonOperation(operation) => {
+                visitor::walk_comparison_operation(&mut collector, Spanned(l, operation, r))
+            }
+            Self::DefListen(def_listen) => {
+                visitor::walk_def_listen(&mut collector, Spanned(l, def_listen, r))
+            }
+            Self::DefListenArg(arg) => {
+                visitor::walk_def_listen_arg(&mut collector, Spanned(l, arg, r))
+            }
+            Self::DefPoll(def_poll) => {
+                visitor::walk_def_poll(&mut collector, Spanned(l, def_poll, r))
+            }
+            Self::DefPollArg(arg) => visitor::walk_def_poll_arg(&mut collector, Spanned(l, arg, r)),
+            Self::DefVar(def_var) => visitor::walk_def_var(&mut collector, Spanned(l, def_var, r)),
+            Self::DefWidget(def_widget) => {
+                visitor::walk_def_widget(&mut collector, Spanned(l, def_widget, r))
+            }
+            Self::DefWidgetParam(param) => {
+                visitor::walk_def_widget_param(&mut collector, Spanned(l, param, r))
+            }
+            Self::DefWindow(def_window) => {
+                visitor::walk_def_window(&mut collector, Spanned(l, def_window, r))
+            }
+            Self::DefWindowArg(arg) => {
+                visitor::walk_def_window_arg(&mut collector, Spanned(l, arg, r))
+            }
+            Self::ForLoop(for_loop) => {
+                visitor::walk_for_loop(&mut collector, Spanned(l, for_loop, r))
+            }
+            Self::FunctionCall(function_call) => {
+                visitor::walk_function_call(&mut collector, Spanned(l, function_call, r))
+            }
+            Self::Include(include) => visitor::walk_include(&mut collector, Spanned(l, include, r)),
+            Self::Literal(literal) => visitor::walk_literal(&mut collector, Spanned(l, literal, r)),
+            Self::LiteralArg(arg) => visitor::walk_literal_arg(&mut collector, Spanned(l, arg, r)),
+            Self::Object(object) => visitor::walk_object(&mut collector, Spanned(l, object, r)),
+            Self::ObjectEntry(entry) => {
+                visitor::walk_object_entry(&mut collector, Spanned(l, entry, r))
+            }
+            Self::Symbol(_) => {}
+            Self::TernaryOperation(operation) => {
+                visitor::walk_ternary_operation(&mut collector, Spanned(l, operation, r))
+            }
+            Self::UnaryOperation(operation) => {
+                visitor::walk_unary_operation(&mut collector, Spanned(l, operation, r))
+            }
+            Self::WidgetCall(widget_call) => {
+                visitor::walk_widget_call(&mut collector, Spanned(l, widget_call, r))
+            }
+            Self::WidgetCallArg(arg) => {
+                visitor::walk_widget_call_arg(&mut collector, Spanned(l, arg, r))
+            }
+        }
+
+        collector.children
+    }
+}
+
+/// Collects the nodes visited by a single `walk_*` call without descending into them, i.e. the
+/// direct children of the walked node.
+struct DirectChildren<'ast> {
+    children: Vec<Spanned<NodeRef<'ast>>>,
+}
+
+impl<'ast> Visitor<'ast> for DirectChildren<'ast> {
+    fn visit_def_listen(&mut self, node: Spanned<&'ast DefListen>) {
+        self.children.push(node.map(NodeRef::DefListen));
+    }
+
+    fn visit_def_listen_arg(&mut self, node: Spanned<&'ast DefListenArg>) {
+        self.children.push(node.map(NodeRef::DefListenArg));
+    }
+
+    fn visit_def_poll(&mut self, node: Spanned<&'ast DefPoll>) {
+        self.children.push(node.map(NodeRef::DefPoll));
+    }
+
+    fn visit_def_poll_arg(&mut self, node: Spanned<&'ast DefPollArg>) {
+        self.children.push(node.map(NodeRef::DefPollArg));
+    }
+
+    fn visit_def_var(&mut self, node: Spanned<&'ast DefVar>) {
+        self.children.push(node.map(NodeRef::DefVar));
+    }
+
+    fn visit_def_widget(&mut self, node: Spanned<&'ast DefWidget>) {
+        self.children.push(node.map(NodeRef::DefWidget));
+    }
+
+    fn visit_def_widget_param(&mut self, node: Spanned<&'ast DefWidgetParam>) {
+        self.children.push(node.map(NodeRef::DefWidgetParam));
+    }
+
+    fn visit_def_window(&mut self, node: Spanned<&'ast DefWindow>) {
+        self.children.push(node.map(NodeRef::DefWindow));
+    }
+
+    fn visit_def_window_arg(&mut self, node: Spanned<&'ast DefWindowArg>) {
+        self.children.push(node.map(NodeRef::DefWindowArg));
+    }
+
+    fn visit_include(&mut self, node: Spanned<&'ast Include>) {
+        self.children.push(node.map(NodeRef::Include));
+    }
+
+    fn visit_literal(&mut self, node: Spanned<&'ast Literal>) {
+        self.children.push(node.map(NodeRef::Literal));
+    }
+
+    fn visit_literal_arg(&mut self, node: Spanned<&'ast LiteralArg>) {
+        self.children.push(node.map(NodeRef::LiteralArg));
+    }
+
+    fn visit_widget_call(&mut self, node: Spanned<&'ast WidgetCall>) {
+        self.children.push(node.map(NodeRef::WidgetCall));
+    }
+
+    fn visit_widget_call_arg(&mut self, node: Spanned<&'ast WidgetCallArg>) {
+        self.children.push(node.map(NodeRef::WidgetCallArg));
+    }
+
+    fn visit_children(&mut self, node: Spanned<&'ast Children>) {
+        self.children.push(node.map(NodeRef::Children));
+    }
+
+    fn visit_for_loop(&mut self, node: Spanned<&'ast ForLoop>) {
+        self.children.push(node.map(NodeRef::ForLoop));
+    }
+
+    fn visit_array(&mut self, node: Spanned<&'ast Array>) {
+        self.children.push(node.map(NodeRef::Array));
+    }
+
+    fn visit_object(&mut self, node: Spanned<&'ast Object>) {
+        self.children.push(node.map(NodeRef::Object));
+    }
+
+    fn visit_object_entry(&mut self, node: Spanned<&'ast ObjectEntry>) {
+        self.children.push(node.map(NodeRef::ObjectEntry));
+    }
+
+    fn visit_binary_operation(&mut self, node: Spanned<&'ast BinaryOperation>) {
+        self.children.push(node.map(NodeRef::BinaryOperation));
+    }
+
+    fn visit_comparison_operation(&mut self, node: Spanned<&'ast ComparisonOperation>) {
+        self.children.push(node.map(NodeRef::ComparisonOperation));
+    }
+
+    fn visit_function_call(&mut self, node: Spanned<&'ast FunctionCall>) {
+        self.children.push(node.map(NodeRef::FunctionCall));
+    }
+
+    fn visit_ternary_operation(&mut self, node: Spanned<&'ast TernaryOperation>) {
+        self.children.push(node.map(NodeRef::TernaryOperation));
+    }
+
+    fn visit_unary_operation(&mut self, node: Spanned<&'ast UnaryOperation>) {
+        self.children.push(node.map(NodeRef::UnaryOperation));
+    }
+
+    fn visit_atom(&mut self, node: Spanned<&'ast Atom>) {
+        self.children.push(node.map(NodeRef::Atom));
+    }
+
+    fn visit_symbol(&mut self, node: Spanned<&'ast Symbol>) {
+        self.children.push(node.map(NodeRef::Symbol));
+    }
+
+    fn visit_arg_name(&mut self, node: Spanned<&'ast Symbol>) {
+        self.children.push(node.map(NodeRef::Symbol));
+    }
+}
+
+/// Returns the nodes enclosing `range`, from the outermost top level expression to the innermost
+/// node. The path is empty if no node encloses the range. Pass `offset..offset` to look up the
+/// node at a single offset, a node is considered to enclose the offsets at both of its ends.
+pub fn node_path_at<'ast>(
+    nodes: &'ast [Spanned<TopLevelExpr<'ast>>],
+    range: Range<usize>,
+) -> Vec<Spanned<NodeRef<'ast>>> {
+    let mut path = Vec::new();
+    let mut candidates: Vec<_> = nodes
+        .iter()
+        .filter_map(NodeRef::from_top_level_expr)
+        .collect();
+
+    while let Some(node) = candidates
+        .into_iter()
+        .find(|node| node.0 <= range.start && range.end <= node.2)
+    {
+        candidates = NodeRef::children(&node);
+        path.push(node);
+    }
+
+    path
+}
+
+/// Calls `f` with the path to every node, in source order. Each path starts at a top level
+/// expression and ends at the visited node, like the paths returned by [`node_path_at`].
+pub fn for_each_node_path<'ast>(
+    nodes: &'ast [Spanned<TopLevelExpr<'ast>>],
+    mut f: impl FnMut(&[Spanned<NodeRef<'ast>>]),
+) {
+    fn walk<'ast>(
+        path: &mut Vec<Spanned<NodeRef<'ast>>>,
+        node: Spanned<NodeRef<'ast>>,
+        f: &mut impl FnMut(&[Spanned<NodeRef<'ast>>]),
+    ) {
+        let children = NodeRef::children(&node);
+        path.push(node);
+        f(path);
+        for child in children {
+            walk(path, child, f);
+        }
+        path.pop();
+    }
+
+    let mut path = Vec::new();
+    for node in nodes.iter().filter_map(NodeRef::from_top_level_expr) {
+        walk(&mut path, node, &mut f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::lexer::Lexer;
+
+    const INPUT: &str = r#"(defwidget bar [?icon]
+  (box :class "bar"
+    {round(volume, 0) > 50 ? icon : "muted"}))"#;
+
+    #[test]
+    fn widget_call_arg() {
+        let ast = test(INPUT);
+
+        // `"bar"`
+        assert_eq!(
+            kinds(&node_path_at(&ast, 38..38)),
+            vec!["DefWidget", "WidgetCall", "WidgetCallArg", "Atom"]
+        );
+    }
+
+    #[test]
+    fn nested_expression() {
+        let ast = test(INPUT);
+
+        // `volume`
+        let path = node_path_at(&ast, 58..60);
+        assert_eq!(
+            kinds(&path),
+            vec![
+                "DefWidget",
+                "WidgetCall",
+                "TernaryOperation",
+                "ComparisonOperation",
+                "FunctionCall",
+                "Symbol",
+            ]
+        );
+        assert_eq!(
+            path.last(),
+            Some(&(54, NodeRef::Symbol(&Symbol::new("volume")), 60).into())
+        );
+    }
+
+    #[test]
+    fn param() {
+        let ast = test(INPUT);
+
+        let path = node_path_at(&ast, 16..16);
+        assert_eq!(kinds(&path), vec!["DefWidget", "DefWidgetParam"]);
+        assert_eq!((path[1].0, path[1].2), (16, 21));
+    }
+
+    #[test]
+    fn adjacent_nodes() {
+        let ast = test("(defwidget foo [] (a)(b))");
+
+        let path = node_path_at(&ast, 21..21);
+        assert_eq!(kinds(&path), vec!["DefWidget", "WidgetCall"]);
+        assert_eq!((path[1].0, path[1].2), (18, 21));
+    }
+
+    #[test]
+    fn outside() {
+        let ast = test(INPUT);
+
+        assert_eq!(node_path_at(&ast, 200..200), Vec::new());
+        assert_eq!(
+            kinds(&node_path_at(&ast, 30..70)),
+            vec!["DefWidget", "WidgetCall"]
+        );
+    }
+
+    #[test]
+    fn every_node_path() {
+        let ast = test("(defvar foo 1)\n(defwidget bar [] (box))");
+
+        let mut paths = Vec::new();
+        for_each_node_path(&ast, |path| paths.push(kinds(path)));
+
+        assert_eq!(
+            paths,
+            vec![
+                vec!["DefVar"],
+                vec!["DefVar", "Symbol"],
+                vec!["DefVar", "Atom"],
+                vec!["DefWidget"],
+                vec!["DefWidget", "Symbol"],
+                vec!["DefWidget", "WidgetCall"],
+                vec!["DefWidget", "WidgetCall", "Symbol"],
+            ]
+        );
+    }
+
+    fn kinds(path: &[Spanned<NodeRef>]) -> Vec<String> {
+        path.iter()
+            .map(|node| {
+                let debug = format!("{:?}", node.1);
+                debug[..debug.find('(').unwrap()].to_owned()
+            })
+            .collect()
+    }
+
+    fn test(inp: &str) -> Vec<Spanned<TopLevelExpr<'_>>> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::TopLevelParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).unwrap();
+        assert_eq!(errors, Vec::new());
+
+        result
+    }
+}
@@ -0,0 +1,162 @@
+use std::borrow::Cow;
+
+use crate::spanned::Spanned;
+
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Object<'inp> {
+    pub entries: Vec<ObjectEntry<'inp>>,
+}
+
+impl<'inp> Object<'inp> {
+    pub fn new(entries: Vec<ObjectEntry<'inp>>) -> Self {
+        Self { entries }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectEntry<'inp> {
+    pub key: Spanned<Cow<'inp, str>>,
+    pub value: Spanned<PrimitiveExpr<'inp>>,
+}
+
+impl<'inp> ObjectEntry<'inp> {
+    pub fn new(
+        key: impl Into<Spanned<Cow<'inp, str>>>,
+        value: impl Into<Spanned<PrimitiveExpr<'inp>>>,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl IntoOwned for Object<'_> {
+    type Owned = Object<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Object {
+            entries: self.entries.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for ObjectEntry<'_> {
+    type Owned = ObjectEntry<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        ObjectEntry {
+            key: self.key.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for Object<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { entries } = self;
+        entries.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for ObjectEntry<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { key, value } = self;
+        key.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        ast::{array::Array, atom::Atom, symbol::Symbol, ParseError},
+        lexer::{Lexer, LexicalError},
+    };
+
+    #[test]
+    fn empty() {
+        let (errs, ast) = test("{}");
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(ast, Ok((0, Object::new(Vec::new()).into(), 2).into()));
+    }
+
+    #[test]
+    fn entries() {
+        let (errs, ast) = test(r#"{"a": 1, "b": name}"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                Object::new(vec![
+                    ObjectEntry::new((1, "a".into(), 4), (6, Atom::new_number("1").into(), 7)),
+                    ObjectEntry::new((9, "b".into(), 12), (14, Symbol::new("name").into(), 18)),
+                ])
+                .into(),
+                19
+            )
+                .into())
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let (errs, ast) = test(r#"{"items": [1], "inner": {}}"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                Object::new(vec![
+                    ObjectEntry::new(
+                        (1, "items".into(), 8),
+                        (
+                            10,
+                            Array::new(vec![(11, Atom::new_number("1").into(), 12)]).into(),
+                            13
+                        )
+                    ),
+                    ObjectEntry::new(
+                        (15, "inner".into(), 22),
+                        (24, Object::new(Vec::new()).into(), 26)
+                    ),
+                ])
+                .into(),
+                27
+            )
+                .into())
+        );
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
+    ) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::BinaryOperationParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).map_err(|e| match e {
+            lalrpop_util::ParseError::User { error } => error,
+            e => unimplemented!("this error should not happen: {:?}", e),
+        });
+
+        (errors, result)
+    }
+}
@@ -7,6 +7,11 @@ use thiserror::Error;
 pub enum ParseError {
     #[error("Token was not expected at this point")]
     UnexpectedToken { err_span: SourceSpan },
+    #[error("unexpected end of file")]
+    UnexpectedEof {
+        #[label("an expression is not closed")]
+        err_span: SourceSpan,
+    },
     #[error("expected a name")]
     ExpectedDefListenName {
         #[label("try providing a name here")]
@@ -52,17 +57,69 @@ pub enum ParseError {
         #[label("try providing a name here")]
         err_span: SourceSpan,
     },
+    #[error("expected a loop variable")]
+    ExpectedForLoopBinding {
+        #[label("try providing a name for the current element here")]
+        err_span: SourceSpan,
+    },
+    #[error("expected `in`")]
+    ExpectedForLoopIn {
+        #[label("try providing `in` here")]
+        err_span: SourceSpan,
+    },
+    #[error("expected a value to iterate over")]
+    ExpectedForLoopIterable {
+        #[label("try providing a variable or array here")]
+        err_span: SourceSpan,
+    },
+    #[error("expected a loop body")]
+    ExpectedForLoopBody {
+        #[label("try providing a widget here")]
+        err_span: SourceSpan,
+    },
+    #[error("expected a non-negative integer")]
+    InvalidChildrenNth {
+        #[label("`:nth` must be a non-negative integer")]
+        err_span: SourceSpan,
+    },
+    #[error("unexpected argument")]
+    UnexpectedChildrenArg {
+        #[label("`children` only accepts `:nth`")]
+        err_span: SourceSpan,
+    },
+    #[error("unexpected children")]
+    UnexpectedChildrenNodes {
+        #[label("`children` cannot have children of its own")]
+        err_span: SourceSpan,
+    },
+    #[error("`children` outside of a `defwidget`")]
+    ChildrenOutsideDefWidget {
+        #[label("only the body of a `defwidget` has children to insert here")]
+        err_span: SourceSpan,
+    },
+    #[error("duplicate parameter")]
+    DuplicateParam {
+        #[label("a parameter with this name was already declared")]
+        err_span: SourceSpan,
+    },
     #[error("expected a path")]
     ExpectedIncludePath {
         #[label("try providing a path here")]
         err_span: SourceSpan,
     },
+    #[error("malformed JSON: {reason}")]
+    MalformedJson {
+        #[label("the JSON is invalid here")]
+        err_span: SourceSpan,
+        reason: String,
+    },
 }
 
 impl ParseError {
     pub fn span(&self) -> &SourceSpan {
         match self {
             ParseError::UnexpectedToken { err_span } => err_span,
+            ParseError::UnexpectedEof { err_span } => err_span,
             ParseError::ExpectedDefListenName { err_span } => err_span,
             ParseError::ExpectedDefListenScript { err_span } => err_span,
             ParseError::ExpectedDefPollName { err_span } => err_span,
@@ -72,7 +129,17 @@ impl ParseError {
             ParseError::ExpectedDefWidgetName { err_span } => err_span,
             ParseError::ExpectedDefWidgetParams { err_span } => err_span,
             ParseError::ExpectedDefWindowName { err_span } => err_span,
+            ParseError::ExpectedForLoopBinding { err_span } => err_span,
+            ParseError::ExpectedForLoopIn { err_span } => err_span,
+            ParseError::ExpectedForLoopIterable { err_span } => err_span,
+            ParseError::ExpectedForLoopBody { err_span } => err_span,
+            ParseError::InvalidChildrenNth { err_span } => err_span,
+            ParseError::UnexpectedChildrenArg { err_span } => err_span,
+            ParseError::UnexpectedChildrenNodes { err_span } => err_span,
+            ParseError::ChildrenOutsideDefWidget { err_span } => err_span,
+            ParseError::DuplicateParam { err_span } => err_span,
             ParseError::ExpectedIncludePath { err_span } => err_span,
+            ParseError::MalformedJson { err_span, .. } => err_span,
         }
     }
 }
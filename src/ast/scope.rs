@@ -0,0 +1,184 @@
+use crate::spanned::Spanned;
+
+use super::{
+    atom::{self, Atom},
+    expr::PrimitiveExpr,
+    symbol::Symbol,
+    unary_operation::UnaryOperation,
+    widget_call::{WidgetCall, WidgetCallArgValue},
+    widget_node::WidgetNode,
+};
+
+/// The names visible at a given point of a widget tree, e.g. `for` loop bindings. Names pushed
+/// later shadow names pushed earlier.
+#[derive(Clone, Debug, Default)]
+pub struct Scope<'ast> {
+    frames: Vec<Vec<&'ast Spanned<Symbol<'ast>>>>,
+}
+
+impl<'ast> Scope<'ast> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, names: impl IntoIterator<Item = &'ast Spanned<Symbol<'ast>>>) {
+        self.frames.push(names.into_iter().collect());
+    }
+
+    pub fn pop(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Returns the innermost binding of `name`, if there is one.
+    pub fn resolve(&self, name: &Symbol<'ast>) -> Option<&'ast Spanned<Symbol<'ast>>> {
+        self.frames
+            .iter()
+            .rev()
+            .flat_map(|frame| frame.iter().rev())
+            .find(|binding| binding.1 == *name)
+            .copied()
+    }
+}
+
+/// A symbol referenced somewhere in a widget tree, together with the binding it resolves to.
+/// `binding` is `None` for names that are not bound by the surrounding scope, e.g. global
+/// variables. References in the `${}` interpolations of string literals are included.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resolution<'ast> {
+    pub reference: Spanned<Symbol<'ast>>,
+    pub binding: Option<&'ast Spanned<Symbol<'ast>>>,
+}
+
+/// Resolves every symbol referenced by `nodes` against `scope`, descending into nested widget
+/// calls and loops.
+pub fn resolve_nodes<'ast>(
+    nodes: &'ast [WidgetNode],
+    scope: &mut Scope<'ast>,
+    resolutions: &mut Vec<Resolution<'ast>>,
+) {
+    for node in nodes {
+        match node {
+            WidgetNode::Atom(Spanned(l, atom, r)) => {
+                resolve_atom(Spanned(*l, atom, *r), scope, resolutions)
+            }
+            WidgetNode::Children(_) | WidgetNode::Err => {}
+            WidgetNode::Expr(expr) => resolve_expr(expr, scope, resolutions),
+            WidgetNode::ForLoop(Spanned(_, for_loop, _)) => {
+                // The iterable is evaluated before the binding is introduced.
+                resolve_expr(&for_loop.iterable, scope, resolutions);
+
+                scope.push([&for_loop.binding]);
+                resolve_nodes(&for_loop.body, scope, resolutions);
+                scope.pop();
+            }
+            WidgetNode::WidgetCall(Spanned(_, widget_call, _)) => {
+                resolve_widget_call(widget_call, scope, resolutions)
+            }
+        }
+    }
+}
+
+/// Resolves every symbol referenced by the arguments and children of `widget_call`.
+pub fn resolve_widget_call<'ast>(
+    widget_call: &'ast WidgetCall<'ast>,
+    scope: &mut Scope<'ast>,
+    resolutions: &mut Vec<Resolution<'ast>>,
+) {
+    for arg in &widget_call.args {
+        match &arg.value {
+            WidgetCallArgValue::Atom(Spanned(l, atom, r)) => {
+                resolve_atom(Spanned(*l, atom, *r), scope, resolutions)
+            }
+            WidgetCallArgValue::Expr(expr) => resolve_expr(expr, scope, resolutions),
+            WidgetCallArgValue::Symbol(symbol) => resolve_symbol(symbol, scope, resolutions),
+        }
+    }
+
+    resolve_nodes(&widget_call.children, scope, resolutions);
+}
+
+pub fn resolve_symbol<'ast>(
+    symbol: &'ast Spanned<Symbol<'ast>>,
+    scope: &Scope<'ast>,
+    resolutions: &mut Vec<Resolution<'ast>>,
+) {
+    let Spanned(l, name, r) = symbol;
+
+    resolutions.push(Resolution {
+        reference: (*l, borrowed(name), *r).into(),
+        binding: scope.resolve(name),
+    });
+}
+
+/// Resolves the variables interpolated into a string literal, e.g. `volume` in `"${volume}%"`.
+fn resolve_atom<'ast>(
+    atom: Spanned<&'ast Atom<'ast>>,
+    scope: &Scope<'ast>,
+    resolutions: &mut Vec<Resolution<'ast>>,
+) {
+    let Spanned(l, Atom::StrLit(value), _) = atom else {
+        return;
+    };
+
+    // The content of the literal starts after its opening quote.
+    for Spanned(l, name, r) in atom::interpolated_symbols(value, l + 1) {
+        let name = Symbol::new(name);
+        resolutions.push(Resolution {
+            binding: scope.resolve(&name),
+            reference: (l, name, r).into(),
+        });
+    }
+}
+
+/// Borrows `symbol` instead of cloning an owned name.
+fn borrowed<'ast>(symbol: &'ast Symbol<'ast>) -> Symbol<'ast> {
+    Symbol::new(symbol.0.as_ref())
+}
+
+fn resolve_expr<'ast>(
+    expr: &'ast Spanned<PrimitiveExpr<'ast>>,
+    scope: &Scope<'ast>,
+    resolutions: &mut Vec<Resolution<'ast>>,
+) {
+    let Spanned(l, expr, r) = expr;
+
+    match expr {
+        PrimitiveExpr::Array(array) => {
+            for item in &array.items {
+                resolve_expr(item, scope, resolutions);
+            }
+        }
+        PrimitiveExpr::Atom(atom) => resolve_atom(Spanned(*l, atom, *r), scope, resolutions),
+        PrimitiveExpr::BinaryOperation(operation) => {
+            resolve_expr(&operation.lhs, scope, resolutions);
+            resolve_expr(&operation.rhs, scope, resolutions);
+        }
+        PrimitiveExpr::ComparisonOperation(operation) => {
+            resolve_expr(&operation.lhs, scope, resolutions);
+            resolve_expr(&operation.rhs, scope, resolutions);
+        }
+        // The name of a function refers to a builtin, not to a variable.
+        PrimitiveExpr::FunctionCall(function_call) => {
+            for arg in &function_call.args {
+                resolve_expr(arg, scope, resolutions);
+            }
+        }
+        PrimitiveExpr::Object(object) => {
+            for entry in &object.entries {
+                resolve_expr(&entry.value, scope, resolutions);
+            }
+        }
+        PrimitiveExpr::Symbol(symbol) => resolutions.push(Resolution {
+            reference: (*l, borrowed(symbol), *r).into(),
+            binding: scope.resolve(symbol),
+        }),
+        PrimitiveExpr::TernaryOperation(operation) => {
+            resolve_expr(&operation.cond, scope, resolutions);
+            resolve_expr(&operation.true_branch, scope, resolutions);
+            resolve_expr(&operation.false_branch, scope, resolutions);
+        }
+        PrimitiveExpr::UnaryOperation(UnaryOperation::Not(operand)) => {
+            resolve_expr(operand, scope, resolutions)
+        }
+    }
+}
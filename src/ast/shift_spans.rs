@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+
+use crate::spanned::Spanned;
+
+/// Moves every span of a node by `delta` bytes, e.g. to keep the nodes behind an edit in line
+/// with the edited source.
+pub trait ShiftSpans {
+    fn shift_spans(&mut self, delta: isize);
+}
+
+impl ShiftSpans for () {
+    fn shift_spans(&mut self, _delta: isize) {}
+}
+
+impl ShiftSpans for usize {
+    fn shift_spans(&mut self, _delta: isize) {}
+}
+
+impl ShiftSpans for Cow<'_, str> {
+    fn shift_spans(&mut self, _delta: isize) {}
+}
+
+impl<T: ShiftSpans> ShiftSpans for Spanned<T> {
+    fn shift_spans(&mut self, delta: isize) {
+        self.0 = shift(self.0, delta);
+        self.1.shift_spans(delta);
+        self.2 = shift(self.2, delta);
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Box<T> {
+    fn shift_spans(&mut self, delta: isize) {
+        (**self).shift_spans(delta);
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Option<T> {
+    fn shift_spans(&mut self, delta: isize) {
+        if let Some(value) = self {
+            value.shift_spans(delta);
+        }
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Vec<T> {
+    fn shift_spans(&mut self, delta: isize) {
+        for value in self {
+            value.shift_spans(delta);
+        }
+    }
+}
+
+fn shift(offset: usize, delta: isize) -> usize {
+    offset
+        .checked_add_signed(delta)
+        .expect("span shifted in front of the source")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ast::top_level_expr::TopLevelExpr;
+
+    #[test]
+    fn shifted_tree_matches_the_moved_source() {
+        let inp = r#"(defwidget bar [?a] (box :x 1 (for i in items (label {a == 1 ? [i] : {"k": !i}})) (children :nth 0)))"#;
+        let mut ast = test(inp);
+        ast.shift_spans(3);
+        assert_eq!(ast, test(&format!("   {inp}")));
+
+        ast.shift_spans(-3);
+        assert_eq!(ast, test(inp));
+    }
+
+    fn test(inp: &str) -> Vec<Spanned<TopLevelExpr<'_>>> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let (ast, diagnostics) = crate::parse_top_level(inp);
+        assert_eq!(diagnostics, Vec::new());
+        ast
+    }
+}
@@ -1,18 +1,35 @@
+use std::borrow::Cow;
+
+use super::{into_owned::IntoOwned, shift_spans::ShiftSpans};
+
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct Symbol(pub String);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol<'inp>(pub Cow<'inp, str>);
 
-impl Symbol {
-    pub fn new(value: impl Into<String>) -> Self {
+impl<'inp> Symbol<'inp> {
+    pub fn new(value: impl Into<Cow<'inp, str>>) -> Self {
         Self(value.into())
     }
 }
 
-impl From<&str> for Symbol {
-    fn from(value: &str) -> Self {
+impl<'inp> From<&'inp str> for Symbol<'inp> {
+    fn from(value: &'inp str) -> Self {
         Self::new(value)
     }
 }
 
+impl IntoOwned for Symbol<'_> {
+    type Owned = Symbol<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Symbol(IntoOwned::into_owned(self.0))
+    }
+}
+
+impl ShiftSpans for Symbol<'_> {
+    fn shift_spans(&mut self, _delta: isize) {}
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -26,7 +43,7 @@ mod tests {
 
     #[test]
     fn symbols_are_parsed() {
-        for input in vec!["foo", "bar123"] {
+        for input in ["foo", "bar123"] {
             let (errs, ast) = test(input);
 
             assert_eq!(errs, Vec::new());
@@ -34,7 +51,12 @@ mod tests {
         }
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<Spanned<Symbol>, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<Symbol<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -1,17 +1,20 @@
-use super::expr::PrimitiveExpr;
+use crate::spanned::Spanned;
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct TernaryOperation {
-    pub cond: Box<PrimitiveExpr>,
-    pub true_branch: Box<PrimitiveExpr>,
-    pub false_branch: Box<PrimitiveExpr>,
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TernaryOperation<'inp> {
+    pub cond: Box<Spanned<PrimitiveExpr<'inp>>>,
+    pub true_branch: Box<Spanned<PrimitiveExpr<'inp>>>,
+    pub false_branch: Box<Spanned<PrimitiveExpr<'inp>>>,
 }
 
-impl TernaryOperation {
+impl<'inp> TernaryOperation<'inp> {
     pub fn new(
-        cond: impl Into<PrimitiveExpr>,
-        true_branch: impl Into<PrimitiveExpr>,
-        false_branch: impl Into<PrimitiveExpr>,
+        cond: impl Into<Spanned<PrimitiveExpr<'inp>>>,
+        true_branch: impl Into<Spanned<PrimitiveExpr<'inp>>>,
+        false_branch: impl Into<Spanned<PrimitiveExpr<'inp>>>,
     ) -> Self {
         Self {
             cond: Box::new(cond.into()),
@@ -21,6 +24,31 @@ impl TernaryOperation {
     }
 }
 
+impl IntoOwned for TernaryOperation<'_> {
+    type Owned = TernaryOperation<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        TernaryOperation {
+            cond: self.cond.into_owned(),
+            true_branch: self.true_branch.into_owned(),
+            false_branch: self.false_branch.into_owned(),
+        }
+    }
+}
+
+impl ShiftSpans for TernaryOperation<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            cond,
+            true_branch,
+            false_branch,
+        } = self;
+        cond.shift_spans(delta);
+        true_branch.shift_spans(delta);
+        false_branch.shift_spans(delta);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -31,6 +59,7 @@ mod tests {
         ast::{
             comparison_operation::{ComparisonOperation, ComparisonOperator},
             function_call::FunctionCall,
+            symbol::Symbol,
             ParseError,
         },
         lexer::{Lexer, LexicalError},
@@ -42,7 +71,17 @@ mod tests {
 
         assert_eq!(
             ast,
-            Ok(TernaryOperation::new(true, Atom::new_number("5"), Atom::new_number("6")).into())
+            Ok((
+                0,
+                TernaryOperation::new(
+                    (0, true.into(), 4),
+                    (7, Atom::new_number("5").into(), 8),
+                    (11, Atom::new_number("6").into(), 12)
+                )
+                .into(),
+                12
+            )
+                .into())
         );
     }
 
@@ -52,28 +91,51 @@ mod tests {
 
         assert_eq!(
             ast,
-            Ok(TernaryOperation::new(
-                ComparisonOperation::new(
+            Ok((
+                0,
+                TernaryOperation::new(
                     (
                         0,
-                        FunctionCall::new(
-                            "strlength",
-                            vec![FunctionCall::new("trim", vec!["hello".into()]).into()]
+                        ComparisonOperation::new(
+                            (
+                                0,
+                                FunctionCall::new(
+                                    (0, Symbol::new("strlength"), 9),
+                                    vec![(
+                                        10,
+                                        FunctionCall::new(
+                                            (10, Symbol::new("trim"), 14),
+                                            vec![(15, "hello".into(), 22)]
+                                        )
+                                        .into(),
+                                        23
+                                    )]
+                                )
+                                .into(),
+                                24
+                            ),
+                            ComparisonOperator::Gt,
+                            (27, Atom::new_number("0").into(), 28),
                         )
                         .into(),
-                        24
+                        28
                     ),
-                    ComparisonOperator::Gt,
-                    (27, Atom::new_number("0").into(), 28),
-                ),
-                "content",
-                "no content"
+                    (31, "content".into(), 40),
+                    (43, "no content".into(), 55)
+                )
+                .into(),
+                55
             )
-            .into())
+                .into())
         );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<PrimitiveExpr, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
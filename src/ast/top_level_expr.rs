@@ -1,64 +1,101 @@
 use super::{
     def_listen::DefListen, def_poll::DefPoll, def_var::DefVar, def_widget::DefWidget,
-    def_window::DefWindow, include::Include, literal::Literal,
+    def_window::DefWindow, include::Include, into_owned::IntoOwned, literal::Literal,
+    shift_spans::ShiftSpans,
 };
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum TopLevelExpr {
-    DefListen(DefListen),
-    DefPoll(DefPoll),
-    DefVar(DefVar),
-    DefWidget(DefWidget),
-    DefWindow(DefWindow),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum TopLevelExpr<'inp> {
+    DefListen(DefListen<'inp>),
+    DefPoll(DefPoll<'inp>),
+    DefVar(DefVar<'inp>),
+    DefWidget(DefWidget<'inp>),
+    DefWindow(DefWindow<'inp>),
     Err,
-    Include(Include),
-    Literal(Literal),
+    Include(Include<'inp>),
+    Literal(Literal<'inp>),
 }
 
-impl From<DefListen> for TopLevelExpr {
-    fn from(value: DefListen) -> Self {
+impl<'inp> From<DefListen<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: DefListen<'inp>) -> Self {
         Self::DefListen(value)
     }
 }
 
-impl From<DefPoll> for TopLevelExpr {
-    fn from(value: DefPoll) -> Self {
+impl<'inp> From<DefPoll<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: DefPoll<'inp>) -> Self {
         Self::DefPoll(value)
     }
 }
 
-impl From<DefVar> for TopLevelExpr {
-    fn from(value: DefVar) -> Self {
+impl<'inp> From<DefVar<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: DefVar<'inp>) -> Self {
         Self::DefVar(value)
     }
 }
 
-impl From<DefWidget> for TopLevelExpr {
-    fn from(value: DefWidget) -> Self {
+impl<'inp> From<DefWidget<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: DefWidget<'inp>) -> Self {
         Self::DefWidget(value)
     }
 }
 
-impl From<DefWindow> for TopLevelExpr {
-    fn from(value: DefWindow) -> Self {
+impl<'inp> From<DefWindow<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: DefWindow<'inp>) -> Self {
         Self::DefWindow(value)
     }
 }
 
-impl From<Include> for TopLevelExpr {
-    fn from(value: Include) -> Self {
+impl<'inp> From<Include<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: Include<'inp>) -> Self {
         Self::Include(value)
     }
 }
 
-impl From<Literal> for TopLevelExpr {
-    fn from(value: Literal) -> Self {
+impl<'inp> From<Literal<'inp>> for TopLevelExpr<'inp> {
+    fn from(value: Literal<'inp>) -> Self {
         Self::Literal(value)
     }
 }
 
+impl IntoOwned for TopLevelExpr<'_> {
+    type Owned = TopLevelExpr<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::DefListen(value) => TopLevelExpr::DefListen(value.into_owned()),
+            Self::DefPoll(value) => TopLevelExpr::DefPoll(value.into_owned()),
+            Self::DefVar(value) => TopLevelExpr::DefVar(value.into_owned()),
+            Self::DefWidget(value) => TopLevelExpr::DefWidget(value.into_owned()),
+            Self::DefWindow(value) => TopLevelExpr::DefWindow(value.into_owned()),
+            Self::Err => TopLevelExpr::Err,
+            Self::Include(value) => TopLevelExpr::Include(value.into_owned()),
+            Self::Literal(value) => TopLevelExpr::Literal(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for TopLevelExpr<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::DefListen(value) => value.shift_spans(delta),
+            Self::DefPoll(value) => value.shift_spans(delta),
+            Self::DefVar(value) => value.shift_spans(delta),
+            Self::DefWidget(value) => value.shift_spans(delta),
+            Self::DefWindow(value) => value.shift_spans(delta),
+            Self::Include(value) => value.shift_spans(delta),
+            Self::Literal(value) => value.shift_spans(delta),
+            Self::Err => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use pretty_assertions::assert_eq;
 
     use crate::{
@@ -67,11 +104,12 @@ mod tests {
             def_listen::{DefListen, DefListenArg},
             def_poll::{DefPoll, DefPollArg},
             def_var::DefVar,
-            def_widget::{DefWidget, DefWidgetChild, DefWidgetParam},
+            def_widget::{DefWidget, DefWidgetParam},
             def_window::{DefWindow, DefWindowArg},
             include::Include,
             literal::{Literal, LiteralArg},
-            widget_call::{WidgetCall, WidgetCallArg, WidgetCallChild},
+            widget_call::{WidgetCall, WidgetCallArg},
+            widget_node::WidgetNode,
             ParseError,
         },
         grammar,
@@ -118,7 +156,7 @@ mod tests {
                         (61, 68),
                         (69, "volume".into(), 75),
                         Vec::<DefPollArg>::new(),
-                        (76, "scripts/getvol".to_owned(), 92)
+                        (76, "scripts/getvol".into(), 92)
                     )
                     .into(),
                     93
@@ -132,7 +170,7 @@ mod tests {
         inp: &str,
     ) -> (
         Vec<ParseError>,
-        Result<Vec<Spanned<TopLevelExpr>>, LexicalError>,
+        Result<Vec<Spanned<TopLevelExpr<'_>>>, LexicalError<'_>>,
     ) {
         let mut errs = Vec::new();
         let lexer = lexer::Lexer::new(inp);
@@ -184,7 +222,7 @@ mod tests {
                     DefVar::new(
                         (54, (), 60),
                         (61, "foo".into(), 64),
-                        Spanned::from((65, Atom::from("bar").into(), 70))
+                        Spanned::from((65, Atom::from("bar"), 70))
                     )
                     .into(),
                     71
@@ -222,8 +260,8 @@ mod tests {
                     DefWidget::new(
                         (278, (), 287),
                         (288, "baz".into(), 291),
-                        Vec::<DefWidgetParam>::new(),
-                        Vec::<DefWidgetChild>::new()
+                        Vec::<Spanned<DefWidgetParam>>::new(),
+                        Vec::<WidgetNode>::new()
                     )
                     .into(),
                     295
@@ -234,8 +272,8 @@ mod tests {
                     DefWidget::new(
                         (309, (), 318),
                         (319, "sidestuff".into(), 328),
-                        Vec::<DefWidgetParam>::new(),
-                        Vec::<DefWidgetChild>::new()
+                        Vec::<Spanned<DefWidgetParam>>::new(),
+                        Vec::<WidgetNode>::new()
                     )
                     .into(),
                     332
@@ -247,11 +285,11 @@ mod tests {
                         (346, (), 355),
                         (356, "metric".into(), 362),
                         vec![
-                            DefWidgetParam::new((364, "label".into(), 369), false),
-                            DefWidgetParam::new((370, "value".into(), 375), false),
-                            DefWidgetParam::new((376, "onchange".into(), 384), false)
+                            (364, DefWidgetParam::new((364, "label".into(), 369), false), 369),
+                            (370, DefWidgetParam::new((370, "value".into(), 375), false), 375),
+                            (376, DefWidgetParam::new((376, "onchange".into(), 384), false), 384)
                         ],
-                        Vec::<DefWidgetChild>::new()
+                        Vec::<WidgetNode>::new()
                     )
                     .into(),
                     386
@@ -262,6 +300,7 @@ mod tests {
                     DefWindow::new(
                         (400, 409),
                         (410, "bar".into(), 413),
+                        Vec::<Spanned<DefWidgetParam>>::new(),
                         vec![
                             DefWindowArg::new(
                                 (415, "type".into(), 419),
@@ -276,7 +315,7 @@ mod tests {
                                     vec![
                                         WidgetCallArg::new(
                                             (452, "align".into(), 457),
-                                            Spanned::from((458, "center".into(), 466))
+                                            Spanned::from((458, Atom::from("center"), 466))
                                         )
                                     ],
                                     vec![
@@ -286,7 +325,7 @@ mod tests {
                                             WidgetCall::new(
                                                 (514, "sidestuff".into(), 523),
                                                 Vec::<WidgetCallArg>::new(),
-                                                Vec::<WidgetCallChild>::new(),
+                                                Vec::<WidgetNode>::new(),
                                             ),
                                             524,
                                         ))
@@ -307,7 +346,7 @@ mod tests {
                         (540, 547),
                         vec![LiteralArg::new(
                             (549, "content".into(), 556),
-                            Spanned::from((557, "(button 'click')".to_owned(), 575))
+                            Spanned::from((557, Cow::Borrowed("(button 'click')"), 575))
                     )])
                     .into(),
                     576
@@ -1,21 +1,43 @@
-use super::expr::PrimitiveExpr;
+use crate::spanned::Spanned;
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub enum UnaryOperation {
-    Not(Box<PrimitiveExpr>),
+use super::{expr::PrimitiveExpr, into_owned::IntoOwned, shift_spans::ShiftSpans};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum UnaryOperation<'inp> {
+    Not(Box<Spanned<PrimitiveExpr<'inp>>>),
 }
 
-impl UnaryOperation {
-    pub fn new_not(value: impl Into<PrimitiveExpr>) -> Self {
+impl<'inp> UnaryOperation<'inp> {
+    pub fn new_not(value: impl Into<Spanned<PrimitiveExpr<'inp>>>) -> Self {
         Self::Not(Box::new(value.into()))
     }
 }
 
+impl IntoOwned for UnaryOperation<'_> {
+    type Owned = UnaryOperation<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Not(value) => UnaryOperation::Not(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for UnaryOperation<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Not(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        ast::ParseError,
+        ast::{symbol::Symbol, ParseError},
         lexer::{Lexer, LexicalError},
     };
 
@@ -24,10 +46,34 @@ mod tests {
         let (errs, ast) = test("!false");
 
         assert_eq!(errs, Vec::new());
-        assert_eq!(ast, Ok(UnaryOperation::new_not(false).into()));
+        assert_eq!(
+            ast,
+            Ok((0, UnaryOperation::new_not((1, false.into(), 6)).into(), 6).into())
+        );
+    }
+
+    #[test]
+    fn not_symbol() {
+        let (errs, ast) = test("!visible");
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok((
+                0,
+                UnaryOperation::new_not((1, Symbol::new("visible").into(), 8)).into(),
+                8
+            )
+                .into())
+        );
     }
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<PrimitiveExpr, LexicalError>) {
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<PrimitiveExpr<'_>>, LexicalError<'_>>,
+    ) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -0,0 +1,556 @@
+//! Read-only traversal of the AST.
+//!
+//! Every `visit_*` method defaults to the matching `walk_*` function, which visits the children
+//! of the node. Implementors override the methods for the nodes they care about and call the
+//! `walk_*` function themselves if they still want to descend.
+//!
+//! Nodes are visited with their spans. Nodes without a span of their own share the span of the
+//! node they make up, e.g. an [`Array`] the span of its expression, and arguments and object
+//! entries span from their name to the end of their value.
+//!
+//! Keyword argument names such as `:text` are visited with [`Visitor::visit_arg_name`],
+//! everything else that is a [`Symbol`] (definition names, parameters, bindings and references)
+//! with [`Visitor::visit_symbol`].
+
+use crate::spanned::Spanned;
+
+use super::{
+    array::Array,
+    atom::Atom,
+    binary_operation::BinaryOperation,
+    children::Children,
+    comparison_operation::ComparisonOperation,
+    def_listen::{DefListen, DefListenArg, DefListenArgValue},
+    def_poll::{DefPoll, DefPollArg, DefPollArgValue},
+    def_var::{DefVar, DefVarValue},
+    def_widget::{DefWidget, DefWidgetParam},
+    def_window::{DefWindow, DefWindowArg, DefWindowArgValue},
+    expr::PrimitiveExpr,
+    for_loop::ForLoop,
+    function_call::FunctionCall,
+    include::Include,
+    literal::{Literal, LiteralArg, LiteralArgValue},
+    object::{Object, ObjectEntry},
+    symbol::Symbol,
+    ternary_operation::TernaryOperation,
+    top_level_expr::TopLevelExpr,
+    unary_operation::UnaryOperation,
+    widget_call::{WidgetCall, WidgetCallArg, WidgetCallArgValue},
+    widget_node::WidgetNode,
+};
+
+pub trait Visitor<'ast> {
+    fn visit_top_level_expr(&mut self, node: Spanned<&'ast TopLevelExpr>) {
+        walk_top_level_expr(self, node)
+    }
+
+    fn visit_def_listen(&mut self, node: Spanned<&'ast DefListen>) {
+        walk_def_listen(self, node)
+    }
+
+    fn visit_def_listen_arg(&mut self, node: Spanned<&'ast DefListenArg>) {
+        walk_def_listen_arg(self, node)
+    }
+
+    fn visit_def_poll(&mut self, node: Spanned<&'ast DefPoll>) {
+        walk_def_poll(self, node)
+    }
+
+    fn visit_def_poll_arg(&mut self, node: Spanned<&'ast DefPollArg>) {
+        walk_def_poll_arg(self, node)
+    }
+
+    fn visit_def_var(&mut self, node: Spanned<&'ast DefVar>) {
+        walk_def_var(self, node)
+    }
+
+    fn visit_def_widget(&mut self, node: Spanned<&'ast DefWidget>) {
+        walk_def_widget(self, node)
+    }
+
+    fn visit_def_widget_param(&mut self, node: Spanned<&'ast DefWidgetParam>) {
+        walk_def_widget_param(self, node)
+    }
+
+    fn visit_def_window(&mut self, node: Spanned<&'ast DefWindow>) {
+        walk_def_window(self, node)
+    }
+
+    fn visit_def_window_arg(&mut self, node: Spanned<&'ast DefWindowArg>) {
+        walk_def_window_arg(self, node)
+    }
+
+    fn visit_include(&mut self, node: Spanned<&'ast Include>) {
+        walk_include(self, node)
+    }
+
+    fn visit_literal(&mut self, node: Spanned<&'ast Literal>) {
+        walk_literal(self, node)
+    }
+
+    fn visit_literal_arg(&mut self, node: Spanned<&'ast LiteralArg>) {
+        walk_literal_arg(self, node)
+    }
+
+    /// Widget nodes are not spanned as a whole, each variant but [`WidgetNode::Err`] has a span.
+    fn visit_widget_node(&mut self, node: &'ast WidgetNode) {
+        walk_widget_node(self, node)
+    }
+
+    fn visit_widget_call(&mut self, node: Spanned<&'ast WidgetCall>) {
+        walk_widget_call(self, node)
+    }
+
+    fn visit_widget_call_arg(&mut self, node: Spanned<&'ast WidgetCallArg>) {
+        walk_widget_call_arg(self, node)
+    }
+
+    fn visit_children(&mut self, node: Spanned<&'ast Children>) {
+        walk_children(self, node)
+    }
+
+    fn visit_for_loop(&mut self, node: Spanned<&'ast ForLoop>) {
+        walk_for_loop(self, node)
+    }
+
+    fn visit_expr(&mut self, node: Spanned<&'ast PrimitiveExpr>) {
+        walk_expr(self, node)
+    }
+
+    fn visit_array(&mut self, node: Spanned<&'ast Array>) {
+        walk_array(self, node)
+    }
+
+    fn visit_object(&mut self, node: Spanned<&'ast Object>) {
+        walk_object(self, node)
+    }
+
+    fn visit_object_entry(&mut self, node: Spanned<&'ast ObjectEntry>) {
+        walk_object_entry(self, node)
+    }
+
+    fn visit_binary_operation(&mut self, node: Spanned<&'ast BinaryOperation>) {
+        walk_binary_operation(self, node)
+    }
+
+    fn visit_comparison_operation(&mut self, node: Spanned<&'ast ComparisonOperation>) {
+        walk_comparison_operation(self, node)
+    }
+
+    fn visit_function_call(&mut self, node: Spanned<&'ast FunctionCall>) {
+        walk_function_call(self, node)
+    }
+
+    fn visit_ternary_operation(&mut self, node: Spanned<&'ast TernaryOperation>) {
+        walk_ternary_operation(self, node)
+    }
+
+    fn visit_unary_operation(&mut self, node: Spanned<&'ast UnaryOperation>) {
+        walk_unary_operation(self, node)
+    }
+
+    fn visit_atom(&mut self, node: Spanned<&'ast Atom>) {
+        walk_atom(self, node)
+    }
+
+    fn visit_symbol(&mut self, _node: Spanned<&'ast Symbol>) {}
+
+    fn visit_arg_name(&mut self, _node: Spanned<&'ast Symbol>) {}
+}
+
+pub fn walk_top_level_expr<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast TopLevelExpr>,
+) {
+    let Spanned(l, node, r) = node;
+
+    match node {
+        TopLevelExpr::DefListen(def_listen) => visitor.visit_def_listen(Spanned(l, def_listen, r)),
+        TopLevelExpr::DefPoll(def_poll) => visitor.visit_def_poll(Spanned(l, def_poll, r)),
+        TopLevelExpr::DefVar(def_var) => visitor.visit_def_var(Spanned(l, def_var, r)),
+        TopLevelExpr::DefWidget(def_widget) => visitor.visit_def_widget(Spanned(l, def_widget, r)),
+        TopLevelExpr::DefWindow(def_window) => visitor.visit_def_window(Spanned(l, def_window, r)),
+        TopLevelExpr::Err => {}
+        TopLevelExpr::Include(include) => visitor.visit_include(Spanned(l, include, r)),
+        TopLevelExpr::Literal(literal) => visitor.visit_literal(Spanned(l, literal, r)),
+    }
+}
+
+pub fn walk_def_listen<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefListen>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    for arg in &node.1.args {
+        let r = match &arg.value {
+            DefListenArgValue::Atom(atom) => atom.2,
+        };
+        visitor.visit_def_listen_arg(Spanned(arg.name.0, arg, r));
+    }
+}
+
+pub fn walk_def_listen_arg<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefListenArg>,
+) {
+    visitor.visit_arg_name(node.1.name.as_ref());
+    match &node.1.value {
+        DefListenArgValue::Atom(atom) => visitor.visit_atom(atom.as_ref()),
+    }
+}
+
+pub fn walk_def_poll<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefPoll>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    for arg in &node.1.args {
+        let r = match &arg.value {
+            DefPollArgValue::Atom(atom) => atom.2,
+        };
+        visitor.visit_def_poll_arg(Spanned(arg.name.0, arg, r));
+    }
+}
+
+pub fn walk_def_poll_arg<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefPollArg>,
+) {
+    visitor.visit_arg_name(node.1.name.as_ref());
+    match &node.1.value {
+        DefPollArgValue::Atom(atom) => visitor.visit_atom(atom.as_ref()),
+    }
+}
+
+pub fn walk_def_var<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: Spanned<&'ast DefVar>) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    match &node.1.value {
+        DefVarValue::Atom(atom) => visitor.visit_atom(atom.as_ref()),
+    }
+}
+
+pub fn walk_def_widget<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefWidget>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    for param in &node.1.params {
+        visitor.visit_def_widget_param(param.as_ref());
+    }
+    for child in &node.1.children {
+        visitor.visit_widget_node(child);
+    }
+}
+
+pub fn walk_def_widget_param<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefWidgetParam>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+}
+
+pub fn walk_def_window<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefWindow>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    for param in &node.1.params {
+        visitor.visit_def_widget_param(param.as_ref());
+    }
+    for arg in &node.1.args {
+        let r = match &arg.value {
+            DefWindowArgValue::Atom(Spanned(_, _, r))
+            | DefWindowArgValue::Symbol(Spanned(_, _, r))
+            | DefWindowArgValue::WidgetCall(Spanned(_, _, r)) => *r,
+        };
+        visitor.visit_def_window_arg(Spanned(arg.name.0, arg, r));
+    }
+    for content in &node.1.content {
+        visitor.visit_widget_node(content);
+    }
+}
+
+pub fn walk_def_window_arg<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast DefWindowArg>,
+) {
+    visitor.visit_arg_name(node.1.name.as_ref());
+    match &node.1.value {
+        DefWindowArgValue::Atom(atom) => visitor.visit_atom(atom.as_ref()),
+        DefWindowArgValue::Symbol(symbol) => visitor.visit_symbol(symbol.as_ref()),
+        DefWindowArgValue::WidgetCall(widget_call) => {
+            visitor.visit_widget_call(widget_call.as_ref())
+        }
+    }
+}
+
+pub fn walk_include<'ast, V: Visitor<'ast> + ?Sized>(
+    _visitor: &mut V,
+    _node: Spanned<&'ast Include>,
+) {
+}
+
+pub fn walk_literal<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast Literal>,
+) {
+    for arg in &node.1.args {
+        let r = match &arg.value {
+            LiteralArgValue::String(Spanned(_, _, r))
+            | LiteralArgValue::Symbol(Spanned(_, _, r)) => *r,
+        };
+        visitor.visit_literal_arg(Spanned(arg.name.0, arg, r));
+    }
+}
+
+pub fn walk_literal_arg<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast LiteralArg>,
+) {
+    visitor.visit_arg_name(node.1.name.as_ref());
+    match &node.1.value {
+        LiteralArgValue::String(_) => {}
+        LiteralArgValue::Symbol(symbol) => visitor.visit_symbol(symbol.as_ref()),
+    }
+}
+
+pub fn walk_widget_node<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: &'ast WidgetNode) {
+    match node {
+        WidgetNode::Atom(atom) => visitor.visit_atom(atom.as_ref()),
+        WidgetNode::Children(children) => visitor.visit_children(children.as_ref()),
+        WidgetNode::Err => {}
+        WidgetNode::Expr(expr) => visitor.visit_expr(expr.as_ref()),
+        WidgetNode::ForLoop(for_loop) => visitor.visit_for_loop(for_loop.as_ref()),
+        WidgetNode::WidgetCall(widget_call) => visitor.visit_widget_call(widget_call.as_ref()),
+    }
+}
+
+pub fn walk_widget_call<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast WidgetCall>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    for arg in &node.1.args {
+        let r = match &arg.value {
+            WidgetCallArgValue::Atom(Spanned(_, _, r))
+            | WidgetCallArgValue::Expr(Spanned(_, _, r))
+            | WidgetCallArgValue::Symbol(Spanned(_, _, r)) => *r,
+        };
+        visitor.visit_widget_call_arg(Spanned(arg.name.0, arg, r));
+    }
+    for child in &node.1.children {
+        visitor.visit_widget_node(child);
+    }
+}
+
+pub fn walk_widget_call_arg<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast WidgetCallArg>,
+) {
+    visitor.visit_arg_name(node.1.name.as_ref());
+    match &node.1.value {
+        WidgetCallArgValue::Atom(atom) => visitor.visit_atom(atom.as_ref()),
+        WidgetCallArgValue::Expr(expr) => visitor.visit_expr(expr.as_ref()),
+        WidgetCallArgValue::Symbol(symbol) => visitor.visit_symbol(symbol.as_ref()),
+    }
+}
+
+pub fn walk_children<'ast, V: Visitor<'ast> + ?Sized>(
+    _visitor: &mut V,
+    _node: Spanned<&'ast Children>,
+) {
+}
+
+pub fn walk_for_loop<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast ForLoop>,
+) {
+    visitor.visit_symbol(node.1.binding.as_ref());
+    visitor.visit_expr(node.1.iterable.as_ref());
+    for child in &node.1.body {
+        visitor.visit_widget_node(child);
+    }
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast PrimitiveExpr>,
+) {
+    let Spanned(l, node, r) = node;
+
+    match node {
+        PrimitiveExpr::Array(array) => visitor.visit_array(Spanned(l, array, r)),
+        PrimitiveExpr::Atom(atom) => visitor.visit_atom(Spanned(l, atom, r)),
+        PrimitiveExpr::BinaryOperation(operation) => {
+            visitor.visit_binary_operation(Spanned(l, operation, r))
+        }
+        PrimitiveExpr::ComparisonOperation(operation) => {
+            visitor.visit_comparison_operation(Spanned(l, operation, r))
+        }
+        PrimitiveExpr::FunctionCall(function_call) => {
+            visitor.visit_function_call(Spanned(l, function_call, r))
+        }
+        PrimitiveExpr::Object(object) => visitor.visit_object(Spanned(l, object, r)),
+        PrimitiveExpr::Symbol(symbol) => visitor.visit_symbol(Spanned(l, symbol, r)),
+        PrimitiveExpr::TernaryOperation(operation) => {
+            visitor.visit_ternary_operation(Spanned(l, operation, r))
+        }
+        PrimitiveExpr::UnaryOperation(operation) => {
+            visitor.visit_unary_operation(Spanned(l, operation, r))
+        }
+    }
+}
+
+pub fn walk_array<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: Spanned<&'ast Array>) {
+    for item in &node.1.items {
+        visitor.visit_expr(item.as_ref());
+    }
+}
+
+pub fn walk_object<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: Spanned<&'ast Object>) {
+    for entry in &node.1.entries {
+        visitor.visit_object_entry(Spanned(entry.key.0, entry, entry.value.2));
+    }
+}
+
+pub fn walk_object_entry<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast ObjectEntry>,
+) {
+    visitor.visit_expr(node.1.value.as_ref());
+}
+
+pub fn walk_binary_operation<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast BinaryOperation>,
+) {
+    visitor.visit_expr((*node.1.lhs).as_ref());
+    visitor.visit_expr((*node.1.rhs).as_ref());
+}
+
+pub fn walk_comparison_operation<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast ComparisonOperation>,
+) {
+    visitor.visit_expr((*node.1.lhs).as_ref());
+    visitor.visit_expr((*node.1.rhs).as_ref());
+}
+
+pub fn walk_function_call<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast FunctionCall>,
+) {
+    visitor.visit_symbol(node.1.name.as_ref());
+    for arg in &node.1.args {
+        visitor.visit_expr(arg.as_ref());
+    }
+}
+
+pub fn walk_ternary_operation<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast TernaryOperation>,
+) {
+    visitor.visit_expr((*node.1.cond).as_ref());
+    visitor.visit_expr((*node.1.true_branch).as_ref());
+    visitor.visit_expr((*node.1.false_branch).as_ref());
+}
+
+pub fn walk_unary_operation<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: Spanned<&'ast UnaryOperation>,
+) {
+    match node.1 {
+        UnaryOperation::Not(operand) => visitor.visit_expr((**operand).as_ref()),
+    }
+}
+
+pub fn walk_atom<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: Spanned<&'ast Atom>) {
+    let Spanned(l, node, r) = node;
+
+    match node {
+        Atom::Bool(_) | Atom::Number(_) | Atom::StrLit(_) => {}
+        Atom::Symbol(symbol) => visitor.visit_symbol(Spanned(l, symbol, r)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{lexer::Lexer, spanned::Spanned};
+
+    #[derive(Default)]
+    struct SymbolCollector<'ast> {
+        symbols: Vec<&'ast str>,
+    }
+
+    impl<'ast> Visitor<'ast> for SymbolCollector<'ast> {
+        fn visit_symbol(&mut self, node: Spanned<&'ast Symbol>) {
+            self.symbols.push(&node.1 .0);
+        }
+    }
+
+    #[derive(Default)]
+    struct WidgetCallCounter {
+        count: usize,
+    }
+
+    impl<'ast> Visitor<'ast> for WidgetCallCounter {
+        fn visit_widget_call(&mut self, node: Spanned<&'ast WidgetCall>) {
+            self.count += 1;
+            walk_widget_call(self, node);
+        }
+
+        // Stops the traversal at loops.
+        fn visit_for_loop(&mut self, _node: Spanned<&'ast ForLoop>) {}
+    }
+
+    #[test]
+    fn collects_every_symbol() {
+        let ast = test(
+            r#"(defwidget bar [?icon]
+                (box :class icon
+                    {volume > 50 ? round(volume, 0) : [muted]}
+                    (for item in items (label :text item))))"#,
+        );
+
+        let mut collector = SymbolCollector::default();
+        collector.visit_top_level_expr(ast.as_ref());
+
+        assert_eq!(
+            collector.symbols,
+            vec![
+                "bar", "icon", "box", "icon", "volume", "round", "volume", "muted", "item",
+                "items", "label", "item",
+            ]
+        );
+    }
+
+    #[test]
+    fn overrides_control_descent() {
+        let ast = test(
+            r#"(defwindow main
+                (box (label) (for item in items (label)) (box (label))))"#,
+        );
+
+        let mut counter = WidgetCallCounter::default();
+        counter.visit_top_level_expr(ast.as_ref());
+
+        assert_eq!(counter.count, 4);
+    }
+
+    fn test(inp: &str) -> Spanned<TopLevelExpr<'_>> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::TopLevelExprParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).unwrap();
+        assert_eq!(errors, Vec::new());
+
+        result
+    }
+}
@@ -0,0 +1,436 @@
+//! Mutable traversal of the AST, see [`super::visitor`] for the read-only counterpart.
+//!
+//! The methods mirror the ones of [`super::visitor::Visitor`] with a `_mut` suffix.
+
+use super::{
+    array::Array,
+    atom::Atom,
+    binary_operation::BinaryOperation,
+    children::Children,
+    comparison_operation::ComparisonOperation,
+    def_listen::{DefListen, DefListenArg, DefListenArgValue},
+    def_poll::{DefPoll, DefPollArg, DefPollArgValue},
+    def_var::{DefVar, DefVarValue},
+    def_widget::{DefWidget, DefWidgetParam},
+    def_window::{DefWindow, DefWindowArg, DefWindowArgValue},
+    expr::PrimitiveExpr,
+    for_loop::ForLoop,
+    function_call::FunctionCall,
+    include::Include,
+    literal::{Literal, LiteralArg, LiteralArgValue},
+    object::{Object, ObjectEntry},
+    symbol::Symbol,
+    ternary_operation::TernaryOperation,
+    top_level_expr::TopLevelExpr,
+    unary_operation::UnaryOperation,
+    widget_call::{WidgetCall, WidgetCallArg, WidgetCallArgValue},
+    widget_node::WidgetNode,
+};
+
+pub trait VisitorMut {
+    fn visit_top_level_expr_mut(&mut self, node: &mut TopLevelExpr) {
+        walk_top_level_expr_mut(self, node)
+    }
+
+    fn visit_def_listen_mut(&mut self, node: &mut DefListen) {
+        walk_def_listen_mut(self, node)
+    }
+
+    fn visit_def_listen_arg_mut(&mut self, node: &mut DefListenArg) {
+        walk_def_listen_arg_mut(self, node)
+    }
+
+    fn visit_def_poll_mut(&mut self, node: &mut DefPoll) {
+        walk_def_poll_mut(self, node)
+    }
+
+    fn visit_def_poll_arg_mut(&mut self, node: &mut DefPollArg) {
+        walk_def_poll_arg_mut(self, node)
+    }
+
+    fn visit_def_var_mut(&mut self, node: &mut DefVar) {
+        walk_def_var_mut(self, node)
+    }
+
+    fn visit_def_widget_mut(&mut self, node: &mut DefWidget) {
+        walk_def_widget_mut(self, node)
+    }
+
+    fn visit_def_widget_param_mut(&mut self, node: &mut DefWidgetParam) {
+        walk_def_widget_param_mut(self, node)
+    }
+
+    fn visit_def_window_mut(&mut self, node: &mut DefWindow) {
+        walk_def_window_mut(self, node)
+    }
+
+    fn visit_def_window_arg_mut(&mut self, node: &mut DefWindowArg) {
+        walk_def_window_arg_mut(self, node)
+    }
+
+    fn visit_include_mut(&mut self, node: &mut Include) {
+        walk_include_mut(self, node)
+    }
+
+    fn visit_literal_mut(&mut self, node: &mut Literal) {
+        walk_literal_mut(self, node)
+    }
+
+    fn visit_literal_arg_mut(&mut self, node: &mut LiteralArg) {
+        walk_literal_arg_mut(self, node)
+    }
+
+    fn visit_widget_node_mut(&mut self, node: &mut WidgetNode) {
+        walk_widget_node_mut(self, node)
+    }
+
+    fn visit_widget_call_mut(&mut self, node: &mut WidgetCall) {
+        walk_widget_call_mut(self, node)
+    }
+
+    fn visit_widget_call_arg_mut(&mut self, node: &mut WidgetCallArg) {
+        walk_widget_call_arg_mut(self, node)
+    }
+
+    fn visit_children_mut(&mut self, node: &mut Children) {
+        walk_children_mut(self, node)
+    }
+
+    fn visit_for_loop_mut(&mut self, node: &mut ForLoop) {
+        walk_for_loop_mut(self, node)
+    }
+
+    fn visit_expr_mut(&mut self, node: &mut PrimitiveExpr) {
+        walk_expr_mut(self, node)
+    }
+
+    fn visit_array_mut(&mut self, node: &mut Array) {
+        walk_array_mut(self, node)
+    }
+
+    fn visit_object_mut(&mut self, node: &mut Object) {
+        walk_object_mut(self, node)
+    }
+
+    fn visit_object_entry_mut(&mut self, node: &mut ObjectEntry) {
+        walk_object_entry_mut(self, node)
+    }
+
+    fn visit_binary_operation_mut(&mut self, node: &mut BinaryOperation) {
+        walk_binary_operation_mut(self, node)
+    }
+
+    fn visit_comparison_operation_mut(&mut self, node: &mut ComparisonOperation) {
+        walk_comparison_operation_mut(self, node)
+    }
+
+    fn visit_function_call_mut(&mut self, node: &mut FunctionCall) {
+        walk_function_call_mut(self, node)
+    }
+
+    fn visit_ternary_operation_mut(&mut self, node: &mut TernaryOperation) {
+        walk_ternary_operation_mut(self, node)
+    }
+
+    fn visit_unary_operation_mut(&mut self, node: &mut UnaryOperation) {
+        walk_unary_operation_mut(self, node)
+    }
+
+    fn visit_atom_mut(&mut self, node: &mut Atom) {
+        walk_atom_mut(self, node)
+    }
+
+    fn visit_symbol_mut(&mut self, _node: &mut Symbol) {}
+}
+
+pub fn walk_top_level_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut TopLevelExpr) {
+    match node {
+        TopLevelExpr::DefListen(def_listen) => visitor.visit_def_listen_mut(def_listen),
+        TopLevelExpr::DefPoll(def_poll) => visitor.visit_def_poll_mut(def_poll),
+        TopLevelExpr::DefVar(def_var) => visitor.visit_def_var_mut(def_var),
+        TopLevelExpr::DefWidget(def_widget) => visitor.visit_def_widget_mut(def_widget),
+        TopLevelExpr::DefWindow(def_window) => visitor.visit_def_window_mut(def_window),
+        TopLevelExpr::Err => {}
+        TopLevelExpr::Include(include) => visitor.visit_include_mut(include),
+        TopLevelExpr::Literal(literal) => visitor.visit_literal_mut(literal),
+    }
+}
+
+pub fn walk_def_listen_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefListen) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    for arg in &mut node.args {
+        visitor.visit_def_listen_arg_mut(arg);
+    }
+}
+
+pub fn walk_def_listen_arg_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefListenArg) {
+    match &mut node.value {
+        DefListenArgValue::Atom(atom) => visitor.visit_atom_mut(&mut atom.1),
+    }
+}
+
+pub fn walk_def_poll_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefPoll) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    for arg in &mut node.args {
+        visitor.visit_def_poll_arg_mut(arg);
+    }
+}
+
+pub fn walk_def_poll_arg_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefPollArg) {
+    match &mut node.value {
+        DefPollArgValue::Atom(atom) => visitor.visit_atom_mut(&mut atom.1),
+    }
+}
+
+pub fn walk_def_var_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefVar) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    match &mut node.value {
+        DefVarValue::Atom(atom) => visitor.visit_atom_mut(&mut atom.1),
+    }
+}
+
+pub fn walk_def_widget_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefWidget) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    for param in &mut node.params {
+        visitor.visit_def_widget_param_mut(&mut param.1);
+    }
+    for child in &mut node.children {
+        visitor.visit_widget_node_mut(child);
+    }
+}
+
+pub fn walk_def_widget_param_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut DefWidgetParam,
+) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+}
+
+pub fn walk_def_window_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefWindow) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    for param in &mut node.params {
+        visitor.visit_def_widget_param_mut(&mut param.1);
+    }
+    for arg in &mut node.args {
+        visitor.visit_def_window_arg_mut(arg);
+    }
+    for content in &mut node.content {
+        visitor.visit_widget_node_mut(content);
+    }
+}
+
+pub fn walk_def_window_arg_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut DefWindowArg) {
+    match &mut node.value {
+        DefWindowArgValue::Atom(atom) => visitor.visit_atom_mut(&mut atom.1),
+        DefWindowArgValue::Symbol(symbol) => visitor.visit_symbol_mut(&mut symbol.1),
+        DefWindowArgValue::WidgetCall(widget_call) => {
+            visitor.visit_widget_call_mut(&mut widget_call.1)
+        }
+    }
+}
+
+pub fn walk_include_mut<V: VisitorMut + ?Sized>(_visitor: &mut V, _node: &mut Include) {}
+
+pub fn walk_literal_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Literal) {
+    for arg in &mut node.args {
+        visitor.visit_literal_arg_mut(arg);
+    }
+}
+
+pub fn walk_literal_arg_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut LiteralArg) {
+    match &mut node.value {
+        LiteralArgValue::String(_) => {}
+        LiteralArgValue::Symbol(symbol) => visitor.visit_symbol_mut(&mut symbol.1),
+    }
+}
+
+pub fn walk_widget_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut WidgetNode) {
+    match node {
+        WidgetNode::Atom(atom) => visitor.visit_atom_mut(&mut atom.1),
+        WidgetNode::Children(children) => visitor.visit_children_mut(&mut children.1),
+        WidgetNode::Err => {}
+        WidgetNode::Expr(expr) => visitor.visit_expr_mut(&mut expr.1),
+        WidgetNode::ForLoop(for_loop) => visitor.visit_for_loop_mut(&mut for_loop.1),
+        WidgetNode::WidgetCall(widget_call) => visitor.visit_widget_call_mut(&mut widget_call.1),
+    }
+}
+
+pub fn walk_widget_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut WidgetCall) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    for arg in &mut node.args {
+        visitor.visit_widget_call_arg_mut(arg);
+    }
+    for child in &mut node.children {
+        visitor.visit_widget_node_mut(child);
+    }
+}
+
+pub fn walk_widget_call_arg_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut WidgetCallArg) {
+    match &mut node.value {
+        WidgetCallArgValue::Atom(atom) => visitor.visit_atom_mut(&mut atom.1),
+        WidgetCallArgValue::Expr(expr) => visitor.visit_expr_mut(&mut expr.1),
+        WidgetCallArgValue::Symbol(symbol) => visitor.visit_symbol_mut(&mut symbol.1),
+    }
+}
+
+pub fn walk_children_mut<V: VisitorMut + ?Sized>(_visitor: &mut V, _node: &mut Children) {}
+
+pub fn walk_for_loop_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut ForLoop) {
+    visitor.visit_symbol_mut(&mut node.binding.1);
+    visitor.visit_expr_mut(&mut node.iterable.1);
+    for child in &mut node.body {
+        visitor.visit_widget_node_mut(child);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut PrimitiveExpr) {
+    match node {
+        PrimitiveExpr::Array(array) => visitor.visit_array_mut(array),
+        PrimitiveExpr::Atom(atom) => visitor.visit_atom_mut(atom),
+        PrimitiveExpr::BinaryOperation(operation) => visitor.visit_binary_operation_mut(operation),
+        PrimitiveExpr::ComparisonOperation(operation) => {
+            visitor.visit_comparison_operation_mut(operation)
+        }
+        PrimitiveExpr::FunctionCall(function_call) => {
+            visitor.visit_function_call_mut(function_call)
+        }
+        PrimitiveExpr::Object(object) => visitor.visit_object_mut(object),
+        PrimitiveExpr::Symbol(symbol) => visitor.visit_symbol_mut(symbol),
+        PrimitiveExpr::TernaryOperation(operation) => {
+            visitor.visit_ternary_operation_mut(operation)
+        }
+        PrimitiveExpr::UnaryOperation(operation) => visitor.visit_unary_operation_mut(operation),
+    }
+}
+
+pub fn walk_array_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Array) {
+    for item in &mut node.items {
+        visitor.visit_expr_mut(&mut item.1);
+    }
+}
+
+pub fn walk_object_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Object) {
+    for entry in &mut node.entries {
+        visitor.visit_object_entry_mut(entry);
+    }
+}
+
+pub fn walk_object_entry_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut ObjectEntry) {
+    visitor.visit_expr_mut(&mut node.value.1);
+}
+
+pub fn walk_binary_operation_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut BinaryOperation,
+) {
+    visitor.visit_expr_mut(&mut node.lhs.1);
+    visitor.visit_expr_mut(&mut node.rhs.1);
+}
+
+pub fn walk_comparison_operation_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut ComparisonOperation,
+) {
+    visitor.visit_expr_mut(&mut node.lhs.1);
+    visitor.visit_expr_mut(&mut node.rhs.1);
+}
+
+pub fn walk_function_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut FunctionCall) {
+    visitor.visit_symbol_mut(&mut node.name.1);
+    for arg in &mut node.args {
+        visitor.visit_expr_mut(&mut arg.1);
+    }
+}
+
+pub fn walk_ternary_operation_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut TernaryOperation,
+) {
+    visitor.visit_expr_mut(&mut node.cond.1);
+    visitor.visit_expr_mut(&mut node.true_branch.1);
+    visitor.visit_expr_mut(&mut node.false_branch.1);
+}
+
+pub fn walk_unary_operation_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut UnaryOperation,
+) {
+    match node {
+        UnaryOperation::Not(operand) => visitor.visit_expr_mut(&mut operand.1),
+    }
+}
+
+pub fn walk_atom_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Atom) {
+    match node {
+        Atom::Bool(_) | Atom::Number(_) | Atom::StrLit(_) => {}
+        Atom::Symbol(symbol) => visitor.visit_symbol_mut(symbol),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{ast::visitor::Visitor, lexer::Lexer, spanned::Spanned};
+
+    struct Renamer {
+        from: &'static str,
+        to: &'static str,
+    }
+
+    impl VisitorMut for Renamer {
+        fn visit_symbol_mut(&mut self, node: &mut Symbol) {
+            if node.0 == self.from {
+                node.0 = self.to.to_owned().into();
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct SymbolCollector {
+        symbols: Vec<String>,
+    }
+
+    impl<'ast> Visitor<'ast> for SymbolCollector {
+        fn visit_symbol(&mut self, node: Spanned<&'ast Symbol>) {
+            self.symbols.push(node.1 .0.to_string());
+        }
+    }
+
+    #[test]
+    fn renames_symbols() {
+        let mut ast = test(
+            r#"(defwidget bar [vol]
+                (box :class vol {vol > 50 ? round(vol, 0) : [vol]} (label :vol "vol")))"#,
+        );
+
+        Renamer {
+            from: "vol",
+            to: "volume",
+        }
+        .visit_top_level_expr_mut(&mut ast.1);
+
+        let mut collector = SymbolCollector::default();
+        collector.visit_top_level_expr(ast.as_ref());
+
+        assert_eq!(
+            collector.symbols,
+            vec!["bar", "volume", "box", "volume", "volume", "round", "volume", "volume", "label"]
+        );
+    }
+
+    fn test(inp: &str) -> Spanned<TopLevelExpr<'_>> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::TopLevelExprParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).unwrap();
+        assert_eq!(errors, Vec::new());
+
+        result
+    }
+}
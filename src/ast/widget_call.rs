@@ -1,19 +1,23 @@
 use crate::spanned::Spanned;
 
-use super::{atom::Atom, symbol::Symbol};
+use super::{
+    atom::Atom, children::Children, expr::PrimitiveExpr, for_loop::ForLoop, into_owned::IntoOwned,
+    shift_spans::ShiftSpans, symbol::Symbol, widget_node::WidgetNode,
+};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct WidgetCall {
-    pub name: Spanned<Symbol>,
-    pub args: Vec<WidgetCallArg>,
-    pub children: Vec<WidgetCallChild>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WidgetCall<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub args: Vec<WidgetCallArg<'inp>>,
+    pub children: Vec<WidgetNode<'inp>>,
 }
 
-impl WidgetCall {
+impl<'inp> WidgetCall<'inp> {
     pub fn new(
-        name: impl Into<Spanned<Symbol>>,
-        args: Vec<WidgetCallArg>,
-        children: Vec<WidgetCallChild>,
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        args: Vec<WidgetCallArg<'inp>>,
+        children: Vec<WidgetNode<'inp>>,
     ) -> Self {
         Self {
             name: name.into(),
@@ -24,13 +28,17 @@ impl WidgetCall {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub struct WidgetCallArg {
-    pub name: Spanned<Symbol>,
-    pub value: WidgetCallArgValue,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WidgetCallArg<'inp> {
+    pub name: Spanned<Symbol<'inp>>,
+    pub value: WidgetCallArgValue<'inp>,
 }
 
-impl WidgetCallArg {
-    pub fn new(name: impl Into<Spanned<Symbol>>, value: impl Into<WidgetCallArgValue>) -> Self {
+impl<'inp> WidgetCallArg<'inp> {
+    pub fn new(
+        name: impl Into<Spanned<Symbol<'inp>>>,
+        value: impl Into<WidgetCallArgValue<'inp>>,
+    ) -> Self {
         Self {
             name: name.into(),
             value: value.into(),
@@ -39,41 +47,159 @@ impl WidgetCallArg {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum WidgetCallArgValue {
-    Atom(Spanned<Atom>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum WidgetCallArgValue<'inp> {
+    Atom(Spanned<Atom<'inp>>),
+    /// `{ ... }`
+    Expr(Spanned<PrimitiveExpr<'inp>>),
+    Symbol(Spanned<Symbol<'inp>>),
 }
 
-impl From<Spanned<Atom>> for WidgetCallArgValue {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for WidgetCallArgValue<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
+impl<'inp> From<Spanned<PrimitiveExpr<'inp>>> for WidgetCallArgValue<'inp> {
+    fn from(value: Spanned<PrimitiveExpr<'inp>>) -> Self {
+        Self::Expr(value)
+    }
+}
+
+impl<'inp> From<Spanned<Symbol<'inp>>> for WidgetCallArgValue<'inp> {
+    fn from(value: Spanned<Symbol<'inp>>) -> Self {
+        Self::Symbol(value)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
-pub enum WidgetCallChild {
-    Atom(Spanned<Atom>),
-    WidgetCall(Spanned<WidgetCall>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum WidgetCallChild<'inp> {
+    Atom(Spanned<Atom<'inp>>),
+    Children(Spanned<Children>),
+    Err,
+    ForLoop(Spanned<ForLoop<'inp>>),
+    WidgetCall(Spanned<WidgetCall<'inp>>),
 }
 
-impl From<Spanned<Atom>> for WidgetCallChild {
-    fn from(value: Spanned<Atom>) -> Self {
+impl<'inp> From<Spanned<Atom<'inp>>> for WidgetCallChild<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
         Self::Atom(value)
     }
 }
 
-impl From<Spanned<WidgetCall>> for WidgetCallChild {
-    fn from(value: Spanned<WidgetCall>) -> Self {
+impl<'inp> From<Spanned<Children>> for WidgetCallChild<'inp> {
+    fn from(value: Spanned<Children>) -> Self {
+        Self::Children(value)
+    }
+}
+
+impl<'inp> From<Spanned<ForLoop<'inp>>> for WidgetCallChild<'inp> {
+    fn from(value: Spanned<ForLoop<'inp>>) -> Self {
+        Self::ForLoop(value)
+    }
+}
+
+impl<'inp> From<Spanned<WidgetCall<'inp>>> for WidgetCallChild<'inp> {
+    fn from(value: Spanned<WidgetCall<'inp>>) -> Self {
         Self::WidgetCall(value)
     }
 }
 
+impl IntoOwned for WidgetCall<'_> {
+    type Owned = WidgetCall<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        WidgetCall {
+            name: self.name.into_owned(),
+            args: self.args.into_owned(),
+            children: self.children.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for WidgetCallArg<'_> {
+    type Owned = WidgetCallArg<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        WidgetCallArg {
+            name: self.name.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl IntoOwned for WidgetCallArgValue<'_> {
+    type Owned = WidgetCallArgValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => WidgetCallArgValue::Atom(value.into_owned()),
+            Self::Expr(value) => WidgetCallArgValue::Expr(value.into_owned()),
+            Self::Symbol(value) => WidgetCallArgValue::Symbol(value.into_owned()),
+        }
+    }
+}
+
+impl IntoOwned for WidgetCallChild<'_> {
+    type Owned = WidgetCallChild<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => WidgetCallChild::Atom(value.into_owned()),
+            Self::Children(value) => WidgetCallChild::Children(value),
+            Self::Err => WidgetCallChild::Err,
+            Self::ForLoop(value) => WidgetCallChild::ForLoop(value.into_owned()),
+            Self::WidgetCall(value) => WidgetCallChild::WidgetCall(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for WidgetCall<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self {
+            name,
+            args,
+            children,
+        } = self;
+        name.shift_spans(delta);
+        args.shift_spans(delta);
+        children.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for WidgetCallArg<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        let Self { name, value } = self;
+        name.shift_spans(delta);
+        value.shift_spans(delta);
+    }
+}
+
+impl ShiftSpans for WidgetCallArgValue<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Atom(value) => value.shift_spans(delta),
+            Self::Expr(value) => value.shift_spans(delta),
+            Self::Symbol(value) => value.shift_spans(delta),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::{
-        ast::ParseError,
+        ast::{
+            binary_operation::{BinaryOperation, BinaryOperator},
+            object::{Object, ObjectEntry},
+            ParseError,
+        },
         lexer::{Lexer, LexicalError},
     };
 
@@ -87,7 +213,7 @@ mod tests {
             Ok(WidgetCall::new(
                 (1, "container".into(), 10),
                 Vec::<WidgetCallArg>::new(),
-                Vec::<WidgetCallChild>::new()
+                Vec::<WidgetNode>::new()
             ))
         );
     }
@@ -110,7 +236,7 @@ mod tests {
                         Spanned::from((30, Atom::from("foo"), 35))
                     )
                 ],
-                Vec::<WidgetCallChild>::new(),
+                Vec::<WidgetNode>::new(),
             ))
         );
     }
@@ -145,7 +271,7 @@ mod tests {
                     WidgetCall::new(
                         (20, "button".into(), 26),
                         Vec::<WidgetCallArg>::new(),
-                        Vec::<WidgetCallChild>::new(),
+                        Vec::<WidgetNode>::new(),
                     ),
                     27
                 ))
@@ -189,6 +315,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn expr_arg() {
+        let (errs, ast) = test("(label :text {x-1})");
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok(WidgetCall::new(
+                (1, "label".into(), 6),
+                vec![WidgetCallArg::new(
+                    (8, "text".into(), 12),
+                    Spanned::from((13, subtraction(14, "x", 16, "1"), 18))
+                )],
+                Vec::<WidgetNode>::new(),
+            ))
+        );
+    }
+
+    #[test]
+    fn object_arg_and_children() {
+        let (errs, ast) = test(r#"(box :style {"a": 1} {"b": x} {})"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok(WidgetCall::new(
+                (1, "box".into(), 4),
+                vec![WidgetCallArg::new(
+                    (6, "style".into(), 11),
+                    Spanned::from((
+                        12,
+                        PrimitiveExpr::from(Object::new(vec![ObjectEntry::new(
+                            (13, "a".into(), 16),
+                            (18, PrimitiveExpr::Atom(Atom::new_number("1")), 19),
+                        )])),
+                        20
+                    ))
+                )],
+                vec![
+                    Spanned::from((
+                        21,
+                        PrimitiveExpr::from(Object::new(vec![ObjectEntry::new(
+                            (22, "b".into(), 25),
+                            (27, PrimitiveExpr::Symbol(Symbol::new("x")), 28),
+                        )])),
+                        29
+                    ))
+                    .into(),
+                    Spanned::from((30, PrimitiveExpr::from(Object::new(Vec::new())), 32)).into(),
+                ],
+            ))
+        );
+    }
+
+    #[test]
+    fn hyphens_in_strings_symbols_and_exprs() {
+        let (errs, ast) = test(r#"(box :halign "space-evenly" :class {a-b} {a-b})"#);
+
+        assert_eq!(errs, Vec::new());
+        assert_eq!(
+            ast,
+            Ok(WidgetCall::new(
+                (1, "box".into(), 4),
+                vec![
+                    WidgetCallArg::new(
+                        (6, "halign".into(), 12),
+                        Spanned::from((13, Atom::from("space-evenly"), 27))
+                    ),
+                    WidgetCallArg::new(
+                        (29, "class".into(), 34),
+                        Spanned::from((35, subtraction(36, "a", 38, "b"), 40))
+                    ),
+                ],
+                vec![Spanned::from((41, subtraction(42, "a", 44, "b"), 46)).into()],
+            ))
+        );
+    }
+
+    #[test]
+    fn ternary_arg() {
+        let (errs, ast) = test(r#"(box :class {a ? "b" : "c"})"#);
+
+        assert_eq!(errs, Vec::new());
+        let arg = &ast.unwrap().args[0];
+        assert!(
+            matches!(
+                arg.value,
+                WidgetCallArgValue::Expr(Spanned(12, PrimitiveExpr::TernaryOperation(_), 27))
+            ),
+            "{:?}",
+            arg.value
+        );
+    }
+
     // TODO: Missing closing parenthesis
     // TODO: Missing opening parenthesis?
     // TODO: Malformed arguments
@@ -196,7 +416,26 @@ mod tests {
     //  - missing name
     //  - missing value
 
-    fn test(inp: &str) -> (Vec<ParseError>, Result<WidgetCall, LexicalError>) {
+    /// `lhs - rhs`, with the symbol `lhs` and the symbol or number `rhs` starting at the offsets.
+    fn subtraction<'inp>(
+        l_lhs: usize,
+        lhs: &'inp str,
+        l_rhs: usize,
+        rhs: &'inp str,
+    ) -> PrimitiveExpr<'inp> {
+        let rhs = match rhs.parse::<f64>() {
+            Ok(_) => PrimitiveExpr::Atom(Atom::new_number(rhs)),
+            Err(_) => PrimitiveExpr::Symbol(Symbol::new(rhs)),
+        };
+        BinaryOperation::new(
+            Spanned(l_lhs, PrimitiveExpr::Symbol(Symbol::new(lhs)), l_lhs + 1),
+            BinaryOperator::Sub,
+            Spanned(l_rhs, rhs, l_rhs + 1),
+        )
+        .into()
+    }
+
+    fn test(inp: &str) -> (Vec<ParseError>, Result<WidgetCall<'_>, LexicalError<'_>>) {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let lexer = Lexer::new(inp);
@@ -0,0 +1,216 @@
+use crate::spanned::Spanned;
+
+use super::{
+    atom::Atom,
+    children::Children,
+    def_widget::DefWidgetChild,
+    def_window::DefWindowContent,
+    expr::PrimitiveExpr,
+    for_loop::ForLoop,
+    into_owned::IntoOwned,
+    shift_spans::ShiftSpans,
+    widget_call::{WidgetCall, WidgetCallChild},
+};
+
+/// A node of a widget tree. Shared by the bodies of `defwidget`, `defwindow`, widget calls and
+/// `for` loops.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum WidgetNode<'inp> {
+    Atom(Spanned<Atom<'inp>>),
+    Children(Spanned<Children>),
+    Err,
+    /// `{ ... }`
+    Expr(Spanned<PrimitiveExpr<'inp>>),
+    ForLoop(Spanned<ForLoop<'inp>>),
+    WidgetCall(Spanned<WidgetCall<'inp>>),
+}
+
+impl<'inp> From<Spanned<Atom<'inp>>> for WidgetNode<'inp> {
+    fn from(value: Spanned<Atom<'inp>>) -> Self {
+        Self::Atom(value)
+    }
+}
+
+impl<'inp> From<Spanned<Children>> for WidgetNode<'inp> {
+    fn from(value: Spanned<Children>) -> Self {
+        Self::Children(value)
+    }
+}
+
+impl<'inp> From<Spanned<PrimitiveExpr<'inp>>> for WidgetNode<'inp> {
+    fn from(value: Spanned<PrimitiveExpr<'inp>>) -> Self {
+        Self::Expr(value)
+    }
+}
+
+impl<'inp> From<Spanned<ForLoop<'inp>>> for WidgetNode<'inp> {
+    fn from(value: Spanned<ForLoop<'inp>>) -> Self {
+        Self::ForLoop(value)
+    }
+}
+
+impl<'inp> From<Spanned<WidgetCall<'inp>>> for WidgetNode<'inp> {
+    fn from(value: Spanned<WidgetCall<'inp>>) -> Self {
+        Self::WidgetCall(value)
+    }
+}
+
+impl<'inp> From<DefWidgetChild<'inp>> for WidgetNode<'inp> {
+    fn from(value: DefWidgetChild<'inp>) -> Self {
+        match value {
+            DefWidgetChild::Children(children) => children.into(),
+            DefWidgetChild::Err => Self::Err,
+            DefWidgetChild::WidgetCall(widget_call) => widget_call.into(),
+        }
+    }
+}
+
+impl<'inp> From<DefWindowContent<'inp>> for WidgetNode<'inp> {
+    fn from(value: DefWindowContent<'inp>) -> Self {
+        match value {
+            DefWindowContent::Atom(atom) => atom.into(),
+            DefWindowContent::WidgetCall(widget_call) => widget_call.into(),
+        }
+    }
+}
+
+impl<'inp> From<WidgetCallChild<'inp>> for WidgetNode<'inp> {
+    fn from(value: WidgetCallChild<'inp>) -> Self {
+        match value {
+            WidgetCallChild::Atom(atom) => atom.into(),
+            WidgetCallChild::Children(children) => children.into(),
+            WidgetCallChild::Err => Self::Err,
+            WidgetCallChild::ForLoop(for_loop) => for_loop.into(),
+            WidgetCallChild::WidgetCall(widget_call) => widget_call.into(),
+        }
+    }
+}
+
+impl IntoOwned for WidgetNode<'_> {
+    type Owned = WidgetNode<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Atom(value) => WidgetNode::Atom(value.into_owned()),
+            Self::Children(value) => WidgetNode::Children(value),
+            Self::Err => WidgetNode::Err,
+            Self::Expr(value) => WidgetNode::Expr(value.into_owned()),
+            Self::ForLoop(value) => WidgetNode::ForLoop(value.into_owned()),
+            Self::WidgetCall(value) => WidgetNode::WidgetCall(value.into_owned()),
+        }
+    }
+}
+
+impl ShiftSpans for WidgetNode<'_> {
+    fn shift_spans(&mut self, delta: isize) {
+        match self {
+            Self::Atom(value) => value.shift_spans(delta),
+            Self::Children(value) => value.shift_spans(delta),
+            Self::Expr(value) => value.shift_spans(delta),
+            Self::ForLoop(value) => value.shift_spans(delta),
+            Self::WidgetCall(value) => value.shift_spans(delta),
+            Self::Err => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        ast::{
+            binary_operation::{BinaryOperation, BinaryOperator},
+            def_widget::DefWidget,
+            symbol::Symbol,
+            top_level_expr::TopLevelExpr,
+            ParseError,
+        },
+        lexer::{Lexer, LexicalError},
+    };
+
+    #[test]
+    fn def_widget_accepts_every_node() {
+        let (errs, ast) = test(
+            r#"(defwidget foo [items]
+                "text"
+                {items + 1}
+                (for item in items (label :text item))
+                (children))"#,
+        );
+
+        assert_eq!(errs, Vec::new());
+
+        let Ok(Spanned(_, TopLevelExpr::DefWidget(DefWidget { children, .. }), _)) = ast else {
+            panic!("expected a defwidget, got: {:?}", ast);
+        };
+
+        assert_eq!(children.len(), 4);
+        assert_eq!(
+            children[0],
+            WidgetNode::Atom((39, Atom::from("text"), 45).into())
+        );
+        assert_eq!(
+            children[1],
+            WidgetNode::Expr(Spanned::from((
+                62,
+                BinaryOperation::new(
+                    (63, Symbol::new("items").into(), 68),
+                    BinaryOperator::Add,
+                    (71, Atom::new_number("1").into(), 72),
+                )
+                .into(),
+                73
+            )))
+        );
+        assert!(matches!(children[2], WidgetNode::ForLoop(_)));
+        assert!(matches!(children[3], WidgetNode::Children(_)));
+    }
+
+    #[test]
+    fn conversions() {
+        let widget_call = Spanned::from((
+            0,
+            WidgetCall::new((1, "box".into(), 4), Vec::new(), Vec::new()),
+            5,
+        ));
+
+        assert_eq!(
+            WidgetNode::from(DefWidgetChild::WidgetCall(widget_call.clone())),
+            WidgetNode::WidgetCall(widget_call.clone())
+        );
+        assert_eq!(
+            WidgetNode::from(DefWindowContent::WidgetCall(widget_call.clone())),
+            WidgetNode::WidgetCall(widget_call.clone())
+        );
+        assert_eq!(
+            WidgetNode::from(WidgetCallChild::WidgetCall(widget_call.clone())),
+            WidgetNode::WidgetCall(widget_call)
+        );
+        assert_eq!(WidgetNode::from(DefWidgetChild::Err), WidgetNode::Err);
+    }
+
+    fn test(
+        inp: &str,
+    ) -> (
+        Vec<ParseError>,
+        Result<Spanned<TopLevelExpr<'_>>, LexicalError<'_>>,
+    ) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let lexer = Lexer::new(inp);
+        let parser = crate::grammar::DefWidgetParser::new();
+
+        let mut errors = Vec::new();
+
+        let result = parser.parse(&mut errors, lexer).map_err(|e| match e {
+            lalrpop_util::ParseError::User { error } => error,
+            e => unimplemented!("this error should not happen: {:?}", e),
+        });
+
+        (errors, result)
+    }
+}
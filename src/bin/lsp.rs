@@ -0,0 +1,10 @@
+use lsp_server::Connection;
+
+fn main() -> rotten_potato::lsp::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    rotten_potato::lsp::run(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+
+    Ok(())
+}
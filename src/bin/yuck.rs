@@ -0,0 +1,260 @@
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use clap::{Args, Parser, Subcommand};
+use miette::{Context, IntoDiagnostic, LabeledSpan, MietteDiagnostic, NamedSource, Report};
+use rotten_potato::{
+    format::format,
+    lexer::Lexer,
+    line_index::{ColumnUnit, LineCol},
+    options::{EwwVersion, ParserOptions, Strictness},
+    workspace::{lint::lint, semantic_tokens::semantic_tokens, Severity, Workspace},
+};
+use serde_json::json;
+
+/// Checks, formats and inspects Eww configuration files.
+#[derive(Parser)]
+#[command(name = "yuck")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parses and lints the files and the files they include.
+    Check {
+        /// Prints one `file.yuck:line:column: message` line per diagnostic.
+        #[arg(long)]
+        short: bool,
+        /// The version of Eww the files are written for.
+        #[arg(long, value_name = "VERSION", default_value_t = EwwVersion::LATEST)]
+        eww_version: EwwVersion,
+        /// Reports syntax the version does not support as errors instead of warnings.
+        #[arg(long)]
+        strict: bool,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Formats the files and the files they include in place.
+    Fmt {
+        /// Only reports the files that are not formatted.
+        #[arg(long)]
+        check: bool,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Prints the files and the files they include in another representation.
+    Dump {
+        #[command(flatten)]
+        format: DumpFormat,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct DumpFormat {
+    /// The syntax tree.
+    #[arg(long)]
+    ast: bool,
+    /// The tokens of the lexer.
+    #[arg(long)]
+    tokens: bool,
+    /// The syntax tree, the diagnostics and the classified tokens, as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+fn main() -> miette::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Check {
+            short,
+            eww_version,
+            strict,
+            files,
+        } => {
+            let strictness = if strict {
+                Strictness::Strict
+            } else {
+                Strictness::Warn
+            };
+            let options = ParserOptions::new(eww_version, strictness);
+            check(&load(&files, options)?, short)
+        }
+        Command::Fmt { check, files } => fmt(&load(&files, ParserOptions::default())?, check),
+        Command::Dump { format, files } => {
+            dump(&load(&files, ParserOptions::default())?, &format);
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+/// Loads `files` and the files they include.
+fn load(files: &[PathBuf], options: ParserOptions) -> miette::Result<Workspace> {
+    let mut workspace = Workspace::with_options(options);
+
+    for path in files {
+        let source = fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        workspace.insert(path, source);
+    }
+    for path in files {
+        workspace.load_includes(path);
+    }
+
+    Ok(workspace)
+}
+
+fn check(workspace: &Workspace, short: bool) -> miette::Result<ExitCode> {
+    let mut error_count = 0;
+    let mut warning_count = 0;
+
+    for (path, file) in workspace.files() {
+        let lints = lint(workspace, path);
+
+        for diagnostic in file.diagnostics.iter().chain(&lints) {
+            match diagnostic.severity {
+                Severity::Error => error_count += 1,
+                Severity::Warning => warning_count += 1,
+            }
+
+            if short {
+                let LineCol { line, col } = file
+                    .line_index
+                    .line_col(diagnostic.span.start, ColumnUnit::Grapheme);
+                eprintln!(
+                    "{}:{}:{}: {}",
+                    path.display(),
+                    line + 1,
+                    col + 1,
+                    diagnostic.message
+                );
+                continue;
+            }
+
+            let report = Report::new(
+                MietteDiagnostic::new(diagnostic.message.clone())
+                    .with_severity(match diagnostic.severity {
+                        Severity::Error => miette::Severity::Error,
+                        Severity::Warning => miette::Severity::Warning,
+                    })
+                    .with_label(LabeledSpan::underline(diagnostic.span.clone())),
+            )
+            .with_source_code(NamedSource::new(
+                path.display().to_string(),
+                file.source.clone(),
+            ));
+            eprintln!("{:?}", report);
+        }
+    }
+
+    if warning_count > 0 {
+        eprintln!("found {} warning(s)", warning_count);
+    }
+    if error_count > 0 {
+        eprintln!("found {} error(s)", error_count);
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn fmt(workspace: &Workspace, check: bool) -> miette::Result<ExitCode> {
+    let mut is_success = true;
+
+    for (path, file) in workspace.files() {
+        let formatted = match format(&file.source) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                is_success = false;
+                continue;
+            }
+        };
+        if formatted == file.source {
+            continue;
+        }
+
+        if check {
+            println!("{} is not formatted", path.display());
+            is_success = false;
+        } else {
+            fs::write(path, formatted)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("could not write {}", path.display()))?;
+        }
+    }
+
+    Ok(if is_success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn dump(workspace: &Workspace, format: &DumpFormat) {
+    if format.json {
+        let files: Vec<_> = workspace
+            .files()
+            .map(|(path, file)| {
+                let diagnostics: Vec<_> = file
+                    .diagnostics
+                    .iter()
+                    .chain(&lint(workspace, path))
+                    .map(|diagnostic| {
+                        json!({
+                            "start": diagnostic.span.start,
+                            "end": diagnostic.span.end,
+                            "severity": match diagnostic.severity {
+                                Severity::Error => "error",
+                                Severity::Warning => "warning",
+                            },
+                            "message": diagnostic.message,
+                        })
+                    })
+                    .collect();
+                let tokens: Vec<_> = semantic_tokens(workspace, path)
+                    .into_iter()
+                    .map(|token| {
+                        json!({
+                            "start": token.span.start,
+                            "end": token.span.end,
+                            "kind": token.kind.name(),
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "path": path,
+                    "ast": file.ast,
+                    "diagnostics": diagnostics,
+                    "tokens": tokens,
+                })
+            })
+            .collect();
+
+        println!("{:#}", json!(files));
+        return;
+    }
+
+    for (path, file) in workspace.files() {
+        println!("; {}", path.display());
+
+        if format.ast {
+            println!("{:#?}", file.ast);
+            continue;
+        }
+        for token in Lexer::new(&file.source) {
+            match token {
+                Ok((l, tok, r)) => println!("{}..{} {:?}", l, r, tok),
+                Err(err) => {
+                    println!("{}", err);
+                    break;
+                }
+            }
+        }
+    }
+}
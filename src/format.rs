@@ -0,0 +1,163 @@
+//! Formatting of `.yuck` files. Only whitespace is changed: forms are indented by their nesting,
+//! runs of whitespace within a line are collapsed and line breaks and comments are kept.
+//! Whether two tokens are separated at all is kept as well, e.g. `:text` or `round(`.
+
+use thiserror::Error;
+
+use crate::{
+    lexer::{Lexer, Tok},
+    workspace::{Diagnostic, SourceFile},
+};
+
+const INDENT: &str = "  ";
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum FormatError {
+    #[error("files with syntax errors cannot be formatted")]
+    SyntaxError,
+}
+
+/// Returns the formatted `source`. Sources that do not parse are not formatted, as their
+/// structure is unknown.
+pub fn format(source: &str) -> Result<String, FormatError> {
+    if SourceFile::parse(source)
+        .diagnostics
+        .iter()
+        .any(Diagnostic::is_error)
+    {
+        return Err(FormatError::SyntaxError);
+    }
+    let tokens = Lexer::new(source)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| FormatError::SyntaxError)?;
+
+    let mut formatted = String::new();
+    let mut depth: usize = 0;
+    let mut previous: Option<(&Tok, usize)> = None;
+
+    for (l, tok, r) in &tokens {
+        if is_closing(tok) {
+            depth = depth.saturating_sub(1);
+        }
+
+        match previous {
+            None => push_indent(&mut formatted, depth),
+            Some((_, previous_end)) if source[previous_end..*l].contains('\n') => {
+                formatted.push('\n');
+                // Keeps a single blank line of every run of blank lines.
+                if source[previous_end..*l].matches('\n').count() > 1 {
+                    formatted.push('\n');
+                }
+                push_indent(&mut formatted, depth);
+            }
+            Some((previous, previous_end)) => {
+                let is_separated = previous_end < *l || matches!(previous, Tok::DelimiterComma);
+                let is_attached = matches!(
+                    previous,
+                    Tok::DelimiterLeftParen | Tok::DelimiterLeftBracket
+                ) || matches!(
+                    tok,
+                    Tok::DelimiterRightParen | Tok::DelimiterRightBracket | Tok::DelimiterComma
+                );
+                if is_separated && !is_attached {
+                    formatted.push(' ');
+                }
+            }
+        }
+
+        formatted.push_str(source[*l..*r].trim_end());
+
+        if is_opening(tok) {
+            depth += 1;
+        }
+        previous = Some((tok, *r));
+    }
+
+    if !formatted.is_empty() {
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+fn push_indent(formatted: &mut String, depth: usize) {
+    for _ in 0..depth {
+        formatted.push_str(INDENT);
+    }
+}
+
+fn is_opening(tok: &Tok) -> bool {
+    matches!(
+        tok,
+        Tok::DelimiterLeftParen | Tok::DelimiterLeftBracket | Tok::DelimiterLeftBrace
+    )
+}
+
+fn is_closing(tok: &Tok) -> bool {
+    matches!(
+        tok,
+        Tok::DelimiterRightParen | Tok::DelimiterRightBracket | Tok::DelimiterRightBrace
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn indentation() {
+        let source = r#"
+(defwidget   bar [ ?icon ]
+        (box :orientation "h"
+    :space-evenly false
+; the icon
+              { icon == ""  ?  "none" : icon }
+  )
+)
+
+
+(defvar a "multi
+  line")   ; trailing
+"#;
+
+        assert_eq!(
+            test(source),
+            r#"(defwidget bar [?icon]
+  (box :orientation "h"
+    :space-evenly false
+    ; the icon
+    { icon == "" ? "none" : icon }
+  )
+)
+
+(defvar a "multi
+  line") ; trailing
+"#
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(
+            test("(defwidget a [] {round( b ,0)+1})"),
+            "(defwidget a [] {round(b, 0)+1})\n"
+        );
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert_eq!(format("(defvar a"), Err(FormatError::SyntaxError));
+        assert_eq!(format("(defvar a \"b)"), Err(FormatError::SyntaxError));
+        assert_eq!(format(""), Ok(String::new()));
+    }
+
+    /// Formats `source` and checks that formatting is idempotent.
+    fn test(source: &str) -> String {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let formatted = format(source).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        formatted
+    }
+}
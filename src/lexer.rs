@@ -1,3 +1,5 @@
+use std::iter::FusedIterator;
+
 use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 use unicode_segmentation::UnicodeSegmentation;
@@ -23,9 +25,17 @@ pub enum LexicalError<'inp> {
     UnterminatedLiteralString {
         #[source_code]
         src: &'inp str,
-        #[label("The string literal is missing its closing quote ('\"')")]
+        #[label("The string literal is missing its closing quote")]
         span: SourceSpan,
     },
+    #[error("Invalid symbol")]
+    InvalidSymbol {
+        #[source_code]
+        src: &'inp str,
+        #[label("'{char}' cannot be part of a symbol")]
+        span: SourceSpan,
+        char: &'inp str,
+    },
 }
 
 impl LexicalError<'_> {
@@ -33,6 +43,7 @@ impl LexicalError<'_> {
         match self {
             LexicalError::UnrecognizedToken { src, .. } => src,
             LexicalError::UnterminatedLiteralString { src, .. } => src,
+            LexicalError::InvalidSymbol { src, .. } => src,
         }
     }
 
@@ -40,118 +51,141 @@ impl LexicalError<'_> {
         match self {
             LexicalError::UnrecognizedToken { span, .. } => span,
             LexicalError::UnterminatedLiteralString { span, .. } => span,
+            LexicalError::InvalidSymbol { span, .. } => span,
         }
     }
 }
 
-// TODO: What's the point of this? Can this just be removed?
-impl<'inp> From<lalrpop_util::ParseError<usize, Tok<'inp>, LexicalError<'inp>>>
-    for LexicalError<'inp>
-{
-    fn from(value: lalrpop_util::ParseError<usize, Tok<'inp>, LexicalError<'inp>>) -> Self {
-        match value {
-            lalrpop_util::ParseError::User { error } => error,
-            // TODO: See if any of the lalrpop error need to be handled / should be converted to
-            // lexical errors.
-            unhandled => todo!("handle lalrpop error: {:?}", unhandled),
-        }
-    }
+/// Where the lexer is, which decides what ends a symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// S-expressions, where symbols like `space-evenly` or `:prop-name` contain operators.
+    SExpr,
+    /// Expressions within `{}` or `${}`, where operators end symbols, e.g. `x-1` is `x - 1`.
+    Expr,
 }
 
-pub type Grapheme<'inp> = (usize, &'inp str);
-
+/// Splits the input into tokens in a single pass. Tokens and spans always start and end on
+/// grapheme boundaries, e.g. a delimiter followed by a combining accent is part of a symbol. The
+/// lexer ends after the first error.
 pub struct Lexer<'inp> {
     raw: &'inp str,
-    graphemes: Vec<(usize, &'inp str)>,
+    /// The byte offset of the next token.
     curr_idx: usize,
+    /// The mode outside of any braces.
+    mode: Mode,
+    /// The number of open `{`, everything within them is an expression.
+    brace_depth: usize,
+    /// Whether the previous token was a `(`.
+    after_left_paren: bool,
 }
 
 impl<'inp> Iterator for Lexer<'inp> {
     type Item = LexerResult<'inp>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let consumed_whitespaces =
-            matcher::peek_whitespace(&self.graphemes, self.curr_idx, self.raw);
+        let consumed_whitespaces = matcher::peek_whitespace(self.raw, self.curr_idx);
         if consumed_whitespaces > 0 {
-            log::debug!("skipped {} whitespace graphemes", consumed_whitespaces);
+            log::debug!("skipped {} bytes of whitespace", consumed_whitespaces);
             self.curr_idx += consumed_whitespaces;
         }
 
-        if self.curr_idx >= self.graphemes.len() {
-            log::debug!("reached end of grapheme vector, returning None");
-            return None;
-        }
-
-        type MatcherFn =
-            for<'inp> fn(&[Grapheme<'inp>], usize, &'inp str) -> Option<matcher::PeekResult<'inp>>;
-
-        const MATCHERS: &[(&str, MatcherFn)] = &[
-            ("comment", matcher::peek_comment),
-            ("keyword", matcher::peek_keyword),
-            ("delimiter", matcher::peek_delimiter),
-            ("punctuation", matcher::peek_punctuation),
-            ("literal string", matcher::peek_literal_string),
-            ("literal number", matcher::peek_literal_number),
-            ("operator math", matcher::peek_operator_math),
-            ("operator comparison", matcher::peek_operator_comparison),
-            ("operator logical", matcher::peek_operator_logical),
-            ("symbol", matcher::peek_symbol),
-        ];
+        let first = *self.raw.as_bytes().get(self.curr_idx)?;
 
         log::debug!(
-            "beginning to consume next token, grapheme index: {}, first grapheme: {:?}",
+            "beginning to consume next token, byte index: {}, first byte: {:?}",
             self.curr_idx,
-            self.graphemes[self.curr_idx]
+            first as char
         );
 
-        for (name, fn_ptr) in MATCHERS {
-            log::debug!("trying to consume input as {}", name);
-            if let Some(res) = fn_ptr(&self.graphemes, self.curr_idx, self.raw) {
-                match res {
-                    Ok(peeked_tok) => {
-                        let count_consumed_graphemes = peeked_tok.count_consumed_graphemes;
-                        self.curr_idx += count_consumed_graphemes;
-
-                        let lexer_result: LexerResult = peeked_tok.into();
-                        log::debug!(
-                            "consumed input as {:?} consisting of {} graphemes",
-                            lexer_result,
-                            count_consumed_graphemes
-                        );
-                        return Some(lexer_result);
-                    }
-                    Err(e) => return Some(Err(e)),
-                }
+        let mode = self.mode();
+
+        // The first byte determines which matcher applies, everything that none of them accepts
+        // is a symbol.
+        let res = match first {
+            b';' => matcher::peek_comment(self.raw, self.curr_idx),
+            b'(' | b')' | b'[' | b']' | b'{' | b'}' | b',' => {
+                matcher::peek_delimiter(self.raw, self.curr_idx)
             }
+            b'?' | b':' => matcher::peek_punctuation(self.raw, self.curr_idx),
+            b'"' | b'\'' => matcher::peek_literal_string(self.raw, self.curr_idx),
+            b'0'..=b'9' => matcher::peek_literal_number(self.raw, self.curr_idx),
+            b'%' | b'*' | b'+' | b'-' | b'/' => {
+                matcher::peek_operator_math(self.raw, self.curr_idx)
+            }
+            b'=' | b'<' | b'>' => matcher::peek_operator_comparison(self.raw, self.curr_idx),
+            b'!' => matcher::peek_operator_comparison(self.raw, self.curr_idx)
+                .or_else(|| matcher::peek_operator_logical(self.raw, self.curr_idx)),
+            b'&' | b'|' => matcher::peek_operator_logical(self.raw, self.curr_idx),
+            _ => matcher::peek_keyword(self.raw, self.curr_idx, mode),
         }
+        .or_else(|| matcher::peek_symbol(self.raw, self.curr_idx, mode));
 
-        if let Some(char) = self.graphemes.get(self.curr_idx) {
-            return Some(Err(LexicalError::UnrecognizedToken {
-                src: self.raw,
-                span: (char.0, 1).into(),
-                char: char.1,
-            }));
-        };
+        match res {
+            Some(Ok(peeked_tok)) => {
+                self.curr_idx = peeked_tok.span_to;
+                match peeked_tok.tok {
+                    Tok::DelimiterLeftBrace => self.brace_depth += 1,
+                    Tok::DelimiterRightBrace => {
+                        self.brace_depth = self.brace_depth.saturating_sub(1)
+                    }
+                    // A top-level form closes the braces a previous form left open.
+                    ref tok if self.after_left_paren && tok.is_top_level_keyword() => {
+                        self.brace_depth = 0
+                    }
+                    _ => {}
+                }
+                self.after_left_paren = peeked_tok.tok == Tok::DelimiterLeftParen;
 
-        // TODO: Convert to lexer error
-        panic!("unexpected end of input");
+                let lexer_result: LexerResult = peeked_tok.into();
+                log::debug!("consumed input as {:?}", lexer_result);
+                Some(lexer_result)
+            }
+            Some(Err(e)) => {
+                self.curr_idx = self.raw.len();
+                Some(Err(e))
+            }
+            None => {
+                let char = grapheme_at(self.raw, self.curr_idx)?;
+                let err = LexicalError::UnrecognizedToken {
+                    src: self.raw,
+                    span: (self.curr_idx, char.len()).into(),
+                    char,
+                };
+                self.curr_idx = self.raw.len();
+                Some(Err(err))
+            }
+        }
     }
 }
 
+impl FusedIterator for Lexer<'_> {}
+
 impl<'inp> Lexer<'inp> {
     pub fn new(raw: &'inp str) -> Self {
-        let input_grapheme_indices = raw.grapheme_indices(true).collect();
+        Self::with_mode(raw, Mode::SExpr)
+    }
 
-        log::debug!("lexer constructed with input: '{}'", raw);
-        log::trace!(
-            "constructed new lexer with the following graphemes: {:?}",
-            input_grapheme_indices
-        );
+    /// Creates a lexer starting in `mode`, e.g. [`Mode::Expr`] for the content of a `${}`
+    /// interpolation.
+    pub fn with_mode(raw: &'inp str, mode: Mode) -> Self {
+        log::debug!("lexer constructed in {:?} mode with input: '{}'", mode, raw);
 
         Lexer {
             raw,
             curr_idx: 0,
-            graphemes: input_grapheme_indices,
+            mode,
+            brace_depth: 0,
+            after_left_paren: false,
+        }
+    }
+
+    /// Returns the mode of the next token.
+    pub fn mode(&self) -> Mode {
+        if self.brace_depth > 0 {
+            Mode::Expr
+        } else {
+            self.mode
         }
     }
 }
@@ -164,12 +198,34 @@ impl<'inp> From<matcher::PeekedToken<'inp>> for LexerResult<'inp> {
     }
 }
 
-pub fn grapheme_is_whitespace(grapheme: &Grapheme<'_>) -> bool {
-    grapheme.1.chars().all(|c| c.is_whitespace())
+/// Returns the grapheme starting at the byte offset `idx`, which must be a grapheme boundary.
+pub fn grapheme_at(raw: &str, idx: usize) -> Option<&str> {
+    let bytes = raw.as_bytes();
+    let first = *bytes.get(idx)?;
+
+    // An ASCII character is a grapheme on its own, unless it is followed by a non-ASCII
+    // character like a combining accent or it is the `\r` of `\r\n`.
+    let is_single = match bytes.get(idx + 1) {
+        None => first.is_ascii(),
+        Some(next) => first.is_ascii() && next.is_ascii() && !(first == b'\r' && *next == b'\n'),
+    };
+    if is_single {
+        return Some(&raw[idx..idx + 1]);
+    }
+
+    raw[idx..].graphemes(true).next()
+}
+
+pub fn grapheme_is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| c.is_whitespace())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -186,4 +242,350 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn non_ascii_spans_are_byte_offsets() {
+        assert_eq!(
+            test("(label :text \"󰕾 ${vol}\") ; ✓ done"),
+            vec![
+                (0, Tok::DelimiterLeftParen, 1),
+                (1, Tok::LiteralSymbol("label"), 6),
+                (7, Tok::PunctuationColon, 8),
+                (8, Tok::LiteralSymbol("text"), 12),
+                (13, Tok::LiteralString("󰕾 ${vol}"), 26),
+                (26, Tok::DelimiterRightParen, 27),
+                (28, Tok::Comment(" ✓ done"), 38),
+            ]
+        );
+    }
+
+    #[test]
+    fn combining_characters_stay_in_their_grapheme() {
+        assert_eq!(
+            test("e\u{301} \"e\"\u{301}\""),
+            vec![
+                (0, Tok::LiteralSymbol("e\u{301}"), 3),
+                (4, Tok::LiteralString("e\"\u{301}"), 10),
+            ]
+        );
+
+        // A delimiter, digit or operator with an accent is neither of them nor part of a symbol.
+        for (inp, offset, char) in [
+            ("a(\u{301}", 1, "(\u{301}"),
+            ("1\u{301}", 0, "1\u{301}"),
+            ("-\u{301}", 0, "-\u{301}"),
+        ] {
+            assert_eq!(
+                Lexer::new(inp).find_map(Result::err),
+                Some(LexicalError::InvalidSymbol {
+                    src: inp,
+                    span: (offset, char.len()).into(),
+                    char,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn hyphens_in_symbols_and_expressions() {
+        assert_eq!(
+            test("(widget-name :prop-name {x-1})"),
+            vec![
+                (0, Tok::DelimiterLeftParen, 1),
+                (1, Tok::LiteralSymbol("widget-name"), 12),
+                (13, Tok::PunctuationColon, 14),
+                (14, Tok::LiteralSymbol("prop-name"), 23),
+                (24, Tok::DelimiterLeftBrace, 25),
+                (25, Tok::LiteralSymbol("x"), 26),
+                (26, Tok::OperatorMathSubtraction, 27),
+                (27, Tok::LiteralNumber("1"), 28),
+                (28, Tok::DelimiterRightBrace, 29),
+                (29, Tok::DelimiterRightParen, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn modes_follow_braces() {
+        assert_eq!(
+            test("{a-b {c-d} e-f} g-h"),
+            vec![
+                (0, Tok::DelimiterLeftBrace, 1),
+                (1, Tok::LiteralSymbol("a"), 2),
+                (2, Tok::OperatorMathSubtraction, 3),
+                (3, Tok::LiteralSymbol("b"), 4),
+                (5, Tok::DelimiterLeftBrace, 6),
+                (6, Tok::LiteralSymbol("c"), 7),
+                (7, Tok::OperatorMathSubtraction, 8),
+                (8, Tok::LiteralSymbol("d"), 9),
+                (9, Tok::DelimiterRightBrace, 10),
+                (11, Tok::LiteralSymbol("e"), 12),
+                (12, Tok::OperatorMathSubtraction, 13),
+                (13, Tok::LiteralSymbol("f"), 14),
+                (14, Tok::DelimiterRightBrace, 15),
+                (16, Tok::LiteralSymbol("g-h"), 19),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_symbol_glued_to_a_property() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let inp = "(box :x+1 :y {y+1})";
+        let mut lexer = Lexer::new(inp);
+        assert_eq!(lexer.nth(2), Some(Ok((5, Tok::PunctuationColon, 6))));
+        assert_eq!(
+            lexer.next(),
+            Some(Err(LexicalError::InvalidSymbol {
+                src: inp,
+                span: (7, 1).into(),
+                char: "+",
+            }))
+        );
+        check_spans(inp);
+    }
+
+    #[test]
+    fn ends_after_an_error() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for (inp, offset, char) in [("{x-1} ${a}", 6, "$"), ("(label :text icon🙂 x)", 17, "🙂")]
+        {
+            let results: Vec<_> = Lexer::new(inp).collect();
+            assert_eq!(
+                results.last(),
+                Some(&Err(LexicalError::InvalidSymbol {
+                    src: inp,
+                    span: (offset, char.len()).into(),
+                    char,
+                }))
+            );
+            assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+        }
+
+        let mut lexer = Lexer::new("\"a");
+        assert!(matches!(lexer.next(), Some(Err(_))));
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn eww_literals() {
+        assert_eq!(
+            test("(defvar x 1.5) {x * 0.5} (label :text 'hi')"),
+            vec![
+                (0, Tok::DelimiterLeftParen, 1),
+                (1, Tok::KeywordDefVar, 7),
+                (8, Tok::LiteralSymbol("x"), 9),
+                (10, Tok::LiteralNumber("1.5"), 13),
+                (13, Tok::DelimiterRightParen, 14),
+                (15, Tok::DelimiterLeftBrace, 16),
+                (16, Tok::LiteralSymbol("x"), 17),
+                (18, Tok::OperatorMathMultiplication, 19),
+                (20, Tok::LiteralNumber("0.5"), 23),
+                (23, Tok::DelimiterRightBrace, 24),
+                (25, Tok::DelimiterLeftParen, 26),
+                (26, Tok::LiteralSymbol("label"), 31),
+                (32, Tok::PunctuationColon, 33),
+                (33, Tok::LiteralSymbol("text"), 37),
+                (38, Tok::LiteralString("hi"), 42),
+                (42, Tok::DelimiterRightParen, 43),
+            ]
+        );
+    }
+
+    #[test]
+    fn expr_mode() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let tokens: Vec<_> = Lexer::with_mode("true&&run-while", Mode::Expr)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                (0, Tok::KeywordTrue, 4),
+                (4, Tok::OperatorLogicalAnd, 6),
+                (6, Tok::LiteralSymbol("run"), 9),
+                (9, Tok::OperatorMathSubtraction, 10),
+                (10, Tok::LiteralSymbol("while"), 15),
+            ]
+        );
+        // A top-level form ends the expression a previous form left open.
+        assert_eq!(
+            test("{a (defvar b-c 1)")[3..6],
+            [
+                (4, Tok::KeywordDefVar, 10),
+                (11, Tok::LiteralSymbol("b-c"), 14),
+                (15, Tok::LiteralNumber("1"), 16),
+            ]
+        );
+
+        // A stray `}` does not leave the mode the lexer started in.
+        let mut lexer = Lexer::with_mode("} a", Mode::Expr);
+        lexer.next();
+        assert_eq!(lexer.mode(), Mode::Expr);
+    }
+
+    #[test]
+    fn comments_end_in_front_of_crlf() {
+        assert_eq!(
+            test("; a\r\n(b)"),
+            vec![
+                (0, Tok::Comment(" a"), 3),
+                (5, Tok::DelimiterLeftParen, 6),
+                (6, Tok::LiteralSymbol("b"), 7),
+                (7, Tok::DelimiterRightParen, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_after_icons() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let inp = "(label :text \"󰕾 ✓ ${vol}";
+        let err = Lexer::new(inp).find_map(Result::err).unwrap();
+
+        assert_eq!(
+            err,
+            LexicalError::UnterminatedLiteralString {
+                src: inp,
+                span: (13, 16).into(),
+            }
+        );
+        check_spans(inp);
+    }
+
+    /// Pieces of configs that are joined to inputs which come close to real configs, but also
+    /// contain broken strings and combining characters in unexpected places.
+    fn piece() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("(".to_owned()),
+            Just(")".to_owned()),
+            Just("{".to_owned()),
+            Just(",".to_owned()),
+            Just(":".to_owned()),
+            Just("\"".to_owned()),
+            Just("\"󰕾 ${vol}\"".to_owned()),
+            Just("; ✓ comment\n".to_owned()),
+            Just("defvar".to_owned()),
+            Just("42".to_owned()),
+            Just("1.5".to_owned()),
+            Just("'".to_owned()),
+            Just("==".to_owned()),
+            Just("!".to_owned()),
+            Just("|".to_owned()),
+            Just("-".to_owned()),
+            Just("\u{301}".to_owned()),
+            Just("🇩🇪".to_owned()),
+            Just("👩‍👩‍👧".to_owned()),
+            Just("\r\n".to_owned()),
+            Just(" ".to_owned()),
+            Just("\u{3000}".to_owned()),
+            any::<char>().prop_map(String::from),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn spans_cover_the_tokens_of_config_like_input(
+            pieces in proptest::collection::vec(piece(), 0..40)
+        ) {
+            check_spans(&pieces.concat());
+        }
+
+        #[test]
+        fn spans_cover_the_tokens_of_any_input(inp in any::<String>()) {
+            check_spans(&inp);
+        }
+    }
+
+    /// Checks that every token and error of `inp` starts and ends on grapheme boundaries, that
+    /// only whitespace is skipped and that tokens cover exactly the text they were made of.
+    fn check_spans(inp: &str) {
+        let boundaries: HashSet<_> = inp
+            .grapheme_indices(true)
+            .map(|(idx, _)| idx)
+            .chain([inp.len()])
+            .collect();
+        let mut prev_end = 0;
+
+        for result in Lexer::new(inp) {
+            let (l, r) = match &result {
+                Ok((l, _, r)) => (*l, *r),
+                Err(err) => (err.span().offset(), err.span().offset() + err.span().len()),
+            };
+
+            assert!(prev_end <= l && l < r, "{:?} after {}", result, prev_end);
+            assert!(
+                boundaries.contains(&l),
+                "{:?} starts within a grapheme",
+                result
+            );
+            assert!(
+                boundaries.contains(&r),
+                "{:?} ends within a grapheme",
+                result
+            );
+            // An invalid symbol is reported at the offending grapheme, behind the valid start of
+            // the symbol.
+            let skipped_to = match &result {
+                Err(LexicalError::InvalidSymbol { .. }) => {
+                    let symbol_start = inp[prev_end..l]
+                        .grapheme_indices(true)
+                        .find(|(_, grapheme)| !grapheme_is_whitespace(grapheme))
+                        .map_or(l, |(idx, _)| prev_end + idx);
+                    assert!(
+                        !inp[symbol_start..l]
+                            .graphemes(true)
+                            .any(grapheme_is_whitespace),
+                        "{:?} after {:?}",
+                        result,
+                        &inp[symbol_start..l]
+                    );
+                    symbol_start
+                }
+                _ => l,
+            };
+            assert!(
+                grapheme_is_whitespace(&inp[prev_end..skipped_to]),
+                "{:?} skipped {:?}",
+                result,
+                &inp[prev_end..skipped_to]
+            );
+
+            let text = &inp[l..r];
+            match result {
+                Ok((_, Tok::Comment(comment), _)) => assert_eq!(text, format!(";{}", comment)),
+                Ok((_, Tok::LiteralString(string), _)) => {
+                    let quote = &text[..1];
+                    assert_eq!(text, format!("{}{}{}", quote, string, quote))
+                }
+                Ok((_, Tok::LiteralNumber(slice) | Tok::LiteralSymbol(slice), _)) => {
+                    assert_eq!(text, slice)
+                }
+                Ok(_) => assert!(text.is_ascii(), "{:?}", text),
+                Err(LexicalError::UnterminatedLiteralString { .. }) => {
+                    assert!(text.starts_with(['"', '\'']), "{:?}", text);
+                    assert_eq!(r, inp.len());
+                    break;
+                }
+                Err(
+                    LexicalError::UnrecognizedToken { char, .. }
+                    | LexicalError::InvalidSymbol { char, .. },
+                ) => {
+                    assert_eq!(text, char);
+                    break;
+                }
+            }
+            prev_end = r;
+        }
+    }
+
+    fn test(inp: &str) -> Vec<(usize, Tok<'_>, usize)> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        Lexer::new(inp).collect::<Result<_, _>>().unwrap()
+    }
 }
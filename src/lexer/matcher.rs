@@ -1,4 +1,4 @@
-use super::{tok::Tok, LexicalError};
+use super::{grapheme_at, tok::Tok, LexicalError};
 
 mod peek_comment;
 mod peek_delimiter;
@@ -29,18 +29,23 @@ pub struct PeekedToken<'inp> {
     pub span_from: usize,
     pub span_to: usize,
     pub tok: Tok<'inp>,
-    pub count_consumed_graphemes: usize,
 }
 
 impl<'inp> PeekedToken<'inp> {
-    pub fn new(tok: Tok<'inp>, span_from: usize, span_to: usize, consumed: usize) -> Self {
+    pub fn new(tok: Tok<'inp>, span_from: usize, span_to: usize) -> Self {
         Self {
             span_from,
             span_to,
             tok,
-            count_consumed_graphemes: consumed,
         }
     }
 }
 
 pub type PeekResult<'inp> = Result<PeekedToken<'inp>, LexicalError<'inp>>;
+
+/// Returns whether `raw` continues with the ASCII `text` at the byte offset `idx`, with the last
+/// character of `text` not being part of a larger grapheme.
+fn starts_with_graphemes(raw: &str, idx: usize, text: &str) -> bool {
+    raw.get(idx..).is_some_and(|rest| rest.starts_with(text))
+        && grapheme_at(raw, idx + text.len() - 1).map(str::len) == Some(1)
+}
@@ -1,46 +1,46 @@
 use super::PeekResult;
-use crate::lexer::{matcher::PeekedToken, tok::Tok, Grapheme};
+use crate::lexer::{grapheme_at, matcher::PeekedToken, tok::Tok};
 
-pub fn peek_delimiter<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    _: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let grapheme = graphemes.get(idx)?;
+pub fn peek_delimiter(raw: &str, idx: usize) -> Option<PeekResult<'_>> {
+    let grapheme = grapheme_at(raw, idx)?;
 
     log::trace!(
         "trying to interpret grapheme: {:?} as a delimiter",
         grapheme
     );
 
-    let tok = match grapheme.1 {
-        "(" => Tok::DelimiterLeftParen,
-        ")" => Tok::DelimiterRightParen,
-        "[" => Tok::DelimiterLeftBracket,
-        "]" => Tok::DelimiterRightBracket,
-        "," => Tok::DelimiterComma,
-        _ => {
-            log::trace!(
-                "could not match '{}' as a delimiter... returning none",
-                grapheme.1
-            );
-            return None;
-        }
+    let Some(tok) = delimiter(grapheme) else {
+        log::trace!(
+            "could not match '{}' as a delimiter... returning none",
+            grapheme
+        );
+        return None;
     };
 
-    let span_from = grapheme.0;
-    let span_to = grapheme.0 + grapheme.1.len();
-    let peeked_token = PeekedToken::new(tok, span_from, span_to, 1);
+    let peeked_token = PeekedToken::new(tok, idx, idx + grapheme.len());
 
     log::trace!("successfully matched: {:?} as a delimiter", peeked_token);
 
     Some(Ok(peeked_token))
 }
 
+/// Returns the delimiter token of `grapheme`, if it is one.
+pub(crate) fn delimiter<'inp>(grapheme: &str) -> Option<Tok<'inp>> {
+    Some(match grapheme {
+        "(" => Tok::DelimiterLeftParen,
+        ")" => Tok::DelimiterRightParen,
+        "[" => Tok::DelimiterLeftBracket,
+        "]" => Tok::DelimiterRightBracket,
+        "{" => Tok::DelimiterLeftBrace,
+        "}" => Tok::DelimiterRightBrace,
+        "," => Tok::DelimiterComma,
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -48,18 +48,16 @@ mod tests {
     fn all_delimiters_are_correctly_identified() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        for (input, expected) in vec![
+        for (input, expected) in [
             ("(", Tok::DelimiterLeftParen),
             (")", Tok::DelimiterRightParen),
             ("[", Tok::DelimiterLeftBracket),
             ("]", Tok::DelimiterRightBracket),
             (",", Tok::DelimiterComma),
         ] {
-            let graphemes: Vec<_> = input.grapheme_indices(true).collect();
-
             assert_eq!(
-                peek_delimiter(&graphemes, 0, input),
-                Some(Ok(PeekedToken::new(expected.clone(), 0, 1, 1))),
+                peek_delimiter(input, 0),
+                Some(Ok(PeekedToken::new(expected.clone(), 0, 1))),
                 "input '{}' failed to be parsed to {:?}",
                 input,
                 expected
@@ -72,11 +70,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "   (    ";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_delimiter(&graphemes, 3, raw),
-            Some(Ok(PeekedToken::new(Tok::DelimiterLeftParen, 3, 4, 1))),
+            peek_delimiter(raw, 3),
+            Some(Ok(PeekedToken::new(Tok::DelimiterLeftParen, 3, 4))),
         );
     }
 
@@ -85,11 +82,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "    (";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_delimiter(&graphemes, 4, raw),
-            Some(Ok(PeekedToken::new(Tok::DelimiterLeftParen, 4, 5, 1))),
+            peek_delimiter(raw, 4),
+            Some(Ok(PeekedToken::new(Tok::DelimiterLeftParen, 4, 5))),
         );
     }
 }
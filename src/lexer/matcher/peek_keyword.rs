@@ -1,53 +1,33 @@
-use super::PeekResult;
+use super::{starts_with_graphemes, PeekResult};
 use crate::lexer::{
-    grapheme_is_whitespace,
-    matcher::{peek_delimiter, PeekedToken},
+    matcher::{symbol_end, PeekedToken},
     tok::Tok,
-    Grapheme,
+    Mode,
 };
 
-pub fn peek_keyword<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let offset_graphemes_end = graphemes.get(idx..)?.iter().enumerate().position(|(o, g)| {
-        grapheme_is_whitespace(g) || peek_delimiter(graphemes, idx + o, raw).is_some()
-    });
-
-    let idx_raw_from = graphemes.get(idx)?.0;
-    let (idx_raw_to, offset_graphemes_end) = offset_graphemes_end
-        .map(|o| (graphemes[idx + o].0, o))
-        .unwrap_or((raw.len(), graphemes.len() - idx));
-
-    let slice = &raw[idx_raw_from..idx_raw_to];
-
-    log::trace!(
-        "trying to interpret slice: '{}', grapheme {} to +{} as keyword",
-        slice,
-        idx,
-        offset_graphemes_end
-    );
-
-    let tok = match slice {
-        "include" => Tok::KeywordInclude,
-        "true" => Tok::KeywordTrue,
-        "false" => Tok::KeywordFalse,
-        "deflisten" => Tok::KeywordDefListen,
-        "defpoll" => Tok::KeywordDefPoll,
-        "defvar" => Tok::KeywordDefVar,
-        "defwidget" => Tok::KeywordDefWidget,
-        "defwindow" => Tok::KeywordDefWindow,
-        "literal" => Tok::KeywordLiteral,
-        unknown => {
-            log::trace!("failed to match '{}' as a keyword", unknown);
-            return None;
-        }
-    };
-
-    let peeked_token = PeekedToken::new(tok, idx_raw_from, idx_raw_to, offset_graphemes_end);
-
-    log::trace!("successfully matched: {:?} as a symbol", peeked_token);
+const KEYWORDS: &[(&str, Tok<'static>)] = &[
+    ("include", Tok::KeywordInclude),
+    ("true", Tok::KeywordTrue),
+    ("false", Tok::KeywordFalse),
+    ("for", Tok::KeywordFor),
+    ("deflisten", Tok::KeywordDefListen),
+    ("defpoll", Tok::KeywordDefPoll),
+    ("defvar", Tok::KeywordDefVar),
+    ("defwidget", Tok::KeywordDefWidget),
+    ("defwindow", Tok::KeywordDefWindow),
+    ("literal", Tok::KeywordLiteral),
+];
+
+pub fn peek_keyword(raw: &str, idx: usize, mode: Mode) -> Option<PeekResult<'_>> {
+    // A keyword has to span the whole word, e.g. `format` is a symbol, not `for` and `mat`.
+    let (keyword, tok) = KEYWORDS.iter().find(|(keyword, _)| {
+        starts_with_graphemes(raw, idx, keyword)
+            && symbol_end(raw, idx, mode) == idx + keyword.len()
+    })?;
+
+    let peeked_token = PeekedToken::new(tok.clone(), idx, idx + keyword.len());
+
+    log::trace!("successfully matched: {:?} as a keyword", peeked_token);
 
     Some(Ok(peeked_token))
 }
@@ -55,7 +35,6 @@ pub fn peek_keyword<'inp>(
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -64,29 +43,19 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         for (raw, expected) in vec![
-            ("include", PeekedToken::new(Tok::KeywordInclude, 0, 7, 7)),
-            ("true", PeekedToken::new(Tok::KeywordTrue, 0, 4, 4)),
-            ("false", PeekedToken::new(Tok::KeywordFalse, 0, 5, 5)),
-            (
-                "deflisten",
-                PeekedToken::new(Tok::KeywordDefListen, 0, 9, 9),
-            ),
-            ("defpoll", PeekedToken::new(Tok::KeywordDefPoll, 0, 7, 7)),
-            ("defvar", PeekedToken::new(Tok::KeywordDefVar, 0, 6, 6)),
-            (
-                "defwidget",
-                PeekedToken::new(Tok::KeywordDefWidget, 0, 9, 9),
-            ),
-            (
-                "defwindow",
-                PeekedToken::new(Tok::KeywordDefWindow, 0, 9, 9),
-            ),
-            ("literal", PeekedToken::new(Tok::KeywordLiteral, 0, 7, 7)),
+            ("include", PeekedToken::new(Tok::KeywordInclude, 0, 7)),
+            ("true", PeekedToken::new(Tok::KeywordTrue, 0, 4)),
+            ("false", PeekedToken::new(Tok::KeywordFalse, 0, 5)),
+            ("for", PeekedToken::new(Tok::KeywordFor, 0, 3)),
+            ("deflisten", PeekedToken::new(Tok::KeywordDefListen, 0, 9)),
+            ("defpoll", PeekedToken::new(Tok::KeywordDefPoll, 0, 7)),
+            ("defvar", PeekedToken::new(Tok::KeywordDefVar, 0, 6)),
+            ("defwidget", PeekedToken::new(Tok::KeywordDefWidget, 0, 9)),
+            ("defwindow", PeekedToken::new(Tok::KeywordDefWindow, 0, 9)),
+            ("literal", PeekedToken::new(Tok::KeywordLiteral, 0, 7)),
         ] {
-            let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
-
             assert_eq!(
-                peek_keyword(&graphemes, 0, raw),
+                peek_keyword(raw, 0, Mode::SExpr),
                 Some(Ok(expected.clone())),
                 "input '{}' failed to be parsed to {:?}",
                 raw,
@@ -100,11 +69,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "     true     ";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_keyword(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::KeywordTrue, 5, 9, 4))),
+            peek_keyword(raw, 5, Mode::SExpr),
+            Some(Ok(PeekedToken::new(Tok::KeywordTrue, 5, 9))),
         );
     }
 
@@ -113,11 +81,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "     true";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_keyword(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::KeywordTrue, 5, 9, 4))),
+            peek_keyword(raw, 5, Mode::SExpr),
+            Some(Ok(PeekedToken::new(Tok::KeywordTrue, 5, 9))),
         );
     }
 }
@@ -1,58 +1,48 @@
 use crate::lexer::{
+    grapheme_at,
     matcher::{self},
     tok::Tok,
-    Grapheme,
 };
 
-pub fn peek_literal_number<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &'inp str,
-) -> Option<matcher::PeekResult<'inp>> {
-    let digit_first = graphemes.get(idx)?;
-
-    if !grapheme_is_digit(digit_first) {
+/// Matches an integer like `42` or a decimal like `1.5`. A `.` without digits behind it is not
+/// part of the number.
+pub fn peek_literal_number(raw: &str, idx: usize) -> Option<matcher::PeekResult<'_>> {
+    let mut idx_inp_end = digits_end(raw, idx);
+    if idx_inp_end == idx {
         return None;
     }
 
-    let offset_next_non_digit = graphemes
-        .get(idx + 1..)
-        .and_then(|reminder| reminder.iter().position(|g| !grapheme_is_digit(g)))
-        .map(|o| o + 1);
-
-    let idx_inp_start = digit_first.0;
-    let (idx_inp_end, offset) = offset_next_non_digit
-        .map(|o| (graphemes[idx + o].0, o))
-        .unwrap_or((raw.len(), graphemes.len() - idx));
+    if grapheme_at(raw, idx_inp_end) == Some(".") {
+        let idx_fraction_end = digits_end(raw, idx_inp_end + 1);
+        if idx_fraction_end > idx_inp_end + 1 {
+            idx_inp_end = idx_fraction_end;
+        }
+    }
 
     Some(Ok(matcher::PeekedToken::new(
-        Tok::LiteralNumber(&raw[idx_inp_start..idx_inp_end]),
-        idx_inp_start,
+        Tok::LiteralNumber(&raw[idx..idx_inp_end]),
+        idx,
         idx_inp_end,
-        offset,
     )))
 }
 
-fn grapheme_is_digit(grapheme: &Grapheme<'_>) -> bool {
-    let chars: Vec<_> = grapheme.1.chars().collect();
-
-    if chars.len() > 1 {
-        // Multi-symbol graphemes, or even grapheme clusters, are not considered digits.
-        return false;
-    }
-
-    if let Some(char) = chars.first() {
-        return char.is_ascii_digit();
+fn digits_end(raw: &str, idx: usize) -> usize {
+    let mut idx_end = idx;
+    while let Some(grapheme) = grapheme_at(raw, idx_end).filter(|g| grapheme_is_digit(g)) {
+        idx_end += grapheme.len();
     }
+    idx_end
+}
 
-    false
+/// Multi-symbol graphemes, or even grapheme clusters, are not considered digits.
+fn grapheme_is_digit(grapheme: &str) -> bool {
+    matches!(grapheme.as_bytes(), [digit] if digit.is_ascii_digit())
 }
 
 #[cfg(test)]
 mod tests {
     use matcher::PeekedToken;
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -61,11 +51,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "8";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_literal_number(&graphemes, 0, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralNumber("8"), 0, 1, 1)))
+            peek_literal_number(raw, 0),
+            Some(Ok(PeekedToken::new(Tok::LiteralNumber("8"), 0, 1)))
         );
     }
 
@@ -74,30 +63,40 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "987654321";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_literal_number(&graphemes, 0, raw),
-            Some(Ok(PeekedToken::new(
-                Tok::LiteralNumber("987654321"),
-                0,
-                9,
-                9
-            )))
+            peek_literal_number(raw, 0),
+            Some(Ok(PeekedToken::new(Tok::LiteralNumber("987654321"), 0, 9)))
         );
     }
 
+    #[test]
+    fn decimals() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for (raw, expected) in [("1.5", "1.5"), ("0.25)", "0.25"), ("1.", "1"), ("1.x", "1")] {
+            assert_eq!(
+                peek_literal_number(raw, 0),
+                Some(Ok(PeekedToken::new(
+                    Tok::LiteralNumber(expected),
+                    0,
+                    expected.len()
+                ))),
+                "input '{}'",
+                raw
+            );
+        }
+    }
+
     #[test]
     fn offsets_are_correctly_calculated_in_the_middle() {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "    3 ";
 
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
-
         assert_eq!(
-            peek_literal_number(&graphemes, 4, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralNumber("3"), 4, 5, 1)))
+            peek_literal_number(raw, 4),
+            Some(Ok(PeekedToken::new(Tok::LiteralNumber("3"), 4, 5)))
         );
     }
 
@@ -107,11 +106,9 @@ mod tests {
 
         let raw = "  0";
 
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
-
         assert_eq!(
-            peek_literal_number(&graphemes, 2, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralNumber("0"), 2, 3, 1)))
+            peek_literal_number(raw, 2),
+            Some(Ok(PeekedToken::new(Tok::LiteralNumber("0"), 2, 3)))
         );
     }
 }
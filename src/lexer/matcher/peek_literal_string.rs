@@ -1,68 +1,50 @@
 use super::PeekResult;
-use crate::lexer::{matcher::PeekedToken, tok::Tok, Grapheme, LexicalError};
-
-pub fn peek_literal_string<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let quote_open = graphemes.get(idx)?;
-
-    if quote_open.1 != "\"" {
-        return None;
-    }
-
-    let Some(offset_grapheme_quote_close) = graphemes
-        .get(idx + 1..)?
-        .iter()
-        .position(|g| g.1 == "\"")
-        .map(|o| o + 1)
-    else {
-        return Some(Err(LexicalError::UnterminatedLiteralString {
-            src: raw,
-            span: (idx, raw.len() - idx).into(),
-        }));
+use crate::lexer::{grapheme_at, matcher::PeekedToken, tok::Tok, LexicalError};
+
+/// Matches a string literal in double or single quotes, e.g. `"hi"` or `'hi'`. The other quote
+/// is an ordinary character within it.
+pub fn peek_literal_string(raw: &str, idx: usize) -> Option<PeekResult<'_>> {
+    let quote = match grapheme_at(raw, idx)? {
+        "\"" => '"',
+        "'" => '\'',
+        _ => return None,
     };
 
-    let quote_close = graphemes[idx + offset_grapheme_quote_close];
-
-    log::trace!(">>> {:?}", quote_close);
-
-    let idx_consumed_from = graphemes[idx].0;
-    let idx_consumed_to = quote_close.0 + quote_close.1.len();
-
-    let char_first = graphemes[idx + 1];
-    let char_last = graphemes[idx + offset_grapheme_quote_close - 1];
-
-    let idx_slice_from = char_first.0;
-    let idx_slice_to = char_last.0 + char_last.1.len();
+    // A quote followed by a combining character is not a closing quote.
+    let mut idx_search = idx + 1;
+    let idx_quote_close = loop {
+        let Some(offset) = raw[idx_search..].find(quote) else {
+            return Some(Err(LexicalError::UnterminatedLiteralString {
+                src: raw,
+                span: (idx, raw.len() - idx).into(),
+            }));
+        };
+        idx_search += offset;
+        if grapheme_at(raw, idx_search).map(str::len) == Some(1) {
+            break idx_search;
+        }
+        idx_search += 1;
+    };
 
-    let slice = &raw[idx_slice_from..idx_slice_to];
+    let slice = &raw[idx + 1..idx_quote_close];
 
     log::trace!(
-        "constructing string out of {} graphemes, indexes: {} to {}, value: '{}', (indexes with quotes: {} to {})",
-        offset_grapheme_quote_close,
-        idx_slice_from,
-        idx_slice_to,
-        slice,
-        idx_consumed_from,
-        idx_consumed_to
+        "constructing string out of indexes: {} to {}, value: '{}'",
+        idx,
+        idx_quote_close + 1,
+        slice
     );
 
-    let peeked_token = PeekedToken::new(
+    Some(Ok(PeekedToken::new(
         Tok::LiteralString(slice),
-        idx_consumed_from,
-        idx_consumed_to,
-        offset_grapheme_quote_close + 1,
-    );
-
-    Some(Ok(peeked_token))
+        idx,
+        idx_quote_close + 1,
+    )))
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -71,10 +53,9 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = r#"   "unterminated"#;
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_literal_string(&graphemes, 3, raw),
+            peek_literal_string(raw, 3),
             Some(Err(LexicalError::UnterminatedLiteralString {
                 src: raw,
                 span: (3, 13).into()
@@ -82,34 +63,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unterminated_after_non_ascii() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let raw = "󰕾 ✓ \"abc";
+
+        assert_eq!(
+            peek_literal_string(raw, 9),
+            Some(Err(LexicalError::UnterminatedLiteralString {
+                src: raw,
+                span: (9, 4).into()
+            }))
+        );
+    }
+
+    #[test]
+    fn non_ascii_content() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let raw = "  \"󰕾 ${vol}\"";
+
+        assert_eq!(
+            peek_literal_string(raw, 2),
+            Some(Ok(PeekedToken::new(Tok::LiteralString("󰕾 ${vol}"), 2, 15))),
+        );
+    }
+
     #[test]
     fn correctly_identified_and_remove_quotes() {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = r#""hello world""#;
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_literal_string(&graphemes, 0, raw),
+            peek_literal_string(raw, 0),
             Some(Ok(PeekedToken::new(
                 Tok::LiteralString("hello world"),
                 0,
                 13,
-                13,
             )))
         );
     }
 
+    #[test]
+    fn single_quotes() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let raw = r#"'say "hi"'"#;
+
+        assert_eq!(
+            peek_literal_string(raw, 0),
+            Some(Ok(PeekedToken::new(
+                Tok::LiteralString(r#"say "hi""#),
+                0,
+                10
+            ))),
+        );
+        assert_eq!(
+            peek_literal_string("'it\"s", 0),
+            Some(Err(LexicalError::UnterminatedLiteralString {
+                src: "'it\"s",
+                span: (0, 5).into()
+            }))
+        );
+    }
+
     #[test]
     fn empty() {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = r#""""#;
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_literal_string(&graphemes, 0, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralString(""), 0, 2, 2))),
+            peek_literal_string(raw, 0),
+            Some(Ok(PeekedToken::new(Tok::LiteralString(""), 0, 2))),
         );
     }
 
@@ -119,11 +147,9 @@ mod tests {
 
         let raw = r#"  "foo" "#;
 
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
-
         assert_eq!(
-            peek_literal_string(&graphemes, 2, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralString("foo"), 2, 7, 5))),
+            peek_literal_string(raw, 2),
+            Some(Ok(PeekedToken::new(Tok::LiteralString("foo"), 2, 7))),
         );
     }
 
@@ -132,11 +158,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = r#"     "foo""#;
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_literal_string(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralString("foo"), 5, 10, 5))),
+            peek_literal_string(raw, 5),
+            Some(Ok(PeekedToken::new(Tok::LiteralString("foo"), 5, 10))),
         );
     }
 }
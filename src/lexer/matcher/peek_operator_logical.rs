@@ -1,86 +1,24 @@
-use super::{PeekResult, PeekedToken};
-use crate::lexer::{tok::Tok, Grapheme};
-
-pub fn peek_operator_logical<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    if let Some(result) = peek_operator_logical_two_characters(graphemes, idx, raw) {
-        return Some(result);
-    }
-
-    peek_operator_logical_single_character(graphemes, idx, raw)
-}
-
-fn peek_operator_logical_two_characters<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &str,
-) -> Option<PeekResult<'inp>> {
-    let [first, second]: &[_; 2] = graphemes.get(idx..idx + 2)?.try_into().ok()?;
-
-    let idx_raw_from = first.0;
-    let idx_raw_to = second.0 + second.1.len();
-
-    let slice = &raw[idx_raw_from..idx_raw_to];
-
-    log::trace!(
-        "trying to interpret grapheme: {:?} as a logical operator with two characters",
-        slice
-    );
-
-    let tok = match slice {
-        "||" => Tok::OperatorLogicalOr,
-        "&&" => Tok::OperatorLogicalAnd,
-        _ => {
-            log::trace!(
-                "could not match '{}' as a logical operator with two characters... returning none",
-                slice
-            );
-            return None;
-        }
-    };
-
-    let peeked_tok = PeekedToken::new(tok, idx_raw_from, idx_raw_to, 2);
-    log::debug!(
-        "successfully interpreted: {:?} as a logical operator with two characters",
-        peeked_tok
-    );
-    Some(Ok(peeked_tok))
-}
-
-fn peek_operator_logical_single_character<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let grapheme = graphemes.get(idx)?;
-
-    let idx_raw_from = grapheme.0;
-    let idx_raw_to = grapheme.0 + grapheme.1.len();
-
-    let slice = &raw[idx_raw_from..idx_raw_to];
-
-    log::trace!(
-        "trying to interpret grapheme: {:?} as a logical operator with a single character",
-        slice
-    );
-
-    let tok = match slice {
-        "!" => Tok::OperatorLogicalNot,
-        _ => {
-            log::trace!(
-                "could not match '{}' as a logical operator with a single character... returning none",
-                slice
-            );
-            return None;
-        }
+use super::{starts_with_graphemes, PeekResult, PeekedToken};
+use crate::lexer::tok::Tok;
+
+const OPERATORS: &[(&str, Tok<'static>)] = &[
+    ("||", Tok::OperatorLogicalOr),
+    ("&&", Tok::OperatorLogicalAnd),
+    ("!", Tok::OperatorLogicalNot),
+];
+
+pub fn peek_operator_logical(raw: &str, idx: usize) -> Option<PeekResult<'_>> {
+    let Some((operator, tok)) = OPERATORS
+        .iter()
+        .find(|(operator, _)| starts_with_graphemes(raw, idx, operator))
+    else {
+        log::trace!("could not match a logical operator at {}", idx);
+        return None;
     };
 
-    let peeked_tok = PeekedToken::new(tok, idx_raw_from, idx_raw_to, 1);
+    let peeked_tok = PeekedToken::new(tok.clone(), idx, idx + operator.len());
     log::debug!(
-        "successfully interpreted: {:?} as a logical character with a single character",
+        "successfully interpreted: {:?} as a logical operator",
         peeked_tok
     );
     Some(Ok(peeked_tok))
@@ -89,7 +27,6 @@ fn peek_operator_logical_single_character<'inp>(
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -97,15 +34,13 @@ mod tests {
     fn all_delimiters_are_correctly_identified() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        for (input, expected) in vec![
-            ("||", PeekedToken::new(Tok::OperatorLogicalOr, 0, 2, 2)),
-            ("&&", PeekedToken::new(Tok::OperatorLogicalAnd, 0, 2, 2)),
-            ("!", PeekedToken::new(Tok::OperatorLogicalNot, 0, 1, 1)),
+        for (input, expected) in [
+            ("||", PeekedToken::new(Tok::OperatorLogicalOr, 0, 2)),
+            ("&&", PeekedToken::new(Tok::OperatorLogicalAnd, 0, 2)),
+            ("!", PeekedToken::new(Tok::OperatorLogicalNot, 0, 1)),
         ] {
-            let graphemes: Vec<_> = input.grapheme_indices(true).collect();
-
             assert_eq!(
-                peek_operator_logical(&graphemes, 0, input),
+                peek_operator_logical(input, 0),
                 Some(Ok(expected.clone())),
                 "input '{}' failed to be parsed to {:?}",
                 input,
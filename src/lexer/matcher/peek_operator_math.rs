@@ -1,36 +1,27 @@
 use super::PeekResult;
-use crate::lexer::{matcher::PeekedToken, tok::Tok, Grapheme};
+use crate::lexer::{grapheme_at, matcher::PeekedToken, tok::Tok};
 
-pub fn peek_operator_math<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    _: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let grapheme = graphemes.get(idx)?;
+pub fn peek_operator_math(raw: &str, idx: usize) -> Option<PeekResult<'_>> {
+    let grapheme = grapheme_at(raw, idx)?;
 
     log::trace!(
         "trying to interpret grapheme: {:?} as a mathematical operator",
         grapheme
     );
 
-    let tok = match grapheme.1 {
+    let tok = match grapheme {
         "%" => Tok::OperatorMathModulo,
         "*" => Tok::OperatorMathMultiplication,
         "+" => Tok::OperatorMathAddition,
         "-" => Tok::OperatorMathSubtraction,
         "/" => Tok::OperatorMathDivision,
         _ => {
-            log::trace!(
-                "could not match '{}' as a mathematical operator",
-                grapheme.1
-            );
+            log::trace!("could not match '{}' as a mathematical operator", grapheme);
             return None;
         }
     };
 
-    let span_from = grapheme.0;
-    let span_to = grapheme.0 + grapheme.1.len();
-    let peeked_token = PeekedToken::new(tok, span_from, span_to, 1);
+    let peeked_token = PeekedToken::new(tok, idx, idx + grapheme.len());
 
     log::trace!(
         "successfully matched: {:?} as a mathematical operator",
@@ -43,7 +34,6 @@ pub fn peek_operator_math<'inp>(
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -51,18 +41,16 @@ mod tests {
     fn all_delimiters_are_correctly_identified() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        for (input, expected) in vec![
+        for (input, expected) in [
             ("+", Tok::OperatorMathAddition),
             ("-", Tok::OperatorMathSubtraction),
             ("/", Tok::OperatorMathDivision),
             ("*", Tok::OperatorMathMultiplication),
             ("%", Tok::OperatorMathModulo),
         ] {
-            let graphemes: Vec<_> = input.grapheme_indices(true).collect();
-
             assert_eq!(
-                peek_operator_math(&graphemes, 0, input),
-                Some(Ok(PeekedToken::new(expected.clone(), 0, 1, 1))),
+                peek_operator_math(input, 0),
+                Some(Ok(PeekedToken::new(expected.clone(), 0, 1))),
                 "input '{}' failed to be parsed to {:?}",
                 input,
                 expected
@@ -75,11 +63,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "   +    ";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_operator_math(&graphemes, 3, raw),
-            Some(Ok(PeekedToken::new(Tok::OperatorMathAddition, 3, 4, 1))),
+            peek_operator_math(raw, 3),
+            Some(Ok(PeekedToken::new(Tok::OperatorMathAddition, 3, 4))),
         );
     }
 
@@ -88,11 +75,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "    +";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_operator_math(&graphemes, 4, raw),
-            Some(Ok(PeekedToken::new(Tok::OperatorMathAddition, 4, 5, 1))),
+            peek_operator_math(raw, 4),
+            Some(Ok(PeekedToken::new(Tok::OperatorMathAddition, 4, 5))),
         );
     }
 }
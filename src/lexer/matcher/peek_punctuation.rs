@@ -1,33 +1,27 @@
 use super::PeekResult;
-use crate::lexer::{matcher::PeekedToken, tok::Tok, Grapheme};
+use crate::lexer::{grapheme_at, matcher::PeekedToken, tok::Tok};
 
-pub fn peek_punctuation<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    _: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let grapheme = graphemes.get(idx)?;
+pub fn peek_punctuation(raw: &str, idx: usize) -> Option<PeekResult<'_>> {
+    let grapheme = grapheme_at(raw, idx)?;
 
     log::trace!(
         "trying to interpret grapheme: {:?} as punctuation",
         grapheme
     );
 
-    let tok = match grapheme.1 {
+    let tok = match grapheme {
         "?" => Tok::PunctuationQuestionMark,
         ":" => Tok::PunctuationColon,
         _ => {
             log::trace!(
                 "could not match '{}' as punctuation... returning none",
-                grapheme.1
+                grapheme
             );
             return None;
         }
     };
 
-    let span_from = grapheme.0;
-    let span_to = grapheme.0 + grapheme.1.len();
-    let spanned_tok = PeekedToken::new(tok, span_from, span_to, 1);
+    let spanned_tok = PeekedToken::new(tok, idx, idx + grapheme.len());
 
     log::trace!("successfully matched: {:?} as punctuation", spanned_tok);
 
@@ -37,7 +31,6 @@ pub fn peek_punctuation<'inp>(
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -45,15 +38,13 @@ mod tests {
     fn all_delimiters_are_correctly_identified() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        for (input, expected) in vec![
+        for (input, expected) in [
             ("?", Tok::PunctuationQuestionMark),
             (":", Tok::PunctuationColon),
         ] {
-            let graphemes: Vec<_> = input.grapheme_indices(true).collect();
-
             assert_eq!(
-                peek_punctuation(&graphemes, 0, input),
-                Some(Ok(PeekedToken::new(expected.clone(), 0, 1, 1))),
+                peek_punctuation(input, 0),
+                Some(Ok(PeekedToken::new(expected.clone(), 0, 1))),
                 "input '{}' failed to be parsed to {:?}",
                 input,
                 expected
@@ -66,11 +57,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "     :     ";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_punctuation(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::PunctuationColon, 5, 6, 1))),
+            peek_punctuation(raw, 5),
+            Some(Ok(PeekedToken::new(Tok::PunctuationColon, 5, 6))),
         );
     }
 
@@ -79,11 +69,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "     :";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_punctuation(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::PunctuationColon, 5, 6, 1))),
+            peek_punctuation(raw, 5),
+            Some(Ok(PeekedToken::new(Tok::PunctuationColon, 5, 6))),
         );
     }
 }
@@ -1,41 +1,91 @@
 use super::PeekResult;
 use crate::lexer::{
-    grapheme_is_whitespace,
-    matcher::{peek_delimiter, PeekedToken},
+    grapheme_at, grapheme_is_whitespace,
+    matcher::{delimiter, PeekedToken},
     tok::Tok,
-    Grapheme,
+    LexicalError, Mode,
 };
 
-pub fn peek_symbol<'inp>(
-    graphemes: &[Grapheme<'inp>],
-    idx: usize,
-    raw: &'inp str,
-) -> Option<PeekResult<'inp>> {
-    let offset_graphemes_end = graphemes.get(idx..)?.iter().enumerate().position(|(o, g)| {
-        grapheme_is_whitespace(g) || peek_delimiter(graphemes, idx + o, raw).is_some()
-    });
-
-    let idx_raw_from = graphemes.get(idx)?.0;
-    let (idx_raw_to, offset_graphemes_end) = offset_graphemes_end
-        .map(|o| (graphemes[idx + o].0, o))
-        .unwrap_or((raw.len(), graphemes.len() - idx));
-
-    let peeked_token = PeekedToken::new(
-        Tok::LiteralSymbol(&raw[idx_raw_from..idx_raw_to]),
-        idx_raw_from,
-        idx_raw_to,
-        offset_graphemes_end,
-    );
+/// Matches a symbol, which starts with a letter or `_`, followed by letters, digits, `_`, `-`, `?`
+/// and `.`, e.g. `circular-progress`, `visible?` or `EWW_BATTERY.BAT0`. Letters and digits are
+/// those of Unicode, a grapheme counts as the first character it is made of.
+///
+/// The symbol ends at whitespace or a delimiter, in expressions at operators as well. Anything
+/// else in front of that is an [`LexicalError::InvalidSymbol`], e.g. the `"` of `foo"bar`.
+pub fn peek_symbol(raw: &str, idx: usize, mode: Mode) -> Option<PeekResult<'_>> {
+    let idx_raw_to = symbol_end(raw, idx, mode);
+    if idx_raw_to == idx {
+        return None;
+    }
+
+    let mut idx_grapheme = idx;
+    while idx_grapheme < idx_raw_to {
+        let grapheme = grapheme_at(raw, idx_grapheme)?;
+        let is_valid = if idx_grapheme == idx {
+            is_symbol_start(grapheme)
+        } else {
+            is_symbol_continue(grapheme)
+        };
+        if !is_valid {
+            log::trace!("{:?} cannot be part of a symbol", grapheme);
+
+            return Some(Err(LexicalError::InvalidSymbol {
+                src: raw,
+                span: (idx_grapheme, grapheme.len()).into(),
+                char: grapheme,
+            }));
+        }
+        idx_grapheme += grapheme.len();
+    }
+
+    let peeked_token = PeekedToken::new(Tok::LiteralSymbol(&raw[idx..idx_raw_to]), idx, idx_raw_to);
 
     log::trace!("successfully matched: {:?} as a symbol", peeked_token);
 
     Some(Ok(peeked_token))
 }
 
+/// Returns the byte offset of the first whitespace or delimiter at or after `idx`. In
+/// expressions, operators end symbols as well.
+pub(crate) fn symbol_end(raw: &str, idx: usize, mode: Mode) -> usize {
+    let mut idx_end = idx;
+    while let Some(grapheme) = grapheme_at(raw, idx_end) {
+        if grapheme_is_whitespace(grapheme)
+            || delimiter(grapheme).is_some()
+            || (mode == Mode::Expr && is_operator(grapheme))
+        {
+            break;
+        }
+        idx_end += grapheme.len();
+    }
+    idx_end
+}
+
+fn is_symbol_start(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+}
+
+fn is_symbol_continue(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '?' | '.'))
+}
+
+/// Whether `grapheme` is an operator or punctuation of expressions on its own, or starts one.
+fn is_operator(grapheme: &str) -> bool {
+    matches!(
+        grapheme,
+        "%" | "*" | "+" | "-" | "/" | "=" | "<" | ">" | "!" | "&" | "|" | "?" | ":"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
@@ -43,17 +93,15 @@ mod tests {
     fn all_delimiters_are_correctly_identified() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        for (raw, expected) in vec![
-            ("foo", PeekedToken::new(Tok::LiteralSymbol("foo"), 0, 3, 3)),
+        for (raw, expected) in [
+            ("foo", PeekedToken::new(Tok::LiteralSymbol("foo"), 0, 3)),
             (
                 "bar123",
-                PeekedToken::new(Tok::LiteralSymbol("bar123"), 0, 6, 6),
+                PeekedToken::new(Tok::LiteralSymbol("bar123"), 0, 6),
             ),
         ] {
-            let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
-
             assert_eq!(
-                peek_symbol(&graphemes, 0, raw),
+                peek_symbol(raw, 0, Mode::SExpr),
                 Some(Ok(expected.clone())),
                 "input '{}' failed to be parsed to {:?}",
                 raw,
@@ -62,16 +110,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn operators_end_symbols_in_expressions() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for (raw, mode, expected) in [
+            ("space-evenly", Mode::SExpr, "space-evenly"),
+            ("space-evenly", Mode::Expr, "space"),
+            ("x-1", Mode::Expr, "x"),
+            ("a==b", Mode::Expr, "a"),
+            ("a?b:c", Mode::Expr, "a"),
+            ("visible?", Mode::SExpr, "visible?"),
+            ("a-\u{301}b", Mode::Expr, "a-\u{301}b"),
+        ] {
+            assert_eq!(
+                peek_symbol(raw, 0, mode),
+                Some(Ok(PeekedToken::new(
+                    Tok::LiteralSymbol(expected),
+                    0,
+                    expected.len()
+                ))),
+                "input '{}' in {:?} mode",
+                raw,
+                mode
+            );
+        }
+        assert_eq!(peek_symbol("-1", 0, Mode::Expr), None);
+    }
+
+    #[test]
+    fn valid_symbols() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for raw in [
+            "_private",
+            "EWW_BATTERY.BAT0.capacity",
+            "run-while",
+            "visible?",
+            "größe",
+            "e\u{301}",
+            "x1",
+        ] {
+            assert_eq!(
+                peek_symbol(raw, 0, Mode::SExpr),
+                Some(Ok(PeekedToken::new(Tok::LiteralSymbol(raw), 0, raw.len()))),
+                "input '{}'",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_symbols() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for (raw, mode, offset, char) in [
+            ("foo\"bar", Mode::SExpr, 3, "\""),
+            ("a;b", Mode::SExpr, 1, ";"),
+            ("x+1", Mode::SExpr, 1, "+"),
+            ("a:b", Mode::SExpr, 1, ":"),
+            (".5", Mode::SExpr, 0, "."),
+            ("-x", Mode::SExpr, 0, "-"),
+            ("1up", Mode::SExpr, 0, "1"),
+            ("icon🙂", Mode::SExpr, 4, "🙂"),
+            ("a$b", Mode::Expr, 1, "$"),
+            ("a(\u{301}", Mode::Expr, 1, "(\u{301}"),
+        ] {
+            assert_eq!(
+                peek_symbol(raw, 0, mode),
+                Some(Err(LexicalError::InvalidSymbol {
+                    src: raw,
+                    span: (offset, char.len()).into(),
+                    char,
+                })),
+                "input '{}' in {:?} mode",
+                raw,
+                mode
+            );
+        }
+    }
+
     #[test]
     fn offsets_are_correctly_calculated_in_middle() {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "     baz     ";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_symbol(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralSymbol("baz"), 5, 8, 3))),
+            peek_symbol(raw, 5, Mode::SExpr),
+            Some(Ok(PeekedToken::new(Tok::LiteralSymbol("baz"), 5, 8))),
         );
     }
 
@@ -80,11 +207,10 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let raw = "     baz";
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
 
         assert_eq!(
-            peek_symbol(&graphemes, 5, raw),
-            Some(Ok(PeekedToken::new(Tok::LiteralSymbol("baz"), 5, 8, 3))),
+            peek_symbol(raw, 5, Mode::SExpr),
+            Some(Ok(PeekedToken::new(Tok::LiteralSymbol("baz"), 5, 8))),
         );
     }
 }
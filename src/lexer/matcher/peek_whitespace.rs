@@ -1,50 +1,49 @@
-use crate::lexer::{grapheme_is_whitespace, Grapheme};
+use crate::lexer::{grapheme_at, grapheme_is_whitespace};
 
-pub fn peek_whitespace<'inp>(graphemes: &[Grapheme<'inp>], idx: usize, raw: &'inp str) -> usize {
+/// Returns the number of whitespace bytes at `idx`.
+pub fn peek_whitespace(raw: &str, idx: usize) -> usize {
     log::trace!(
-        "skipping potential whitespaces, starting at grapheme index: {}",
+        "skipping potential whitespaces, starting at byte index: {}",
         idx
     );
 
-    let offset = graphemes
-        .get(idx..)
-        .and_then(|remainder| remainder.iter().position(|g| !grapheme_is_whitespace(g)));
-
-    if let Some(offset) = offset {
-        let grapheme_end = graphemes[idx + offset];
-
-        let idx_raw_from = graphemes[idx].0;
-        let idx_raw_to = grapheme_end.0 + grapheme_end.1.len();
+    let mut idx_end = idx;
+    while let Some(grapheme) = grapheme_at(raw, idx_end).filter(|g| grapheme_is_whitespace(g)) {
+        idx_end += grapheme.len();
+    }
 
+    if idx_end > idx {
         log::trace!(
-            "found {} whitespace-graphemes to skip ('{}')",
-            offset,
-            &raw[idx_raw_from..idx_raw_to]
+            "found {} whitespace bytes to skip ('{}')",
+            idx_end - idx,
+            &raw[idx..idx_end]
         );
-
-        return offset;
     }
 
-    graphemes.len() - idx
+    idx_end - idx
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
-    use unicode_segmentation::UnicodeSegmentation;
 
     use super::*;
 
     #[test]
     fn single_whitespace() {
         for raw in [" ", "\n", "\r\n", "\t"] {
-            assert_eq!(test(raw, 0), 1, "failed to consume '{}' as whitespace", raw);
+            assert_eq!(
+                test(raw, 0),
+                raw.len(),
+                "failed to consume '{}' as whitespace",
+                raw
+            );
         }
     }
 
     #[test]
     fn multiple_whitespaces() {
-        for (raw, expected) in [("   ", 3), ("\t\t\t", 3), (" \r\n\t", 3)] {
+        for (raw, expected) in [("   ", 3), ("\t\t\t", 3), (" \r\n\t", 4), (" \u{3000}", 4)] {
             assert_eq!(
                 test(raw, 0),
                 expected,
@@ -66,8 +65,6 @@ mod tests {
 
     fn test(raw: &str, idx: usize) -> usize {
         let _ = env_logger::builder().is_test(true).try_init();
-        let graphemes: Vec<_> = raw.grapheme_indices(true).collect();
-
-        peek_whitespace(&graphemes, idx, raw)
+        peek_whitespace(raw, idx)
     }
 }
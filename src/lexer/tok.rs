@@ -2,8 +2,10 @@
 pub enum Tok<'inp> {
     Comment(&'inp str),
     DelimiterComma,
+    DelimiterLeftBrace,
     DelimiterLeftBracket,
     DelimiterLeftParen,
+    DelimiterRightBrace,
     DelimiterRightBracket,
     DelimiterRightParen,
     KeywordDefListen,
@@ -12,6 +14,7 @@ pub enum Tok<'inp> {
     KeywordDefWidget,
     KeywordDefWindow,
     KeywordFalse,
+    KeywordFor,
     KeywordInclude,
     KeywordLiteral,
     KeywordTrue,
@@ -35,3 +38,20 @@ pub enum Tok<'inp> {
     PunctuationColon,
     PunctuationQuestionMark,
 }
+
+impl Tok<'_> {
+    /// Whether the token is the keyword of a top-level form, e.g. `defvar`. These are only valid
+    /// right after the `(` of a form at the top level.
+    pub fn is_top_level_keyword(&self) -> bool {
+        matches!(
+            self,
+            Tok::KeywordDefListen
+                | Tok::KeywordDefPoll
+                | Tok::KeywordDefVar
+                | Tok::KeywordDefWidget
+                | Tok::KeywordDefWindow
+                | Tok::KeywordInclude
+                | Tok::KeywordLiteral
+        )
+    }
+}
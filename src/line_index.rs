@@ -0,0 +1,301 @@
+//! Conversions between byte offsets and lines and columns.
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The unit columns are counted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnUnit {
+    /// Bytes of UTF-8, like byte offsets.
+    Utf8,
+    /// UTF-16 code units, like LSP positions.
+    Utf16,
+    /// Extended grapheme clusters, i.e. the characters a user perceives.
+    Grapheme,
+}
+
+/// A zero-based line and column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl LineCol {
+    pub fn new(line: usize, col: usize) -> Self {
+        Self { line, col }
+    }
+}
+
+/// A character or grapheme that takes fewer units in a column than bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Wide {
+    span: Range<usize>,
+    /// The bytes saved by this and all previous elements.
+    saved: usize,
+}
+
+/// Converts between byte offsets and [`LineCol`]s of a source in O(log n). Lines end at `\n`,
+/// which belongs to the line it ends.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineIndex {
+    len: usize,
+    /// The content of every line, without the line ending.
+    lines: Vec<Range<usize>>,
+    /// Characters taking fewer UTF-16 code units than bytes, i.e. all non-ASCII ones.
+    wide_chars: Vec<Wide>,
+    /// Graphemes of more than one byte.
+    wide_graphemes: Vec<Wide>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        for (idx, _) in source.match_indices('\n') {
+            let line_end = if source[..idx].ends_with('\r') {
+                idx - 1
+            } else {
+                idx
+            };
+            lines.push(line_start..line_end.max(line_start));
+            line_start = idx + 1;
+        }
+        lines.push(line_start..source.len());
+
+        let mut saved = 0;
+        let wide_chars = source
+            .char_indices()
+            .filter(|(_, c)| !c.is_ascii())
+            .map(|(idx, c)| {
+                saved += c.len_utf8() - c.len_utf16();
+                Wide {
+                    span: idx..idx + c.len_utf8(),
+                    saved,
+                }
+            })
+            .collect();
+
+        let mut saved = 0;
+        let wide_graphemes = source
+            .grapheme_indices(true)
+            .filter(|(_, grapheme)| grapheme.len() > 1)
+            .map(|(idx, grapheme)| {
+                saved += grapheme.len() - 1;
+                Wide {
+                    span: idx..idx + grapheme.len(),
+                    saved,
+                }
+            })
+            .collect();
+
+        Self {
+            len: source.len(),
+            lines,
+            wide_chars,
+            wide_graphemes,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the line and column of `offset`. Offsets past the end of the source are clamped
+    /// to its end, offsets within a character or grapheme to its start.
+    pub fn line_col(&self, offset: usize, unit: ColumnUnit) -> LineCol {
+        let offset = self.round_down(offset.min(self.len), unit);
+        let line = self.lines.partition_point(|line| line.start <= offset) - 1;
+        let line_start = self.lines[line].start;
+
+        let col = match self.wides(unit) {
+            Some(wides) => {
+                offset
+                    - line_start
+                    - (saved_before(wides, offset) - saved_before(wides, line_start))
+            }
+            None => offset - line_start,
+        };
+
+        LineCol { line, col }
+    }
+
+    /// Returns the offset of `line_col`. Lines past the end of the source are clamped to its end,
+    /// columns past the end of a line to the end of the line.
+    pub fn offset(&self, line_col: LineCol, unit: ColumnUnit) -> usize {
+        let Some(line) = self.lines.get(line_col.line) else {
+            return self.len;
+        };
+
+        let offset = match self.wides(unit) {
+            Some(wides) => {
+                let lo = wides.partition_point(|wide| wide.span.start < line.start);
+                let hi = wides.partition_point(|wide| wide.span.start < line.end);
+                let saved_in_front = saved_before(wides, line.start);
+                let col_after =
+                    |wide: &Wide| wide.span.end - line.start - (wide.saved - saved_in_front);
+
+                // The last wide element of the line in front of the column, the rest is as long
+                // in columns as in bytes.
+                let in_front =
+                    wides[lo..hi].partition_point(|wide| col_after(wide) <= line_col.col);
+                let (offset, col) = match in_front.checked_sub(1) {
+                    Some(idx) => (wides[lo + idx].span.end, col_after(&wides[lo + idx])),
+                    None => (line.start, 0),
+                };
+                let next_start = wides[lo..hi]
+                    .get(in_front)
+                    .map_or(usize::MAX, |wide| wide.span.start);
+                (offset + line_col.col - col).min(next_start)
+            }
+            None => self.round_down(line.start.saturating_add(line_col.col).min(line.end), unit),
+        };
+
+        offset.min(line.end)
+    }
+
+    fn wides(&self, unit: ColumnUnit) -> Option<&[Wide]> {
+        match unit {
+            ColumnUnit::Utf8 => None,
+            ColumnUnit::Utf16 => Some(&self.wide_chars),
+            ColumnUnit::Grapheme => Some(&self.wide_graphemes),
+        }
+    }
+
+    /// Moves `offset` to the start of the character, or the grapheme for grapheme columns, it
+    /// lies in.
+    fn round_down(&self, offset: usize, unit: ColumnUnit) -> usize {
+        let wides = match unit {
+            ColumnUnit::Grapheme => &self.wide_graphemes,
+            ColumnUnit::Utf8 | ColumnUnit::Utf16 => &self.wide_chars,
+        };
+        let idx = wides.partition_point(|wide| wide.span.end <= offset);
+        match wides.get(idx) {
+            Some(wide) if wide.span.start < offset => wide.span.start,
+            _ => offset,
+        }
+    }
+}
+
+/// Returns the bytes saved by the elements ending at or in front of `offset`.
+fn saved_before(wides: &[Wide], offset: usize) -> usize {
+    let idx = wides.partition_point(|wide| wide.span.end <= offset);
+    idx.checked_sub(1).map_or(0, |idx| wides[idx].saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const SOURCE: &str = "(defvar a 1)\r\n(defvar 𝄞 \"é🇩🇪\")\n\n;";
+
+    #[test]
+    fn line_col() {
+        let index = test(SOURCE);
+
+        for (offset, utf8, utf16, grapheme) in [
+            (0, (0, 0), (0, 0), (0, 0)),
+            (12, (0, 12), (0, 12), (0, 12)),
+            (14, (1, 0), (1, 0), (1, 0)),
+            (22, (1, 8), (1, 8), (1, 8)),
+            (26, (1, 12), (1, 10), (1, 9)),
+            (28, (1, 14), (1, 12), (1, 11)),
+            (30, (1, 16), (1, 13), (1, 12)),
+            // Within the grapheme of the flag.
+            (34, (1, 20), (1, 15), (1, 12)),
+            (38, (1, 24), (1, 17), (1, 13)),
+            (42, (3, 0), (3, 0), (3, 0)),
+            (43, (3, 1), (3, 1), (3, 1)),
+        ] {
+            assert_eq!(index.line_col(offset, ColumnUnit::Utf8), utf8.into());
+            assert_eq!(index.line_col(offset, ColumnUnit::Utf16), utf16.into());
+            assert_eq!(
+                index.line_col(offset, ColumnUnit::Grapheme),
+                grapheme.into()
+            );
+        }
+    }
+
+    #[test]
+    fn offset() {
+        let index = test(SOURCE);
+
+        for (line_col, unit, offset) in [
+            ((1, 10), ColumnUnit::Utf16, 26),
+            ((1, 11), ColumnUnit::Grapheme, 28),
+            ((1, 12), ColumnUnit::Grapheme, 30),
+            ((1, 13), ColumnUnit::Grapheme, 38),
+            ((1, 15), ColumnUnit::Utf16, 34),
+            // Columns within a character.
+            ((1, 9), ColumnUnit::Utf8, 22),
+            ((1, 9), ColumnUnit::Utf16, 22),
+            ((1, 16), ColumnUnit::Utf16, 34),
+            // Columns past the end of a line.
+            ((1, 27), ColumnUnit::Utf8, 40),
+            ((0, 100), ColumnUnit::Grapheme, 12),
+            ((2, 1), ColumnUnit::Utf16, 41),
+            ((100, 0), ColumnUnit::Utf8, SOURCE.len()),
+        ] {
+            assert_eq!(
+                index.offset(line_col.into(), unit),
+                offset,
+                "{line_col:?} {unit:?}"
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(source in "[a \n\r\u{301}é𝄞🇩🇪]{0,20}") {
+            let index = test(&source);
+
+            for unit in [ColumnUnit::Utf8, ColumnUnit::Utf16, ColumnUnit::Grapheme] {
+                let boundaries: Vec<usize> = match unit {
+                    ColumnUnit::Grapheme => source
+                        .grapheme_indices(true)
+                        .map(|(idx, _)| idx)
+                        .chain([source.len()])
+                        .collect(),
+                    _ => source
+                        .char_indices()
+                        .map(|(idx, _)| idx)
+                        .chain([source.len()])
+                        .collect(),
+                };
+
+                for offset in boundaries {
+                    let line_col = index.line_col(offset, unit);
+                    let line_start = source[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+                    let expected = match unit {
+                        ColumnUnit::Utf8 => offset - line_start,
+                        ColumnUnit::Utf16 => source[line_start..offset].encode_utf16().count(),
+                        ColumnUnit::Grapheme => source[line_start..offset].graphemes(true).count(),
+                    };
+                    prop_assert_eq!(line_col.line, source[..offset].matches('\n').count());
+                    prop_assert_eq!(line_col.col, expected);
+
+                    // A CR in front of the LF is not part of the line.
+                    if !source[offset..].starts_with('\n') || !source[..offset].ends_with('\r') {
+                        prop_assert_eq!(index.offset(line_col, unit), offset);
+                    }
+                }
+            }
+        }
+    }
+
+    impl From<(usize, usize)> for LineCol {
+        fn from((line, col): (usize, usize)) -> Self {
+            Self::new(line, col)
+        }
+    }
+
+    fn test(inp: &str) -> LineIndex {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        LineIndex::new(inp)
+    }
+}
@@ -0,0 +1,1147 @@
+//! A language server for `.yuck` files, speaking LSP over an [`lsp_server::Connection`].
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use lsp_server::{Connection, ErrorCode, ExtractError, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{
+        CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+        DocumentSymbolRequest, GotoDefinition, HoverRequest, References, Rename, Request as _,
+        SemanticTokensFullRequest,
+    },
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall,
+    CallHierarchyServerCapability, DocumentSymbol, DocumentSymbolResponse, GotoDefinitionResponse,
+    Hover, HoverContents, HoverProviderCapability, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, ReferenceParams, RenameParams, SemanticToken, SemanticTokenModifier,
+    SemanticTokenType, SemanticTokens, SemanticTokensFullOptions, SemanticTokensLegend,
+    SemanticTokensOptions, SemanticTokensServerCapabilities, ServerCapabilities, SymbolKind,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+    WorkspaceEdit,
+};
+use serde_json::Value;
+
+use crate::{
+    ast::{def_var::DefVarValue, def_widget::DefWidgetParam, lookup::NodeRef},
+    spanned::Spanned,
+    workspace::{
+        call_hierarchy, references,
+        rename::{self, RenameError},
+        semantic_tokens::{self, TokenKind},
+        symbols::{self, Definition, DefinitionKind, SymbolRef},
+        Severity, Workspace,
+    },
+};
+
+mod convert;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// The token types of the semantic tokens legend, indexed by [`token_type`].
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::MACRO,
+];
+
+/// Built-in widgets are classes with the `defaultLibrary` modifier.
+const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[SemanticTokenModifier::DEFAULT_LIBRARY];
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: SemanticTokensLegend {
+                    token_types: TOKEN_TYPES.to_vec(),
+                    token_modifiers: TOKEN_MODIFIERS.to_vec(),
+                },
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..Default::default()
+            },
+        )),
+        ..Default::default()
+    }
+}
+
+/// Performs the initialization handshake and serves requests until the client shuts the server
+/// down.
+pub fn run(connection: &Connection) -> Result<()> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    let mut server = Server {
+        connection,
+        workspace: Workspace::new(),
+    };
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// An error response to a request.
+struct RequestError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl RequestError {
+    fn new(code: ErrorCode, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<ExtractError<Request>> for RequestError {
+    fn from(err: ExtractError<Request>) -> Self {
+        match err {
+            ExtractError::JsonError { method, error } => Self::new(
+                ErrorCode::InvalidParams,
+                format!("invalid params for {}: {}", method, error),
+            ),
+            ExtractError::MethodMismatch(request) => Self::new(
+                ErrorCode::MethodNotFound,
+                format!("unsupported request: {}", request.method),
+            ),
+        }
+    }
+}
+
+impl From<serde_json::Error> for RequestError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::new(ErrorCode::InternalError, err)
+    }
+}
+
+struct Server<'conn> {
+    connection: &'conn Connection,
+    workspace: Workspace,
+}
+
+impl Server<'_> {
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        let id = request.id.clone();
+        let response = match self.dispatch(request) {
+            Ok(result) => Response::new_ok(id, result),
+            Err(err) => Response::new_err(id, err.code as i32, err.message),
+        };
+
+        self.respond(response)
+    }
+
+    /// Answers `request`. Errors are sent to the client rather than stopping the server.
+    fn dispatch(&mut self, request: Request) -> std::result::Result<Value, RequestError> {
+        let result = match request.method.as_str() {
+            DocumentSymbolRequest::METHOD => {
+                let params = extract::<DocumentSymbolRequest>(request)?;
+                serde_json::to_value(self.document_symbols(&params.text_document.uri))?
+            }
+            GotoDefinition::METHOD => {
+                let params = extract::<GotoDefinition>(request)?;
+                serde_json::to_value(self.definition(&params.text_document_position_params))?
+            }
+            HoverRequest::METHOD => {
+                let params = extract::<HoverRequest>(request)?;
+                serde_json::to_value(self.hover(&params.text_document_position_params))?
+            }
+            References::METHOD => {
+                let params = extract::<References>(request)?;
+                serde_json::to_value(self.references(&params))?
+            }
+            CallHierarchyPrepare::METHOD => {
+                let params = extract::<CallHierarchyPrepare>(request)?;
+                serde_json::to_value(
+                    self.symbol_at(&params.text_document_position_params)
+                        .and_then(|(_, definition)| self.call_hierarchy_item(&definition))
+                        .map(|item| vec![item]),
+                )?
+            }
+            CallHierarchyIncomingCalls::METHOD => {
+                let params = extract::<CallHierarchyIncomingCalls>(request)?;
+                serde_json::to_value(self.incoming_calls(&params.item))?
+            }
+            CallHierarchyOutgoingCalls::METHOD => {
+                let params = extract::<CallHierarchyOutgoingCalls>(request)?;
+                serde_json::to_value(self.outgoing_calls(&params.item))?
+            }
+            SemanticTokensFullRequest::METHOD => {
+                let params = extract::<SemanticTokensFullRequest>(request)?;
+                serde_json::to_value(self.semantic_tokens(&params.text_document.uri))?
+            }
+            Rename::METHOD => {
+                let params = extract::<Rename>(request)?;
+                let edit = self
+                    .rename(&params)
+                    .map_err(|err| RequestError::new(ErrorCode::RequestFailed, err))?;
+                serde_json::to_value(edit)?
+            }
+            method => {
+                return Err(RequestError::new(
+                    ErrorCode::MethodNotFound,
+                    format!("unsupported request: {}", method),
+                ));
+            }
+        };
+
+        Ok(result)
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = extract_notification::<DidOpenTextDocument>(notification) else {
+                    return Ok(());
+                };
+                self.update(&params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = extract_notification::<DidChangeTextDocument>(notification)
+                else {
+                    return Ok(());
+                };
+                let Some(path) = file_path(&params.text_document.uri) else {
+                    return Ok(());
+                };
+                for change in params.content_changes {
+                    // Changes without a range replace the whole document.
+                    let Some(range) = change.range else {
+                        self.workspace.insert(path.clone(), change.text);
+                        continue;
+                    };
+                    let Some(file) = self.workspace.get(&path) else {
+                        continue;
+                    };
+                    let start = convert::offset(&file.line_index, range.start);
+                    let end = convert::offset(&file.line_index, range.end);
+                    if start > end
+                        || !file.source.is_char_boundary(start)
+                        || !file.source.is_char_boundary(end)
+                    {
+                        log::warn!("ignoring a change of the invalid range {:?}", range);
+                        continue;
+                    }
+                    self.workspace.edit(&path, start..end, &change.text);
+                }
+                self.publish_diagnostics(&params.text_document.uri, &path)
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = extract_notification::<DidCloseTextDocument>(notification)
+                else {
+                    return Ok(());
+                };
+                let Some(path) = file_path(&params.text_document.uri) else {
+                    return Ok(());
+                };
+                // Other files may still depend on the definitions of the closed file, so it is
+                // replaced with its saved version instead of being removed.
+                if let Ok(source) = std::fs::read_to_string(&path) {
+                    self.workspace.insert(path, source);
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn update(&mut self, uri: &Url, source: String) -> Result<()> {
+        let Some(path) = file_path(uri) else {
+            return Ok(());
+        };
+
+        self.workspace.insert(path.clone(), source);
+        self.publish_diagnostics(uri, &path)
+    }
+
+    fn publish_diagnostics(&mut self, uri: &Url, path: &Path) -> Result<()> {
+        self.workspace.load_includes(path);
+
+        let Some(file) = self.workspace.get(path) else {
+            return Ok(());
+        };
+        let diagnostics = file
+            .diagnostics
+            .iter()
+            .map(|diagnostic| lsp_types::Diagnostic {
+                range: convert::range(&file.line_index, diagnostic.span.clone()),
+                severity: Some(match diagnostic.severity {
+                    Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
+                    Severity::Warning => lsp_types::DiagnosticSeverity::WARNING,
+                }),
+                source: Some("yuck".to_owned()),
+                message: diagnostic.message.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(
+            uri.clone(),
+            diagnostics,
+            None,
+        ))
+    }
+
+    fn document_symbols(&self, uri: &Url) -> Option<DocumentSymbolResponse> {
+        let path = file_path(uri)?;
+        let file = self.workspace.get(&path)?;
+
+        let document_symbols = symbols::definitions(&self.workspace, &path)
+            .into_iter()
+            .filter(|definition| definition.path == path)
+            .map(|definition| {
+                #[allow(deprecated)]
+                DocumentSymbol {
+                    name: definition.name.1 .0.to_string(),
+                    detail: Some(signature(&self.workspace, &definition)),
+                    kind: symbol_kind(definition.kind),
+                    tags: None,
+                    deprecated: None,
+                    range: convert::range(&file.line_index, definition.node.0..definition.node.2),
+                    selection_range: convert::range(
+                        &file.line_index,
+                        definition.name.0..definition.name.2,
+                    ),
+                    children: None,
+                }
+            })
+            .collect();
+
+        Some(DocumentSymbolResponse::Nested(document_symbols))
+    }
+
+    fn definition(&self, params: &TextDocumentPositionParams) -> Option<GotoDefinitionResponse> {
+        let (_, definition) = self.symbol_at(params)?;
+
+        self.location(definition.path, definition.name.0..definition.name.2)
+            .map(GotoDefinitionResponse::Scalar)
+    }
+
+    fn hover(&self, params: &TextDocumentPositionParams) -> Option<Hover> {
+        let (symbol_ref, definition) = self.symbol_at(params)?;
+        let path = file_path(&params.text_document.uri)?;
+        let line_index = &self.workspace.get(&path)?.line_index;
+        let Spanned(l, _, r) = symbol_ref.name();
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```yuck\n{}\n```", signature(&self.workspace, &definition)),
+            }),
+            range: Some(convert::range(line_index, l..r)),
+        })
+    }
+
+    fn references(&self, params: &ReferenceParams) -> Option<Vec<lsp_types::Location>> {
+        let position = &params.text_document_position;
+        let path = file_path(&position.text_document.uri)?;
+        let offset = convert::offset(&self.workspace.get(&path)?.line_index, position.position);
+
+        let references = references::references_at(
+            &self.workspace,
+            &path,
+            offset,
+            params.context.include_declaration,
+        );
+        references
+            .into_iter()
+            .map(|reference| self.location(reference.path, reference.span))
+            .collect()
+    }
+
+    fn semantic_tokens(&self, uri: &Url) -> Option<SemanticTokens> {
+        let path = file_path(uri)?;
+        let file = self.workspace.get(&path)?;
+
+        let mut data = Vec::new();
+        let mut previous = Position::new(0, 0);
+        for token in semantic_tokens::semantic_tokens(&self.workspace, &path) {
+            let Some((token_type, modifiers)) = token_type(token.kind) else {
+                continue;
+            };
+
+            // Clients may not support tokens spanning lines, e.g. of multiline strings.
+            let mut start = token.span.start;
+            for line in file.source[token.span].split_inclusive('\n') {
+                let line = line.trim_end_matches('\n');
+                let position = convert::position(&file.line_index, start);
+                let length: usize = line.chars().map(char::len_utf16).sum();
+                start += line.len() + 1;
+                if length == 0 {
+                    continue;
+                }
+
+                let delta_line = position.line - previous.line;
+                data.push(SemanticToken {
+                    delta_line,
+                    delta_start: if delta_line == 0 {
+                        position.character - previous.character
+                    } else {
+                        position.character
+                    },
+                    length: length as u32,
+                    token_type,
+                    token_modifiers_bitset: modifiers,
+                });
+                previous = position;
+            }
+        }
+
+        Some(SemanticTokens {
+            result_id: None,
+            data,
+        })
+    }
+
+    fn call_hierarchy_item(&self, definition: &Definition) -> Option<CallHierarchyItem> {
+        if !matches!(
+            definition.kind,
+            DefinitionKind::DefWidget | DefinitionKind::DefWindow
+        ) {
+            return None;
+        }
+        let line_index = &self.workspace.get(definition.path)?.line_index;
+
+        Some(CallHierarchyItem {
+            name: definition.name.1 .0.to_string(),
+            kind: symbol_kind(definition.kind),
+            tags: None,
+            detail: Some(signature(&self.workspace, definition)),
+            uri: Url::from_file_path(definition.path).ok()?,
+            range: convert::range(line_index, definition.node.0..definition.node.2),
+            selection_range: convert::range(line_index, definition.name.0..definition.name.2),
+            data: None,
+        })
+    }
+
+    /// Returns the definition a call hierarchy item was created for.
+    fn call_hierarchy_definition(&self, item: &CallHierarchyItem) -> Option<Definition<'_>> {
+        self.symbol_at(&TextDocumentPositionParams::new(
+            lsp_types::TextDocumentIdentifier::new(item.uri.clone()),
+            item.selection_range.start,
+        ))
+        .map(|(_, definition)| definition)
+    }
+
+    fn incoming_calls(&self, item: &CallHierarchyItem) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let definition = self.call_hierarchy_definition(item)?;
+
+        call_hierarchy::incoming_calls(&self.workspace, &definition)
+            .into_iter()
+            .map(|call| {
+                let line_index = &self.workspace.get(call.definition.path)?.line_index;
+                Some(CallHierarchyIncomingCall {
+                    from: self.call_hierarchy_item(&call.definition)?,
+                    from_ranges: call
+                        .spans
+                        .into_iter()
+                        .map(|span| convert::range(line_index, span))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    fn outgoing_calls(&self, item: &CallHierarchyItem) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let definition = self.call_hierarchy_definition(item)?;
+        let line_index = &self.workspace.get(definition.path)?.line_index;
+
+        call_hierarchy::outgoing_calls(&self.workspace, &definition)
+            .into_iter()
+            .map(|call| {
+                Some(CallHierarchyOutgoingCall {
+                    to: self.call_hierarchy_item(&call.definition)?,
+                    from_ranges: call
+                        .spans
+                        .into_iter()
+                        .map(|span| convert::range(line_index, span))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    fn location(&self, path: &Path, span: std::ops::Range<usize>) -> Option<lsp_types::Location> {
+        let line_index = &self.workspace.get(path)?.line_index;
+
+        Some(lsp_types::Location::new(
+            Url::from_file_path(path).ok()?,
+            convert::range(line_index, span),
+        ))
+    }
+
+    fn rename(&self, params: &RenameParams) -> std::result::Result<WorkspaceEdit, RenameError> {
+        let position = &params.text_document_position;
+        let Some((path, file)) = file_path(&position.text_document.uri)
+            .and_then(|path| self.workspace.get(&path).map(|file| (path, file)))
+        else {
+            return Err(RenameError::NoDefinition);
+        };
+        let offset = convert::offset(&file.line_index, position.position);
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for edit in rename::rename(&self.workspace, &path, offset, &params.new_name)? {
+            let (Ok(uri), Some(file)) = (
+                Url::from_file_path(&edit.path),
+                self.workspace.get(&edit.path),
+            ) else {
+                continue;
+            };
+            changes.entry(uri).or_default().push(TextEdit::new(
+                convert::range(&file.line_index, edit.span),
+                edit.new_text,
+            ));
+        }
+
+        Ok(WorkspaceEdit::new(changes))
+    }
+
+    /// Returns the symbol at the position along with its definition.
+    fn symbol_at(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<(SymbolRef<'_>, Definition<'_>)> {
+        let path = file_path(&params.text_document.uri)?;
+        let line_index = &self.workspace.get(&path)?.line_index;
+        let offset = convert::offset(line_index, params.position);
+
+        let symbol_ref = symbols::symbol_at(&self.workspace, &path, offset)?;
+        let definition = symbols::definition_of(&self.workspace, &path, &symbol_ref)?;
+
+        Some((symbol_ref, definition))
+    }
+
+    fn respond(&self, response: Response) -> Result<()> {
+        self.connection.sender.send(Message::Response(response))?;
+        Ok(())
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) -> Result<()> {
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                N::METHOD.to_owned(),
+                params,
+            )))?;
+        Ok(())
+    }
+}
+
+fn symbol_kind(kind: DefinitionKind) -> SymbolKind {
+    match kind {
+        DefinitionKind::DefWidget => SymbolKind::FUNCTION,
+        DefinitionKind::DefWindow => SymbolKind::OBJECT,
+        _ => SymbolKind::VARIABLE,
+    }
+}
+
+/// Returns the index into [`TOKEN_TYPES`] and the modifier bitset of a token, `None` for tokens
+/// that are not highlighted.
+fn token_type(kind: TokenKind) -> Option<(u32, u32)> {
+    let (token_type, modifiers) = match kind {
+        TokenKind::Keyword => (SemanticTokenType::KEYWORD, 0),
+        TokenKind::Widget => (SemanticTokenType::CLASS, 0),
+        TokenKind::BuiltinWidget => (SemanticTokenType::CLASS, 1),
+        TokenKind::Property => (SemanticTokenType::PROPERTY, 0),
+        TokenKind::Variable => (SemanticTokenType::VARIABLE, 0),
+        TokenKind::Function => (SemanticTokenType::FUNCTION, 0),
+        TokenKind::String => (SemanticTokenType::STRING, 0),
+        TokenKind::Number => (SemanticTokenType::NUMBER, 0),
+        TokenKind::Operator => (SemanticTokenType::OPERATOR, 0),
+        TokenKind::Comment => (SemanticTokenType::COMMENT, 0),
+        TokenKind::Interpolation => (SemanticTokenType::MACRO, 0),
+        TokenKind::Punctuation => return None,
+    };
+    let idx = TOKEN_TYPES.iter().position(|other| *other == token_type)?;
+
+    Some((idx as u32, modifiers))
+}
+
+/// Returns a one line summary of `definition`, e.g. `(defwidget bar [?icon])`.
+fn signature(workspace: &Workspace, definition: &Definition) -> String {
+    let name = &definition.name.1 .0;
+    let params = |params: &[Spanned<DefWidgetParam>]| {
+        params
+            .iter()
+            .map(|param| {
+                let optional = if param.1.is_optional { "?" } else { "" };
+                format!("{}{}", optional, param.1.name.1 .0)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    match definition.node.1 {
+        NodeRef::DefListen(_) => format!("(deflisten {})", name),
+        NodeRef::DefPoll(_) => format!("(defpoll {})", name),
+        NodeRef::DefVar(def_var) => {
+            // Shows the value as written, e.g. with the quotes and escapes of string literals.
+            let DefVarValue::Atom(Spanned(l, _, r)) = &def_var.value;
+            let value = workspace
+                .get(definition.path)
+                .and_then(|file| file.source.get(*l..*r))
+                .unwrap_or_default();
+            format!("(defvar {} {})", name, value)
+        }
+        NodeRef::DefWidget(def_widget) => {
+            format!("(defwidget {} [{}])", name, params(&def_widget.params))
+        }
+        NodeRef::DefWindow(def_window) if def_window.params.is_empty() => {
+            format!("(defwindow {})", name)
+        }
+        NodeRef::DefWindow(def_window) => {
+            format!("(defwindow {} [{}])", name, params(&def_window.params))
+        }
+        NodeRef::DefWidgetParam(param) if param.is_optional => {
+            format!("?{} ; optional parameter", name)
+        }
+        NodeRef::DefWidgetParam(_) => format!("{} ; parameter", name),
+        _ => format!("{} ; for loop binding", name),
+    }
+}
+
+fn file_path(uri: &Url) -> Option<PathBuf> {
+    uri.to_file_path().ok()
+}
+
+fn extract<P: lsp_types::request::Request>(
+    request: Request,
+) -> std::result::Result<P::Params, ExtractError<Request>> {
+    request.extract(P::METHOD).map(|(_, params)| params)
+}
+
+/// Notifications cannot be answered with an error, so malformed ones are logged and dropped.
+fn extract_notification<N: lsp_types::notification::Notification>(
+    notification: Notification,
+) -> Option<N::Params> {
+    match notification.extract(N::METHOD) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            log::warn!("dropping a malformed notification: {}", err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use lsp_server::RequestId;
+    use lsp_types::{
+        notification::{Exit, Initialized},
+        request::{Initialize, Shutdown},
+        CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCallsParams,
+        CallHierarchyPrepareParams, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+        DocumentSymbolParams, GotoDefinitionParams, HoverParams, InitializeParams,
+        InitializedParams, Location, Position, Range, ReferenceContext, SemanticTokensParams,
+        TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        VersionedTextDocumentIdentifier,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct Client {
+        connection: Connection,
+        server: Option<thread::JoinHandle<()>>,
+        next_id: i32,
+    }
+
+    impl Client {
+        fn start() -> Self {
+            let (client, server) = Connection::memory();
+            let server = thread::spawn(move || run(&server).unwrap());
+            let mut client = Self {
+                connection: client,
+                server: Some(server),
+                next_id: 0,
+            };
+
+            client.request::<Initialize>(InitializeParams::default());
+            client.notify::<Initialized>(InitializedParams {});
+            client
+        }
+
+        fn request<R: lsp_types::request::Request>(&mut self, params: R::Params) -> Value {
+            let response = self.response::<R>(params);
+            response.result.unwrap()
+        }
+
+        fn response<R: lsp_types::request::Request>(&mut self, params: R::Params) -> Response {
+            self.send(R::METHOD, serde_json::to_value(params).unwrap())
+        }
+
+        /// Sends a request with arbitrary `params`, which need not match the method.
+        fn send(&mut self, method: &str, params: Value) -> Response {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            self.connection
+                .sender
+                .send(Request::new(id.clone(), method.to_owned(), params).into())
+                .unwrap();
+
+            match self.receive() {
+                Message::Response(response) => {
+                    assert_eq!(response.id, id);
+                    response
+                }
+                message => panic!("expected a response, got {:?}", message),
+            }
+        }
+
+        fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) {
+            self.connection
+                .sender
+                .send(Notification::new(N::METHOD.to_owned(), params).into())
+                .unwrap();
+        }
+
+        /// Sends a notification with arbitrary `params`, which need not match the method.
+        fn send_notification(&self, method: &str, params: Value) {
+            self.connection
+                .sender
+                .send(Notification::new(method.to_owned(), params).into())
+                .unwrap();
+        }
+
+        fn receive(&self) -> Message {
+            self.connection
+                .receiver
+                .recv_timeout(Duration::from_secs(5))
+                .unwrap()
+        }
+
+        fn open(&self, uri: &Url, text: &str) -> PublishDiagnosticsParams {
+            self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "yuck".to_owned(),
+                    0,
+                    text.to_owned(),
+                ),
+            });
+
+            match self.receive() {
+                Message::Notification(notification) => {
+                    notification.extract(PublishDiagnostics::METHOD).unwrap()
+                }
+                message => panic!("expected diagnostics, got {:?}", message),
+            }
+        }
+    }
+
+    impl Drop for Client {
+        fn drop(&mut self) {
+            self.request::<Shutdown>(());
+            self.notify::<Exit>(());
+            self.server.take().unwrap().join().unwrap();
+        }
+    }
+
+    fn uri(path: &str) -> Url {
+        Url::from_file_path(path).unwrap()
+    }
+
+    fn position_params(uri: &Url, line: u32, character: u32) -> TextDocumentPositionParams {
+        TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(uri.clone()),
+            Position::new(line, character),
+        )
+    }
+
+    #[test]
+    fn diagnostics() {
+        let client = Client::start();
+        let uri = uri("/main.yuck");
+
+        let diagnostics = client.open(&uri, "(defvar)");
+
+        assert_eq!(diagnostics.uri, uri);
+        assert_eq!(diagnostics.diagnostics.len(), 1);
+        assert_eq!(diagnostics.diagnostics[0].message, "expected a name");
+        assert_eq!(
+            diagnostics.diagnostics[0].range,
+            Range::new(Position::new(0, 0), Position::new(0, 8))
+        );
+    }
+
+    #[test]
+    fn incremental_changes() {
+        let client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(&uri, "(defvar foo 1)\n(defvar)");
+
+        client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 1),
+            content_changes: vec![
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 8), Position::new(0, 11))),
+                    range_length: None,
+                    text: "bar".to_owned(),
+                },
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 0), Position::new(0, 0))),
+                    text: "; vars\n".to_owned(),
+                    range_length: None,
+                },
+            ],
+        });
+        let Message::Notification(notification) = client.receive() else {
+            panic!("expected diagnostics");
+        };
+        let diagnostics: PublishDiagnosticsParams =
+            notification.extract(PublishDiagnostics::METHOD).unwrap();
+
+        assert_eq!(diagnostics.diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics.diagnostics[0].range,
+            Range::new(Position::new(2, 0), Position::new(2, 8))
+        );
+    }
+
+    #[test]
+    fn invalid_notifications() {
+        let mut client = Client::start();
+        let uri = uri("/main.yuck");
+        client.send_notification(
+            DidOpenTextDocument::METHOD,
+            serde_json::json!({ "textDocument": 1 }),
+        );
+        client.open(&uri, "(defvar volume 50)");
+        client.send_notification(
+            DidChangeTextDocument::METHOD,
+            serde_json::json!({ "textDocument": { "uri": uri }, "contentChanges": "volume" }),
+        );
+
+        // The server keeps serving requests after an invalid notification.
+        let response = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        assert_ne!(response, Value::Null);
+    }
+
+    #[test]
+    fn reversed_range() {
+        let client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(&uri, "(defvar foo 1)\n(defvar)");
+
+        client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 1),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(1, 0), Position::new(0, 8))),
+                range_length: None,
+                text: "bar".to_owned(),
+            }],
+        });
+        let Message::Notification(notification) = client.receive() else {
+            panic!("expected diagnostics");
+        };
+        let diagnostics: PublishDiagnosticsParams =
+            notification.extract(PublishDiagnostics::METHOD).unwrap();
+
+        // The change is ignored, so the document still has its error in the second line.
+        assert_eq!(diagnostics.diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics.diagnostics[0].range,
+            Range::new(Position::new(1, 0), Position::new(1, 8))
+        );
+    }
+
+    #[test]
+    fn document_symbols() {
+        let mut client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(&uri, "(defvar foo 1)\n(defwidget bar [?icon] (box))");
+
+        let response = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        let Ok(DocumentSymbolResponse::Nested(symbols)) = serde_json::from_value(response) else {
+            panic!("expected nested document symbols");
+        };
+
+        let symbols: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind, symbol.detail.as_deref()))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                ("foo", SymbolKind::VARIABLE, Some("(defvar foo 1)")),
+                ("bar", SymbolKind::FUNCTION, Some("(defwidget bar [?icon])")),
+            ]
+        );
+    }
+
+    #[test]
+    fn definition_across_files() {
+        let mut client = Client::start();
+        let vars = uri("/vars.yuck");
+        let main = uri("/main.yuck");
+        client.open(&vars, "(defvar volume 50)");
+        client.open(
+            &main,
+            "(include \"vars.yuck\")\n(defwidget bar [] (label :text volume))",
+        );
+
+        let response = client.request::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: position_params(&main, 1, 33),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+        assert_eq!(
+            serde_json::from_value::<GotoDefinitionResponse>(response).unwrap(),
+            GotoDefinitionResponse::Scalar(Location::new(
+                vars,
+                Range::new(Position::new(0, 8), Position::new(0, 14))
+            ))
+        );
+    }
+
+    #[test]
+    fn rename() {
+        let mut client = Client::start();
+        let vars = uri("/vars.yuck");
+        let main = uri("/main.yuck");
+        client.open(&vars, "(defvar volume 50)\n(defvar muted false)");
+        client.open(
+            &main,
+            "(include \"vars.yuck\")\n(defwidget bar [] (label :text volume))",
+        );
+
+        let params = |new_name: &str| RenameParams {
+            text_document_position: position_params(&main, 1, 33),
+            new_name: new_name.to_owned(),
+            work_done_progress_params: Default::default(),
+        };
+
+        let response = client.request::<Rename>(params("level"));
+        let changes = serde_json::from_value::<WorkspaceEdit>(response)
+            .unwrap()
+            .changes
+            .unwrap();
+        assert_eq!(
+            changes[&vars],
+            vec![TextEdit::new(
+                Range::new(Position::new(0, 8), Position::new(0, 14)),
+                "level".to_owned()
+            )]
+        );
+        assert_eq!(
+            changes[&main],
+            vec![TextEdit::new(
+                Range::new(Position::new(1, 31), Position::new(1, 37)),
+                "level".to_owned()
+            )]
+        );
+
+        let response = client.response::<Rename>(params("muted"));
+        assert_eq!(
+            response.error.unwrap().message,
+            "`muted` is already defined"
+        );
+    }
+
+    #[test]
+    fn references() {
+        let mut client = Client::start();
+        let vars = uri("/vars.yuck");
+        let main = uri("/main.yuck");
+        client.open(&vars, "(defvar volume 50)");
+        client.open(
+            &main,
+            "(include \"vars.yuck\")\n(defwidget bar [] (label :text volume))",
+        );
+
+        let response = client.request::<References>(ReferenceParams {
+            text_document_position: position_params(&vars, 0, 9),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+        assert_eq!(
+            serde_json::from_value::<Vec<Location>>(response).unwrap(),
+            vec![Location::new(
+                main,
+                Range::new(Position::new(1, 31), Position::new(1, 37))
+            )]
+        );
+    }
+
+    #[test]
+    fn call_hierarchy() {
+        let mut client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(
+            &uri,
+            "(defwidget clock [] (label :text \"12:00\"))\n(defwindow main (box (clock)))",
+        );
+
+        let response = client.request::<CallHierarchyPrepare>(CallHierarchyPrepareParams {
+            text_document_position_params: position_params(&uri, 0, 12),
+            work_done_progress_params: Default::default(),
+        });
+        let items: Vec<CallHierarchyItem> = serde_json::from_value(response).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "clock");
+
+        let response =
+            client.request::<CallHierarchyIncomingCalls>(CallHierarchyIncomingCallsParams {
+                item: items[0].clone(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            });
+        let calls: Vec<CallHierarchyIncomingCall> = serde_json::from_value(response).unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].from.name, "main");
+        assert_eq!(calls[0].from.kind, SymbolKind::OBJECT);
+        assert_eq!(
+            calls[0].from_ranges,
+            vec![Range::new(Position::new(1, 22), Position::new(1, 27))]
+        );
+
+        let response =
+            client.request::<CallHierarchyOutgoingCalls>(CallHierarchyOutgoingCallsParams {
+                item: calls[0].from.clone(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            });
+        let calls: Vec<CallHierarchyOutgoingCall> = serde_json::from_value(response).unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].to, items[0]);
+    }
+
+    #[test]
+    fn semantic_tokens() {
+        let mut client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(&uri, "(defwindow w (box))\n(defvar s \"a\n b\")");
+
+        let response = client.request::<SemanticTokensFullRequest>(SemanticTokensParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        let tokens: SemanticTokens = serde_json::from_value(response).unwrap();
+
+        let token =
+            |delta_line, delta_start, length, token_type, token_modifiers_bitset| SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type,
+                token_modifiers_bitset,
+            };
+        assert_eq!(
+            tokens.data,
+            vec![
+                token(0, 1, 9, 0, 0),
+                token(0, 10, 1, 1, 0),
+                token(0, 3, 3, 1, 1),
+                token(1, 1, 6, 0, 0),
+                token(0, 7, 1, 3, 0),
+                token(0, 2, 2, 5, 0),
+                token(1, 0, 3, 5, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_params() {
+        let mut client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(&uri, "(defvar volume 50)");
+
+        let response = client.send(HoverRequest::METHOD, serde_json::json!({ "position": 3 }));
+        assert_eq!(response.result, None);
+        assert_eq!(
+            response.error.unwrap().code,
+            ErrorCode::InvalidParams as i32
+        );
+
+        // The server keeps serving requests after an invalid one.
+        let response = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        assert_ne!(response, Value::Null);
+    }
+
+    #[test]
+    fn hover() {
+        let mut client = Client::start();
+        let uri = uri("/main.yuck");
+        client.open(
+            &uri,
+            "(defwidget bar [?icon] (label :text icon))\n(defwindow main (bar))",
+        );
+
+        let hover = |client: &mut Client, line, character| {
+            let response = client.request::<HoverRequest>(HoverParams {
+                text_document_position_params: position_params(&uri, line, character),
+                work_done_progress_params: Default::default(),
+            });
+            match serde_json::from_value::<Option<Hover>>(response).unwrap() {
+                Some(Hover {
+                    contents: HoverContents::Markup(markup),
+                    ..
+                }) => Some(markup.value),
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            hover(&mut client, 1, 17).as_deref(),
+            Some("```yuck\n(defwidget bar [?icon])\n```")
+        );
+        assert_eq!(
+            hover(&mut client, 0, 37).as_deref(),
+            Some("```yuck\n?icon ; optional parameter\n```")
+        );
+        assert_eq!(hover(&mut client, 0, 25), None);
+    }
+}
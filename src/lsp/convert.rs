@@ -0,0 +1,58 @@
+//! Conversions between byte offsets and LSP positions, which count UTF-16 code units.
+
+use std::ops::Range;
+
+use lsp_types::Position;
+
+use crate::line_index::{ColumnUnit, LineCol, LineIndex};
+
+pub fn position(line_index: &LineIndex, offset: usize) -> Position {
+    let LineCol { line, col } = line_index.line_col(offset, ColumnUnit::Utf16);
+    Position::new(line as u32, col as u32)
+}
+
+/// Positions past the end of a line are clamped to its end.
+pub fn offset(line_index: &LineIndex, position: Position) -> usize {
+    line_index.offset(
+        LineCol::new(position.line as usize, position.character as usize),
+        ColumnUnit::Utf16,
+    )
+}
+
+pub fn range(line_index: &LineIndex, span: Range<usize>) -> lsp_types::Range {
+    lsp_types::Range::new(
+        position(line_index, span.start),
+        position(line_index, span.end),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const SOURCE: &str = "(defvar a 1)\n(defvar 𝄞 \"ä\")\n";
+
+    #[test]
+    fn round_trip() {
+        for (byte_offset, expected) in [
+            (0, Position::new(0, 0)),
+            (12, Position::new(0, 12)),
+            (13, Position::new(1, 0)),
+            (21, Position::new(1, 8)),
+            (26, Position::new(1, 11)),
+            (SOURCE.len(), Position::new(2, 0)),
+        ] {
+            assert_eq!(position(&LineIndex::new(SOURCE), byte_offset), expected);
+            assert_eq!(offset(&LineIndex::new(SOURCE), expected), byte_offset);
+        }
+    }
+
+    #[test]
+    fn clamps() {
+        let line_index = LineIndex::new(SOURCE);
+        assert_eq!(offset(&line_index, Position::new(0, 100)), 12);
+        assert_eq!(offset(&line_index, Position::new(100, 0)), SOURCE.len());
+    }
+}
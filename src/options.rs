@@ -0,0 +1,293 @@
+//! Options of the parser: the Eww version a config is written for and how syntax that version
+//! does not support is reported.
+//!
+//! The grammar always accepts the syntax of [`EwwVersion::LATEST`], the options never change how a
+//! source parses. They are checked in a pass over the parsed AST, which only adds diagnostics, by
+//! [`SourceFile::parse_with_options`](crate::workspace::SourceFile::parse_with_options),
+//! [`Workspace::with_options`](crate::workspace::Workspace::with_options) and
+//! [`parse_top_level_with_options`](crate::parse::parse_top_level_with_options).
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    ast::{
+        def_window::{DefWindow, DefWindowArg},
+        for_loop::ForLoop,
+        top_level_expr::TopLevelExpr,
+        visitor::{self, Visitor},
+    },
+    spanned::Spanned,
+    workspace::Diagnostic,
+};
+
+/// `for` loops in widget trees.
+const FOR_LOOP: EwwVersion = EwwVersion::new(0, 4, 0);
+/// Parameters of windows, `(defwindow bar [monitor] ...)`.
+const WINDOW_PARAMS: EwwVersion = EwwVersion::new(0, 5, 0);
+/// The `:namespace` of windows on Wayland.
+const WINDOW_NAMESPACE: EwwVersion = EwwVersion::new(0, 5, 0);
+/// `:screen` of windows, replaced by `:monitor`.
+const WINDOW_SCREEN_DEPRECATED: EwwVersion = EwwVersion::new(0, 2, 0);
+
+/// A release of Eww.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EwwVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl EwwVersion {
+    /// The latest release whose syntax the parser knows.
+    pub const LATEST: Self = Self::new(0, 6, 0);
+
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl Default for EwwVersion {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+impl fmt::Display for EwwVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("`{0}` is not a version of Eww, expected e.g. `0.4.0`")]
+pub struct InvalidEwwVersion(String);
+
+/// Parses versions like `0.4.0`, `0.4` or `v0.4.0`.
+impl FromStr for EwwVersion {
+    type Err = InvalidEwwVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidEwwVersion(s.to_owned());
+
+        let mut parts = s.strip_prefix('v').unwrap_or(s).split('.');
+        let mut next = |is_required: bool| match parts.next() {
+            Some(part) => part.parse::<u16>().map_err(|_| invalid()),
+            None if is_required => Err(invalid()),
+            None => Ok(0),
+        };
+        let version = Self::new(next(true)?, next(true)?, next(false)?);
+
+        match parts.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(version),
+        }
+    }
+}
+
+/// How syntax that the target version of Eww does not support is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Accepts all syntax the parser knows.
+    Lenient,
+    /// Reports syntax that is newer than the target version or deprecated in it as warnings.
+    #[default]
+    Warn,
+    /// Reports it as errors.
+    Strict,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParserOptions {
+    pub eww_version: EwwVersion,
+    pub strictness: Strictness,
+}
+
+impl ParserOptions {
+    pub fn new(eww_version: EwwVersion, strictness: Strictness) -> Self {
+        Self {
+            eww_version,
+            strictness,
+        }
+    }
+}
+
+/// Returns the diagnostics for the syntax in `ast` that `options` do not allow, ordered by the
+/// top-level forms they are in.
+pub(crate) fn check(ast: &[Spanned<TopLevelExpr<'_>>], options: &ParserOptions) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        options,
+        diagnostics: Vec::new(),
+    };
+    if options.strictness != Strictness::Lenient {
+        for node in ast {
+            checker.visit_top_level_expr(node.as_ref());
+        }
+    }
+
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    options: &'a ParserOptions,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn require(&mut self, span: (usize, usize), syntax: &str, since: EwwVersion) {
+        if self.options.eww_version < since {
+            let message = format!(
+                "Eww {} does not support {}, added in {}",
+                self.options.eww_version, syntax, since
+            );
+            self.report(span, message);
+        }
+    }
+
+    fn deprecated(&mut self, span: (usize, usize), syntax: &str, since: EwwVersion, hint: &str) {
+        if self.options.eww_version >= since {
+            let message = format!("{} is deprecated since Eww {}, {}", syntax, since, hint);
+            self.report(span, message);
+        }
+    }
+
+    fn report(&mut self, (l, r): (usize, usize), message: String) {
+        self.diagnostics.push(match self.options.strictness {
+            Strictness::Strict => Diagnostic::new(l, r - l, message),
+            Strictness::Lenient | Strictness::Warn => Diagnostic::warning(l, r - l, message),
+        });
+    }
+}
+
+impl<'ast> Visitor<'ast> for Checker<'_> {
+    fn visit_for_loop(&mut self, node: Spanned<&'ast ForLoop>) {
+        self.require(
+            (node.1.keyword.0, node.1.keyword.2),
+            "`for` loops",
+            FOR_LOOP,
+        );
+        visitor::walk_for_loop(self, node);
+    }
+
+    fn visit_def_window(&mut self, node: Spanned<&'ast DefWindow>) {
+        if let (Some(first), Some(last)) = (node.1.params.first(), node.1.params.last()) {
+            self.require((first.0, last.2), "window parameters", WINDOW_PARAMS);
+        }
+        visitor::walk_def_window(self, node);
+    }
+
+    fn visit_def_window_arg(&mut self, node: Spanned<&'ast DefWindowArg>) {
+        let span = (node.1.name.0, node.1.name.2);
+        match node.1.name.1 .0.as_ref() {
+            "namespace" => self.require(span, "`:namespace`", WINDOW_NAMESPACE),
+            "screen" => self.deprecated(
+                span,
+                "`:screen`",
+                WINDOW_SCREEN_DEPRECATED,
+                "use `:monitor` instead",
+            ),
+            _ => {}
+        }
+        visitor::walk_def_window_arg(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::workspace::SourceFile;
+
+    const SOURCE: &str = r#"(defwindow bar [monitor] :monitor monitor :namespace "bar"
+  (box (for item in items (label :text item))))
+(defwindow old :screen 0 (box))"#;
+
+    #[test]
+    fn latest() {
+        assert_eq!(
+            test(SOURCE, ParserOptions::default()),
+            vec![Diagnostic::warning(
+                123,
+                6,
+                "`:screen` is deprecated since Eww 0.2.0, use `:monitor` instead"
+            )]
+        );
+    }
+
+    #[test]
+    fn older_version() {
+        let options = ParserOptions::new(EwwVersion::new(0, 3, 0), Strictness::Warn);
+        assert_eq!(
+            test(SOURCE, options),
+            vec![
+                Diagnostic::warning(
+                    16,
+                    7,
+                    "Eww 0.3.0 does not support window parameters, added in 0.5.0"
+                ),
+                Diagnostic::warning(
+                    43,
+                    9,
+                    "Eww 0.3.0 does not support `:namespace`, added in 0.5.0"
+                ),
+                Diagnostic::warning(
+                    67,
+                    3,
+                    "Eww 0.3.0 does not support `for` loops, added in 0.4.0"
+                ),
+                Diagnostic::warning(
+                    123,
+                    6,
+                    "`:screen` is deprecated since Eww 0.2.0, use `:monitor` instead"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn strictness() {
+        let options = ParserOptions::new(EwwVersion::new(0, 4, 0), Strictness::Strict);
+        assert_eq!(
+            test("(defwindow bar [monitor] (box))", options),
+            vec![Diagnostic::new(
+                16,
+                7,
+                "Eww 0.4.0 does not support window parameters, added in 0.5.0"
+            )]
+        );
+
+        let options = ParserOptions::new(EwwVersion::new(0, 1, 0), Strictness::Lenient);
+        assert_eq!(test(SOURCE, options), Vec::new());
+    }
+
+    #[test]
+    fn parse_version() {
+        assert_eq!("0.4.0".parse(), Ok(EwwVersion::new(0, 4, 0)));
+        assert_eq!("v0.5".parse(), Ok(EwwVersion::new(0, 5, 0)));
+        assert_eq!(
+            "0.x".parse::<EwwVersion>(),
+            Err(InvalidEwwVersion("0.x".to_owned()))
+        );
+        assert_eq!(
+            "1".parse::<EwwVersion>(),
+            Err(InvalidEwwVersion("1".to_owned()))
+        );
+        assert_eq!(
+            "0.4.0.1".parse::<EwwVersion>(),
+            Err(InvalidEwwVersion("0.4.0.1".to_owned()))
+        );
+        assert_eq!(EwwVersion::LATEST.to_string(), "0.6.0");
+    }
+
+    fn test(inp: &str, options: ParserOptions) -> Vec<Diagnostic> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        SourceFile::parse_with_options(inp, options).diagnostics
+    }
+}
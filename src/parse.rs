@@ -0,0 +1,408 @@
+//! Entry points parsing a single construct, e.g. a snippet in a tool or a test, or the top-level
+//! forms of a whole file.
+//!
+//! Unlike [`SourceFile::parse`](crate::workspace::SourceFile::parse), which owns its source, these
+//! borrow from the source and parse exactly one construct from its start to its end. Errors are
+//! reported as [`Diagnostics`], the node is `None` if nothing could be recovered.
+//!
+//! Lexing is all or nothing: a lexical error, e.g. an unterminated string, is the only
+//! diagnostic and leaves the node `None`, as the construct is cut off at the error.
+//!
+//! All of them accept the syntax of the latest Eww. Only [`parse_top_level_with_options`] reports
+//! the syntax that [`ParserOptions`] do not allow, see [`options`].
+
+use crate::{
+    ast::{
+        def_listen::DefListen, def_poll::DefPoll, def_var::DefVar, def_widget::DefWidget,
+        def_window::DefWindow, expr::PrimitiveExpr, include::Include, literal::Literal,
+        top_level_expr::TopLevelExpr, widget_call::WidgetCall, ParseError,
+    },
+    grammar,
+    lexer::{Lexer, LexicalError, Mode, Tok},
+    options::{self, ParserOptions},
+    spanned::Spanned,
+    workspace::Diagnostic,
+};
+
+pub type Diagnostics = Vec<Diagnostic>;
+
+type LalrpopError<'inp> = lalrpop_util::ParseError<usize, Tok<'inp>, LexicalError<'inp>>;
+
+/// Parses an expression as written between `{` and `}`, e.g. `volume > 50 ? "loud" : "quiet"`.
+pub fn parse_expr(source: &str) -> (Option<Spanned<PrimitiveExpr<'_>>>, Diagnostics) {
+    parse(source, Mode::Expr, |errors, tokens| {
+        grammar::TernaryOperationParser::new().parse(errors, tokens)
+    })
+}
+
+/// Parses a widget call, e.g. `(label :text "hello")`.
+pub fn parse_widget_call(source: &str) -> (Option<Spanned<WidgetCall<'_>>>, Diagnostics) {
+    parse(source, Mode::SExpr, |errors, tokens| {
+        grammar::WidgetCallParser::new().parse(errors, tokens)
+    })
+}
+
+/// Parses a whole file, e.g. `(defvar volume 50) (defwidget bar [] (label :text volume))`.
+pub fn parse_top_level(source: &str) -> (Vec<Spanned<TopLevelExpr<'_>>>, Diagnostics) {
+    let (nodes, diagnostics) = parse(source, Mode::SExpr, |errors, tokens| {
+        grammar::TopLevelParser::new().parse(errors, tokens)
+    });
+    (nodes.unwrap_or_default(), diagnostics)
+}
+
+/// Parses a whole file like [`parse_top_level`], additionally reporting the syntax that `options`
+/// do not allow.
+pub fn parse_top_level_with_options<'inp>(
+    source: &'inp str,
+    options: &ParserOptions,
+) -> (Vec<Spanned<TopLevelExpr<'inp>>>, Diagnostics) {
+    let (nodes, mut diagnostics) = parse_top_level(source);
+    diagnostics.extend(options::check(&nodes, options));
+    (nodes, diagnostics)
+}
+
+/// Parses a `defwidget`, e.g. `(defwidget bar [icon] (label :text icon))`.
+pub fn parse_def_widget(source: &str) -> (Option<Spanned<DefWidget<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::DefWidgetParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::DefWidget(def_widget) => Some(def_widget),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a `defwindow`, e.g. `(defwindow bar :monitor 0 (bar))`.
+pub fn parse_def_window(source: &str) -> (Option<Spanned<DefWindow<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::DefWindowParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::DefWindow(def_window) => Some(def_window),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a `defvar`, e.g. `(defvar volume 50)`.
+pub fn parse_def_var(source: &str) -> (Option<Spanned<DefVar<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::DefVarParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::DefVar(def_var) => Some(def_var),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a `defpoll`, e.g. `(defpoll time :interval "1s" "date +%H:%M")`.
+pub fn parse_def_poll(source: &str) -> (Option<Spanned<DefPoll<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::DefPollParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::DefPoll(def_poll) => Some(def_poll),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a `deflisten`, e.g. `(deflisten music "playerctl --follow metadata")`.
+pub fn parse_def_listen(source: &str) -> (Option<Spanned<DefListen<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::DefListenParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::DefListen(def_listen) => Some(def_listen),
+            _ => None,
+        },
+    )
+}
+
+/// Parses an `include`, e.g. `(include "bar.yuck")`.
+pub fn parse_include(source: &str) -> (Option<Spanned<Include<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::IncludeParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::Include(include) => Some(include),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a `literal`, e.g. `(literal :content "(box)")`.
+pub fn parse_literal(source: &str) -> (Option<Spanned<Literal<'_>>>, Diagnostics) {
+    parse_form(
+        source,
+        |errors, tokens| grammar::LiteralParser::new().parse(errors, tokens),
+        |node| match node {
+            TopLevelExpr::Literal(literal) => Some(literal),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a top-level form and unwraps the variant returned by `variant`. The node is `None` if
+/// the form was replaced by [`TopLevelExpr::Err`] during error recovery.
+fn parse_form<'inp, T>(
+    source: &'inp str,
+    parse_form: impl FnOnce(
+        &mut Vec<ParseError>,
+        Vec<(usize, Tok<'inp>, usize)>,
+    ) -> Result<Spanned<TopLevelExpr<'inp>>, LalrpopError<'inp>>,
+    variant: impl FnOnce(TopLevelExpr<'inp>) -> Option<T>,
+) -> (Option<Spanned<T>>, Diagnostics) {
+    let (node, diagnostics) = parse(source, Mode::SExpr, parse_form);
+
+    let node = node.and_then(|Spanned(l, node, r)| Some(Spanned(l, variant(node)?, r)));
+    (node, diagnostics)
+}
+
+fn parse<'inp, T>(
+    source: &'inp str,
+    mode: Mode,
+    parse: impl FnOnce(
+        &mut Vec<ParseError>,
+        Vec<(usize, Tok<'inp>, usize)>,
+    ) -> Result<T, LalrpopError<'inp>>,
+) -> (Option<T>, Diagnostics) {
+    let mut tokens = Vec::new();
+    for result in Lexer::with_mode(source, mode) {
+        match result {
+            Ok((_, Tok::Comment(_), _)) => {}
+            Ok(token) => tokens.push(token),
+            Err(err) => {
+                let diagnostic =
+                    Diagnostic::new(err.span().offset(), err.span().len(), err.to_string());
+                return (None, vec![diagnostic]);
+            }
+        }
+    }
+
+    let mut errors = Vec::new();
+    let result = parse(&mut errors, tokens);
+
+    let mut diagnostics: Diagnostics = errors
+        .iter()
+        .map(|err| Diagnostic::new(err.span().offset(), err.span().len(), err.to_string()))
+        .collect();
+    match result {
+        Ok(node) => (Some(node), diagnostics),
+        Err(err) => {
+            diagnostics.push(Diagnostic::from_lalrpop_error(err, source.len()));
+            (None, diagnostics)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ast::{
+        atom::Atom,
+        binary_operation::{BinaryOperation, BinaryOperator},
+        symbol::Symbol,
+        widget_call::WidgetCallArg,
+    };
+    use crate::options::{EwwVersion, Strictness};
+
+    #[test]
+    fn expr() {
+        let (node, diagnostics) = parse_expr("a + 1");
+
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(
+            node,
+            Some(Spanned(
+                0,
+                PrimitiveExpr::BinaryOperation(BinaryOperation::new(
+                    Spanned(0, PrimitiveExpr::Symbol(Symbol::new("a")), 1),
+                    BinaryOperator::Add,
+                    Spanned(4, PrimitiveExpr::Atom(Atom::new_number("1")), 5),
+                )),
+                5
+            ))
+        );
+    }
+
+    #[test]
+    fn decimals_in_expr() {
+        let (node, diagnostics) = parse_expr("x * 0.5");
+
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(
+            node,
+            Some(Spanned(
+                0,
+                PrimitiveExpr::BinaryOperation(BinaryOperation::new(
+                    Spanned(0, PrimitiveExpr::Symbol(Symbol::new("x")), 1),
+                    BinaryOperator::Mul,
+                    Spanned(4, PrimitiveExpr::Atom(Atom::new_number("0.5")), 7),
+                )),
+                7
+            ))
+        );
+    }
+
+    #[test]
+    fn hyphens_in_expr() {
+        let (node, diagnostics) = parse_expr("x-1");
+
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(
+            node,
+            Some(Spanned(
+                0,
+                PrimitiveExpr::BinaryOperation(BinaryOperation::new(
+                    Spanned(0, PrimitiveExpr::Symbol(Symbol::new("x")), 1),
+                    BinaryOperator::Sub,
+                    Spanned(2, PrimitiveExpr::Atom(Atom::new_number("1")), 3),
+                )),
+                3
+            ))
+        );
+    }
+
+    #[test]
+    fn widget_call() {
+        let (node, diagnostics) = parse_widget_call(r#"(label :text "hi") ; a label"#);
+
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(
+            node,
+            Some(Spanned(
+                0,
+                WidgetCall::new(
+                    Spanned(1, Symbol::new("label"), 6),
+                    vec![WidgetCallArg::new(
+                        Spanned(8, Symbol::new("text"), 12),
+                        Spanned(13, Atom::StrLit("hi".into()), 17),
+                    )],
+                    Vec::new(),
+                ),
+                18
+            ))
+        );
+    }
+
+    #[test]
+    fn def_window() {
+        let (node, diagnostics) = parse_def_window("(defwindow bar :monitor 0 (bar))");
+
+        assert_eq!(diagnostics, Vec::new());
+        let node = node.unwrap();
+        assert_eq!((node.0, node.2), (0, 32));
+        assert_eq!(node.1.name, Spanned(11, Symbol::new("bar"), 14));
+    }
+
+    #[test]
+    fn top_level_forms() {
+        let source = r#"(include "vars.yuck")
+(defvar volume 50)
+(defpoll time :interval "1s" "date +%H:%M")
+(deflisten music "playerctl --follow metadata")
+(literal :content "(box)")
+(defwidget bar [icon] (label :text icon))"#;
+
+        let (nodes, diagnostics) = parse_top_level(source);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(nodes.len(), 6);
+
+        let forms = source.lines().collect::<Vec<_>>();
+        let (node, diagnostics) = parse_include(forms[0]);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(node.unwrap().1.path, Spanned(9, "vars.yuck".into(), 20));
+
+        let (node, diagnostics) = parse_def_var(forms[1]);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(node.unwrap().1.name, Spanned(8, Symbol::new("volume"), 14));
+
+        let (node, diagnostics) = parse_def_poll(forms[2]);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(node.unwrap().1.name, Spanned(9, Symbol::new("time"), 13));
+
+        let (node, diagnostics) = parse_def_listen(forms[3]);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(node.unwrap().1.name, Spanned(11, Symbol::new("music"), 16));
+
+        let (node, diagnostics) = parse_literal(forms[4]);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(node.unwrap().1.args.len(), 1);
+
+        let (node, diagnostics) = parse_def_widget(forms[5]);
+        assert_eq!(diagnostics, Vec::new());
+        assert_eq!(node.unwrap().1.name, Spanned(11, Symbol::new("bar"), 14));
+    }
+
+    #[test]
+    fn top_level_with_options() {
+        let source = "(defwindow bar [monitor] (box))";
+
+        let (nodes, diagnostics) = parse_top_level_with_options(source, &ParserOptions::default());
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(diagnostics, Vec::new());
+
+        let options = ParserOptions::new(EwwVersion::new(0, 4, 0), Strictness::Strict);
+        let (nodes, diagnostics) = parse_top_level_with_options(source, &options);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::new(
+                16,
+                7,
+                "Eww 0.4.0 does not support window parameters, added in 0.5.0"
+            )]
+        );
+        assert_eq!(parse_top_level(source).1, Vec::new());
+    }
+
+    #[test]
+    fn recovered_errors() {
+        let (node, diagnostics) = parse_def_window("(defwindow)");
+
+        assert_eq!(node, None);
+        assert_eq!(diagnostics, vec![Diagnostic::new(0, 11, "expected a name")]);
+
+        let (node, diagnostics) = parse_def_var("(defvar volume)");
+        assert_eq!(node, None);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::new(0, 15, "expected a value")]
+        );
+    }
+
+    #[test]
+    fn unrecoverable_errors() {
+        assert_eq!(
+            parse_expr("a +"),
+            (None, vec![Diagnostic::new(3, 0, "unexpected end of file")])
+        );
+        assert_eq!(
+            parse_widget_call("(box) (box)"),
+            (
+                None,
+                vec![Diagnostic::new(
+                    6,
+                    1,
+                    "Token was not expected at this point"
+                )]
+            )
+        );
+        assert_eq!(
+            parse_expr("a + 1🙂"),
+            (None, vec![Diagnostic::new(5, 4, "Invalid symbol")])
+        );
+        assert_eq!(
+            parse_expr(r#""a"#),
+            (
+                None,
+                vec![Diagnostic::new(0, 2, "Unterminated string literal")]
+            )
+        );
+    }
+}
@@ -11,6 +11,10 @@ impl<T> Spanned<T> {
     pub fn map<R>(self, map_fn: impl FnOnce(T) -> R) -> Spanned<R> {
         (self.0, map_fn(self.1), self.2).into()
     }
+
+    pub fn as_ref(&self) -> Spanned<&T> {
+        Spanned(self.0, &self.1, self.2)
+    }
 }
 
 impl<T> From<(usize, T, usize)> for Spanned<T> {
@@ -24,3 +28,37 @@ impl From<(usize, usize)> for Spanned<()> {
         Self(value.0, (), value.1)
     }
 }
+
+/// Serialized as `{"start": 0, "end": 1, "node": ...}`.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Spanned;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Spanned")]
+    struct Repr<T> {
+        start: usize,
+        end: usize,
+        node: T,
+    }
+
+    impl<T: Serialize> Serialize for Spanned<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Repr {
+                start: self.0,
+                end: self.2,
+                node: &self.1,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let Repr { start, end, node } = Repr::deserialize(deserializer)?;
+            Ok(Self(start, node, end))
+        }
+    }
+}
@@ -0,0 +1,749 @@
+//! A set of source files, connected through `include`s, that are analyzed together.
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    ast::{top_level_expr::TopLevelExpr, IntoOwned, ParseError, ShiftSpans},
+    grammar,
+    lexer::{Lexer, Tok},
+    line_index::LineIndex,
+    options::{self, ParserOptions},
+    spanned::Spanned,
+};
+
+pub mod call_hierarchy;
+pub mod completion;
+pub mod lint;
+pub mod references;
+pub mod rename;
+pub mod schema;
+pub mod semantic_tokens;
+pub mod symbols;
+
+/// A parsed source file. The AST owns its strings, so that it can be kept next to the source.
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub source: String,
+    pub ast: Vec<Spanned<TopLevelExpr<'static>>>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub line_index: LineIndex,
+    options: ParserOptions,
+    /// The number of diagnostics in front of the ones of the [`ParserOptions`].
+    syntax_diagnostics: usize,
+    /// Whether parsing needed error recovery across forms, i.e. the forms cannot be reparsed on
+    /// their own.
+    recovered: bool,
+}
+
+impl SourceFile {
+    /// Parses `source`. Parsing stops at the first lexical error, the forms in front of it are
+    /// still recovered. A form that does not parse, even when left open, only affects itself, the
+    /// next `(defvar`, `(defwidget` etc. starts a new one. All errors are reported as diagnostics.
+    pub fn parse(source: impl Into<String>) -> Self {
+        Self::parse_with_options(source, ParserOptions::default())
+    }
+
+    /// Parses `source` like [`SourceFile::parse`], additionally reporting the syntax that
+    /// `options` do not allow.
+    pub fn parse_with_options(source: impl Into<String>, options: ParserOptions) -> Self {
+        let source = source.into();
+        let mut diagnostics = Vec::new();
+        let mut tokens = Vec::new();
+        let mut lexical_error = None;
+        for result in Lexer::new(&source) {
+            match result {
+                Ok((_, Tok::Comment(_), _)) => {}
+                Ok(token) => tokens.push(token),
+                Err(err) => {
+                    lexical_error = Some(err);
+                    break;
+                }
+            }
+        }
+
+        // Every top-level form is parsed on its own, so that a form left open by an error does
+        // not swallow the forms behind it.
+        let mut ast = Vec::new();
+        let mut recovered = lexical_error.is_some();
+        let chunks = top_level_chunks(tokens);
+        let chunk_count = chunks.len();
+        for (idx, chunk) in chunks.into_iter().enumerate() {
+            let mut errors = Vec::new();
+            let result = grammar::TopLevelParser::new().parse(&mut errors, chunk);
+            recovered |= result.is_err() || is_recovery(&errors);
+
+            // Forms left open by a lexical error are reported by the lexical error alone.
+            let is_cut_off = lexical_error.is_some() && idx + 1 == chunk_count;
+            diagnostics.extend(
+                errors
+                    .iter()
+                    .filter(|err| !is_cut_off || !matches!(err, ParseError::UnexpectedEof { .. }))
+                    .map(|err| {
+                        Diagnostic::new(err.span().offset(), err.span().len(), err.to_string())
+                    }),
+            );
+
+            match result {
+                Ok(nodes) => ast.extend(nodes.into_owned()),
+                Err(err) => diagnostics.push(Diagnostic::from_lalrpop_error(err, source.len())),
+            }
+        }
+
+        if let Some(err) = lexical_error {
+            diagnostics.push(Diagnostic::new(
+                err.span().offset(),
+                err.span().len(),
+                err.to_string(),
+            ));
+        }
+
+        let syntax_diagnostics = diagnostics.len();
+        diagnostics.extend(options::check(&ast, &options));
+
+        Self {
+            line_index: LineIndex::new(&source),
+            source,
+            ast,
+            diagnostics,
+            options,
+            syntax_diagnostics,
+            recovered,
+        }
+    }
+
+    /// Replaces `range` of the source with `replacement` and reparses only the top-level forms
+    /// touched by the edit. The forms in front of the edit are kept, the ones behind it are kept
+    /// and moved, as soon as the tokens of the edited region line up with them again. Falls back
+    /// to [`SourceFile::parse_with_options`] whenever error recovery is involved, so that the
+    /// result is always the same as parsing the new source from scratch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or does not lie on `char` boundaries.
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) {
+        let mut source = std::mem::take(&mut self.source);
+        source.replace_range(range.clone(), replacement);
+
+        if self.recovered {
+            *self = Self::parse_with_options(source, self.options);
+            return;
+        }
+
+        let delta = replacement.len() as isize - range.len() as isize;
+        let prefix_len = self.ast.partition_point(|node| node.2 < range.start);
+        let region_start = prefix_len.checked_sub(1).map_or(0, |idx| self.ast[idx].2);
+        let candidates = self.ast.partition_point(|node| node.0 <= range.end);
+
+        // Lex from the end of the last kept form until a token starts where a form behind the
+        // edit started before, from there on the tokens are the same as before the edit.
+        let mut tokens = Vec::new();
+        let mut suffix_start = self.ast.len();
+        let mut candidate = candidates;
+        for result in Lexer::new(&source[region_start..]) {
+            let Ok((l, tok, r)) = result else {
+                *self = Self::parse_with_options(source, self.options);
+                return;
+            };
+            let (l, r) = (l + region_start, r + region_start);
+
+            while candidate < self.ast.len() && moved(self.ast[candidate].0, delta) < l {
+                candidate += 1;
+            }
+            if candidate < self.ast.len() && moved(self.ast[candidate].0, delta) == l {
+                suffix_start = candidate;
+                break;
+            }
+            if !matches!(tok, Tok::Comment(_)) {
+                tokens.push((l, tok, r));
+            }
+        }
+
+        let mut errors = Vec::new();
+        let region = match grammar::TopLevelParser::new().parse(&mut errors, tokens) {
+            Ok(region) if !is_recovery(&errors) => region.into_owned(),
+            _ => {
+                *self = Self::parse_with_options(source, self.options);
+                return;
+            }
+        };
+
+        let suffix_source_start = self.ast.get(suffix_start).map_or(usize::MAX, |node| node.0);
+        let mut suffix = self.ast.split_off(suffix_start);
+        suffix.shift_spans(delta);
+        self.ast.truncate(prefix_len);
+        self.ast.extend(region);
+        self.ast.extend(suffix);
+
+        // Every diagnostic lies within the form it was reported for. The ones of the options are
+        // cheap to recompute for the whole file.
+        self.diagnostics.truncate(self.syntax_diagnostics);
+        let mut suffix_diagnostics = Vec::new();
+        self.diagnostics.retain(|diagnostic| {
+            if diagnostic.span.start >= suffix_source_start {
+                suffix_diagnostics.push(Diagnostic {
+                    span: moved(diagnostic.span.start, delta)..moved(diagnostic.span.end, delta),
+                    severity: diagnostic.severity,
+                    message: diagnostic.message.clone(),
+                });
+            }
+            diagnostic.span.end <= region_start
+        });
+        self.diagnostics.extend(
+            errors
+                .iter()
+                .map(|err| Diagnostic::new(err.span().offset(), err.span().len(), err.to_string())),
+        );
+        self.diagnostics.extend(suffix_diagnostics);
+        self.syntax_diagnostics = self.diagnostics.len();
+        self.diagnostics
+            .extend(options::check(&self.ast, &self.options));
+        self.line_index = LineIndex::new(&source);
+        self.source = source;
+    }
+
+    /// Returns the `include`d paths as written in the source.
+    pub fn include_paths(&self) -> impl Iterator<Item = &Spanned<Cow<'static, str>>> {
+        self.ast.iter().filter_map(|node| match &node.1 {
+            TopLevelExpr::Include(include) => Some(&include.path),
+            _ => None,
+        })
+    }
+}
+
+/// Splits `tokens` in front of every `(` followed by the keyword of a top-level form, which only
+/// ever starts a form at the top level. Returns at least one, possibly empty, chunk.
+fn top_level_chunks(tokens: Vec<(usize, Tok<'_>, usize)>) -> Vec<Vec<(usize, Tok<'_>, usize)>> {
+    let mut chunks = vec![Vec::new()];
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let starts_form = token.1 == Tok::DelimiterLeftParen
+            && tokens
+                .peek()
+                .is_some_and(|next| next.1.is_top_level_keyword());
+        if starts_form && chunks.last().is_some_and(|chunk| !chunk.is_empty()) {
+            chunks.push(Vec::new());
+        }
+        chunks.last_mut().unwrap().push(token);
+    }
+    chunks
+}
+
+/// Whether `errors` contain errors of the error recovery at the top level.
+fn is_recovery(errors: &[ParseError]) -> bool {
+    errors.iter().any(|err| {
+        matches!(
+            err,
+            ParseError::UnexpectedToken { .. } | ParseError::UnexpectedEof { .. }
+        )
+    })
+}
+
+fn moved(offset: usize, delta: isize) -> usize {
+    offset
+        .checked_add_signed(delta)
+        .expect("offset moved in front of the source")
+}
+
+/// An error or warning in a source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub span: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Diagnostic {
+    /// Creates an error.
+    pub fn new(offset: usize, len: usize, message: impl Into<String>) -> Self {
+        Self {
+            span: offset..offset + len,
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(offset: usize, len: usize, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::new(offset, len, message)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub(crate) fn from_lalrpop_error<T, E: ToString>(
+        err: lalrpop_util::ParseError<usize, T, E>,
+        source_len: usize,
+    ) -> Self {
+        match err {
+            lalrpop_util::ParseError::InvalidToken { location } => {
+                Self::new(location, 0, "invalid token")
+            }
+            lalrpop_util::ParseError::UnrecognizedEof { location, .. } => {
+                Self::new(location.min(source_len), 0, "unexpected end of file")
+            }
+            lalrpop_util::ParseError::UnrecognizedToken {
+                token: (l, _, r), ..
+            }
+            | lalrpop_util::ParseError::ExtraToken { token: (l, _, r) } => {
+                Self::new(l, r - l, "Token was not expected at this point")
+            }
+            lalrpop_util::ParseError::User { error } => Self::new(0, 0, error.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Workspace {
+    files: BTreeMap<PathBuf, SourceFile>,
+    options: ParserOptions,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a workspace parsing its files with `options`.
+    pub fn with_options(options: ParserOptions) -> Self {
+        Self {
+            files: BTreeMap::new(),
+            options,
+        }
+    }
+
+    /// Parses `source` and stores it under `path`, replacing the previous version of the file.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) {
+        self.files.insert(
+            path.into(),
+            SourceFile::parse_with_options(source, self.options),
+        );
+    }
+
+    /// Applies an edit to the file at `path`, see [`SourceFile::edit`]. Does nothing if `path` is
+    /// not part of the workspace.
+    pub fn edit(&mut self, path: &Path, range: Range<usize>, replacement: &str) {
+        if let Some(file) = self.files.get_mut(path) {
+            file.edit(range, replacement);
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) -> Option<SourceFile> {
+        self.files.remove(path)
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&SourceFile> {
+        self.files.get(path)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &SourceFile)> {
+        self.files.iter().map(|(path, file)| (path.as_path(), file))
+    }
+
+    /// Reads the files transitively included by `path` from disk, skipping files that are
+    /// already part of the workspace or cannot be read.
+    pub fn load_includes(&mut self, path: &Path) {
+        let mut pending = self.includes(path);
+
+        while let Some(path) = pending.pop() {
+            if self.files.contains_key(&path) {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                log::debug!("could not read included file {:?}", path);
+                continue;
+            };
+
+            self.insert(path.clone(), source);
+            pending.extend(self.includes(&path));
+        }
+    }
+
+    /// Returns the paths included by `path`, resolved relative to its directory.
+    pub fn includes(&self, path: &Path) -> Vec<PathBuf> {
+        let Some(file) = self.files.get(path) else {
+            return Vec::new();
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        file.include_paths()
+            .map(|include| normalize(&dir.join(&*include.1)))
+            .collect()
+    }
+
+    /// Returns the files sharing their definitions with `path`, i.e. every file connected to it
+    /// through `include`s in either direction, including `path` itself.
+    pub fn unit(&self, path: &Path) -> Vec<&Path> {
+        let mut unit: Vec<&Path> = Vec::new();
+        let Some((path, _)) = self.files.get_key_value(path) else {
+            return unit;
+        };
+        let mut pending = vec![path.as_path()];
+
+        while let Some(path) = pending.pop() {
+            if unit.contains(&path) {
+                continue;
+            }
+            unit.push(path);
+
+            for (other, _) in self.files() {
+                let is_connected = self.includes(path).iter().any(|p| p == other)
+                    || self.includes(other).iter().any(|p| p == path);
+                if is_connected && !unit.contains(&other) {
+                    pending.push(other);
+                }
+            }
+        }
+
+        unit.sort();
+        unit
+    }
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// The workspace the tests of the workspace queries run against.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use pretty_assertions::assert_eq;
+
+    use super::Workspace;
+
+    pub const MAIN: &str = r#"(include "widgets.yuck")
+(defvar volume 50)
+(defvar muted false)
+(defwindow main [screen]
+  (bar :icon volume :screen screen))"#;
+
+    pub const WIDGETS: &str = r#"(defwidget bar [?icon ?screen]
+  (box
+    (label :text "${volume}% ${icon}")
+    (for item in items (label :text item :tooltip volume))
+    (for muted in [true, false] (label :text muted))
+    {volume > 50 ? round(volume, 0) : icon}))"#;
+
+    /// Returns a workspace of [`MAIN`] at `/main.yuck` and [`WIDGETS`] at `/widgets.yuck`.
+    pub fn workspace() -> Workspace {
+        workspace_of(&[("/main.yuck", MAIN), ("/widgets.yuck", WIDGETS)])
+    }
+
+    /// Returns a workspace of `files`, which must parse without diagnostics.
+    pub fn workspace_of(files: &[(&str, &str)]) -> Workspace {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut workspace = Workspace::new();
+        for (path, source) in files {
+            workspace.insert(*path, *source);
+        }
+
+        for (_, file) in workspace.files() {
+            assert_eq!(file.diagnostics, Vec::new());
+        }
+
+        workspace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::options::{EwwVersion, Strictness};
+
+    const CONFIG: &str = r#"; vars
+(defvar volume 50)
+(defpoll time :interval "1s" "date")
+
+(defwidget bar [?muted]
+  (box :orientation "h"
+    (label :text time)
+    {muted ? "" : volume}))
+(defvar)
+(defwindow main :monitor 0 (bar))
+"#;
+
+    #[test]
+    fn diagnostics() {
+        let file = SourceFile::parse("(defvar)\n(defwidget foo [] (box))");
+
+        assert_eq!(file.ast.len(), 2);
+        assert_eq!(
+            file.diagnostics,
+            vec![Diagnostic::new(0, 8, "expected a name")]
+        );
+    }
+
+    #[test]
+    fn unclosed() {
+        let file = SourceFile::parse(r#"(defvar foo "bar""#);
+
+        assert_eq!(file.ast, vec![(17, TopLevelExpr::Err, 17).into()]);
+        assert_eq!(
+            file.diagnostics,
+            vec![Diagnostic::new(17, 0, "unexpected end of file")]
+        );
+    }
+
+    #[test]
+    fn lexical_error() {
+        let file = SourceFile::parse(r#"(defvar foo "bar)"#);
+        assert_eq!(file.ast, vec![(11, TopLevelExpr::Err, 11).into()]);
+        assert_eq!(
+            file.diagnostics,
+            vec![Diagnostic::new(12, 5, "Unterminated string literal")]
+        );
+    }
+
+    #[test]
+    fn forms_before_lexical_error() {
+        let file = SourceFile::parse("(defvar foo 1)\n(defvar bar \"baz");
+
+        assert_eq!(file.ast.len(), 2);
+        assert!(matches!(file.ast[0].1, TopLevelExpr::DefVar(_)));
+        assert_eq!(
+            file.diagnostics,
+            vec![Diagnostic::new(27, 4, "Unterminated string literal")]
+        );
+    }
+
+    #[test]
+    fn open_form_before_another() {
+        let file = SourceFile::parse("(defwidget bar [] (label :text {vol\n(defvar muted-icon 1)");
+        assert!(matches!(file.ast[0].1, TopLevelExpr::Err));
+        assert!(matches!(file.ast[1].1, TopLevelExpr::DefVar(_)));
+        assert_eq!(
+            file.diagnostics,
+            vec![Diagnostic::new(35, 0, "unexpected end of file")]
+        );
+    }
+
+    #[test]
+    fn decimals_and_single_quotes() {
+        let file = SourceFile::parse("(defvar x 1.5)\n(defvar y 'hi')\n(defwidget w [] (box))");
+        assert_eq!(file.diagnostics, Vec::new());
+        assert_eq!(file.ast.len(), 3);
+    }
+
+    #[test]
+    fn comments() {
+        let file = SourceFile::parse("; the bar\n(defwidget bar [] (box))");
+
+        assert_eq!(file.ast.len(), 1);
+        assert_eq!(file.diagnostics, Vec::new());
+    }
+
+    #[test]
+    fn edit_inside_a_form() {
+        let file = edit(CONFIG, &[(CONFIG.find("50").unwrap(), 2, "100")]);
+        assert_eq!(file.ast.len(), 5);
+        assert_eq!(
+            file.diagnostics,
+            vec![Diagnostic::new(164, 8, "expected a name")]
+        );
+    }
+
+    #[test]
+    fn edit_between_forms() {
+        let offset = CONFIG.find("(defwidget").unwrap();
+        let file = edit(CONFIG, &[(offset, 0, "(defvar muted false)\n; comment\n")]);
+        assert_eq!(file.ast.len(), 6);
+    }
+
+    #[test]
+    fn edit_at_the_edges() {
+        edit(CONFIG, &[(0, 0, "(include \"a.yuck\") ")]);
+        edit(CONFIG, &[(CONFIG.len(), 0, "(defvar a 1)")]);
+        edit(CONFIG, &[(0, CONFIG.len(), "(defvar a 1)")]);
+        edit("", &[(0, 0, "(defvar a 1)")]);
+    }
+
+    #[test]
+    fn edit_merging_forms() {
+        let offset = CONFIG.find("(defpoll").unwrap();
+        edit(CONFIG, &[(offset - 2, 2, "")]);
+        edit(CONFIG, &[(offset, 0, "\"")]);
+        edit(CONFIG, &[(offset, 0, "; ")]);
+    }
+
+    #[test]
+    fn edit_opening_and_closing_braces() {
+        let offset = CONFIG.find("(label").unwrap();
+        edit(CONFIG, &[(offset, 0, "{"), (offset, 1, "")]);
+        let offset = CONFIG.find("volume}").unwrap() + "volume".len();
+        edit(CONFIG, &[(offset, 1, ""), (offset, 0, "}")]);
+        let offset = CONFIG.find("(defvar)").unwrap();
+        edit(CONFIG, &[(offset, 0, "{a-")]);
+        edit(CONFIG, &[(offset, 0, "(box :class {a-b} \u{301}")]);
+    }
+
+    #[test]
+    fn edit_recovering_from_errors() {
+        let offset = CONFIG.find("volume}").unwrap() + "volume}".len();
+        let file = edit(
+            CONFIG,
+            &[(offset, 2, ""), (offset, 0, ")"), (offset + 1, 0, ")")],
+        );
+        assert_eq!(file.source, CONFIG);
+    }
+
+    #[test]
+    fn edit_reporting_options() {
+        let options = ParserOptions::new(EwwVersion::new(0, 3, 0), Strictness::Warn);
+        let offset = CONFIG.find("(defwidget").unwrap();
+        let file = edit_with_options(
+            CONFIG,
+            options,
+            &[
+                (offset, 0, "(defwindow bar [monitor] (bar))\n"),
+                (0, 0, "(defvar a 1)\n"),
+            ],
+        );
+        assert_eq!(
+            file.diagnostics,
+            vec![
+                Diagnostic::new(208, 8, "expected a name"),
+                Diagnostic::warning(
+                    93,
+                    7,
+                    "Eww 0.3.0 does not support window parameters, added in 0.5.0"
+                ),
+            ]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn edits_match_a_full_reparse(
+            edits in proptest::collection::vec(
+                (0..=CONFIG.len(), 0..8usize, piece()),
+                1..4,
+            )
+        ) {
+            let mut source = CONFIG.to_owned();
+            let edits: Vec<_> = edits
+                .iter()
+                .map(|(offset, len, replacement)| {
+                    let offset = floor_char_boundary(&source, *offset);
+                    let len = floor_char_boundary(&source, offset + len) - offset;
+                    source.replace_range(offset..offset + len, replacement);
+                    (offset, len, replacement.as_str())
+                })
+                .collect();
+            edit(CONFIG, &edits);
+        }
+    }
+
+    /// The largest char boundary of `source` at or in front of `offset`.
+    fn floor_char_boundary(source: &str, offset: usize) -> usize {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    fn piece() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just(String::new()),
+            Just("(".to_owned()),
+            Just(")".to_owned()),
+            Just("\"".to_owned()),
+            Just(";".to_owned()),
+            Just("\n".to_owned()),
+            Just(" ".to_owned()),
+            Just("(defvar)".to_owned()),
+            Just("(defvar a 1)".to_owned()),
+            Just("{a + 1}".to_owned()),
+            Just("{".to_owned()),
+            Just("}".to_owned()),
+            Just("-".to_owned()),
+            Just(":x {a-b}".to_owned()),
+            Just("é".to_owned()),
+            Just("\u{301}".to_owned()),
+            Just("🇩🇪".to_owned()),
+            "[a-z ]{1,4}",
+        ]
+    }
+
+    /// Applies `edits` of `(offset, len, replacement)` one after the other and compares the file
+    /// after every edit with a full reparse of its source.
+    fn edit(source: &str, edits: &[(usize, usize, &str)]) -> SourceFile {
+        edit_with_options(source, ParserOptions::default(), edits)
+    }
+
+    fn edit_with_options(
+        source: &str,
+        options: ParserOptions,
+        edits: &[(usize, usize, &str)],
+    ) -> SourceFile {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut file = SourceFile::parse_with_options(source, options);
+        for (offset, len, replacement) in edits {
+            file.edit(*offset..*offset + len, replacement);
+
+            let expected = SourceFile::parse_with_options(file.source.clone(), options);
+            assert_eq!(file.ast, expected.ast, "source: {:?}", file.source);
+            assert_eq!(file.diagnostics, expected.diagnostics);
+            assert_eq!(file.recovered, expected.recovered);
+            assert_eq!(file.line_index, expected.line_index);
+        }
+        file
+    }
+
+    #[test]
+    fn unit() {
+        let mut workspace = Workspace::new();
+        workspace.insert("/cfg/eww.yuck", r#"(include "widgets/bar.yuck")"#);
+        workspace.insert("/cfg/widgets/bar.yuck", r#"(include "../vars.yuck")"#);
+        workspace.insert("/cfg/vars.yuck", "(defvar foo 1)");
+        workspace.insert("/other/eww.yuck", "(defvar foo 2)");
+
+        assert_eq!(
+            workspace.includes(Path::new("/cfg/eww.yuck")),
+            vec![PathBuf::from("/cfg/widgets/bar.yuck")]
+        );
+        assert_eq!(
+            workspace.unit(Path::new("/cfg/vars.yuck")),
+            vec![
+                Path::new("/cfg/eww.yuck"),
+                Path::new("/cfg/vars.yuck"),
+                Path::new("/cfg/widgets/bar.yuck"),
+            ]
+        );
+        assert_eq!(
+            workspace.unit(Path::new("/other/eww.yuck")),
+            vec![Path::new("/other/eww.yuck")]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Which widgets and windows use which widgets.
+
+use std::ops::Range;
+
+use crate::{ast::lookup, spanned::Spanned};
+
+use super::{
+    references::{self, is_same, ReferenceKind},
+    symbols::{self, Definition, DefinitionKind, SymbolRef},
+    Workspace,
+};
+
+/// A use of widgets by a `defwidget` or `defwindow`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call<'ws> {
+    /// The caller for incoming calls, the called widget for outgoing calls.
+    pub definition: Definition<'ws>,
+    /// The names of the widget calls, in the file of the caller.
+    pub spans: Vec<Range<usize>>,
+}
+
+/// Returns the widgets and windows that use `widget` directly, in the order of their first use.
+pub fn incoming_calls<'ws>(workspace: &'ws Workspace, widget: &Definition<'ws>) -> Vec<Call<'ws>> {
+    let mut calls: Vec<Call> = Vec::new();
+    let definitions = symbols::definitions(workspace, widget.path);
+
+    for reference in references::references(workspace, widget) {
+        if reference.kind != ReferenceKind::WidgetCall {
+            continue;
+        }
+        let Some(caller) = definitions.iter().find(|definition| {
+            definition.path == reference.path
+                && definition.node.0 <= reference.span.start
+                && reference.span.end <= definition.node.2
+        }) else {
+            continue;
+        };
+
+        match calls
+            .iter_mut()
+            .find(|call| is_same(&call.definition, caller))
+        {
+            Some(call) => call.spans.push(reference.span),
+            None => calls.push(Call {
+                definition: caller.clone(),
+                spans: vec![reference.span],
+            }),
+        }
+    }
+
+    calls
+}
+
+/// Returns the user defined widgets `caller` uses directly, in the order of their first use.
+/// Built-in widgets are not included.
+pub fn outgoing_calls<'ws>(workspace: &'ws Workspace, caller: &Definition<'ws>) -> Vec<Call<'ws>> {
+    let mut calls: Vec<Call> = Vec::new();
+    let Some(file) = workspace.get(caller.path) else {
+        return calls;
+    };
+
+    lookup::for_each_node_path(&file.ast, |nodes| {
+        let is_in_caller = nodes
+            .first()
+            .is_some_and(|node| node.0 == caller.node.0 && node.2 == caller.node.2);
+        if !is_in_caller {
+            return;
+        }
+        let Some(symbol_ref @ SymbolRef::Widget(Spanned(l, _, r))) =
+            symbols::classify(caller.path, nodes)
+        else {
+            return;
+        };
+        let Some(callee) = symbols::definition_of(workspace, caller.path, &symbol_ref) else {
+            return;
+        };
+        let span = l..r;
+
+        match calls
+            .iter_mut()
+            .find(|call| is_same(&call.definition, &callee))
+        {
+            Some(call) => call.spans.push(span),
+            None => calls.push(Call {
+                definition: callee,
+                spans: vec![span],
+            }),
+        }
+    });
+
+    calls
+}
+
+/// Returns every widget and window that uses `widget`, directly or through other widgets.
+pub fn transitive_callers<'ws>(
+    workspace: &'ws Workspace,
+    widget: &Definition<'ws>,
+) -> Vec<Definition<'ws>> {
+    let mut callers: Vec<Definition> = Vec::new();
+    let mut pending = vec![widget.clone()];
+
+    while let Some(callee) = pending.pop() {
+        for call in incoming_calls(workspace, &callee) {
+            // Recursive widgets would otherwise be visited forever.
+            let is_known = is_same(&call.definition, widget)
+                || callers
+                    .iter()
+                    .any(|caller| is_same(caller, &call.definition));
+            if is_known {
+                continue;
+            }
+
+            if call.definition.kind == DefinitionKind::DefWidget {
+                pending.push(call.definition.clone());
+            }
+            callers.push(call.definition);
+        }
+    }
+
+    callers
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::workspace::fixture::workspace_of;
+
+    const MAIN: &str = r#"(include "widgets.yuck")
+(defwindow top (bar))
+(defwindow bottom (box (volume) (volume)))"#;
+
+    const WIDGETS: &str = r#"(defwidget bar []
+  (box (volume) (clock)))
+(defwidget volume [] (label :text "50%"))
+(defwidget clock [] (label :text "12:00"))"#;
+
+    #[test]
+    fn incoming() {
+        let workspace = test();
+        let volume = widget(&workspace, "volume");
+
+        assert_eq!(
+            summarize(&workspace, incoming_calls(&workspace, &volume), None),
+            vec![
+                ("bottom", vec!["volume", "volume"]),
+                ("bar", vec!["volume"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn outgoing() {
+        let workspace = test();
+        let bar = widget(&workspace, "bar");
+
+        assert_eq!(
+            summarize(
+                &workspace,
+                outgoing_calls(&workspace, &bar),
+                Some("/widgets.yuck")
+            ),
+            vec![("volume", vec!["volume"]), ("clock", vec!["clock"])]
+        );
+
+        let bottom = symbols::definitions(&workspace, Path::new("/main.yuck"))
+            .into_iter()
+            .find(|definition| definition.name.1 .0 == "bottom")
+            .unwrap();
+        assert_eq!(
+            summarize(
+                &workspace,
+                outgoing_calls(&workspace, &bottom),
+                Some("/main.yuck")
+            ),
+            vec![("volume", vec!["volume", "volume"])]
+        );
+    }
+
+    #[test]
+    fn transitive() {
+        let workspace = test();
+        let clock = widget(&workspace, "clock");
+
+        let callers: Vec<_> = transitive_callers(&workspace, &clock)
+            .into_iter()
+            .map(|caller| caller.name.1 .0.as_ref())
+            .collect();
+        assert_eq!(callers, vec!["bar", "top"]);
+    }
+
+    #[test]
+    fn recursive() {
+        let mut workspace = Workspace::new();
+        workspace.insert("/main.yuck", "(defwidget a [] (b))\n(defwidget b [] (a))");
+        let a = widget(&workspace, "a");
+
+        let callers: Vec<_> = transitive_callers(&workspace, &a)
+            .into_iter()
+            .map(|caller| caller.name.1 .0.as_ref())
+            .collect();
+        assert_eq!(callers, vec!["b"]);
+    }
+
+    fn widget<'ws>(workspace: &'ws Workspace, name: &str) -> Definition<'ws> {
+        let (path, _) = workspace.files().next().unwrap();
+
+        symbols::definitions(workspace, path)
+            .into_iter()
+            .find(|definition| {
+                definition.kind == DefinitionKind::DefWidget && definition.name.1 .0 == name
+            })
+            .unwrap()
+    }
+
+    /// Returns the name of the definition and the text of the spans of every call. The spans are
+    /// in the file at `path`, or in the file of the caller for incoming calls.
+    fn summarize<'ws>(
+        workspace: &'ws Workspace,
+        calls: Vec<Call<'ws>>,
+        path: Option<&str>,
+    ) -> Vec<(&'ws str, Vec<&'ws str>)> {
+        calls
+            .into_iter()
+            .map(|call| {
+                let path = path.map_or(call.definition.path, Path::new);
+                let source = &workspace.get(path).unwrap().source;
+                let spans = call
+                    .spans
+                    .iter()
+                    .map(|span| &source[span.clone()])
+                    .collect();
+
+                (call.definition.name.1 .0.as_ref(), spans)
+            })
+            .collect()
+    }
+
+    fn test() -> Workspace {
+        workspace_of(&[("/main.yuck", MAIN), ("/widgets.yuck", WIDGETS)])
+    }
+}
@@ -0,0 +1,606 @@
+//! Completion of keywords, widgets, properties, variables and functions at a byte offset.
+//!
+//! The form being typed usually does not parse yet. The tokens in front of the offset tell what is
+//! being typed, e.g. a widget after `(` or a property after `:`. The source up to the offset is
+//! then repaired by putting a placeholder there and closing the open delimiters, and the enclosing
+//! widget, property and scopes are looked up around the placeholder in the AST of the repaired
+//! source. The names to complete come from the definitions the parser recovered from the rest of
+//! the unit, the enclosing parameter lists and `for` loops, and the built-in [`schema`].
+
+use std::{ops::Range, path::Path};
+
+use crate::{
+    ast::{
+        def_widget::DefWidget,
+        def_window::DefWindow,
+        lookup::{self, NodeRef},
+    },
+    lexer::{Lexer, LexicalError, Tok},
+    parse,
+    spanned::Spanned,
+};
+
+use super::{
+    schema::{
+        self, WidgetSchema, DEF_LISTEN_PROPERTIES, DEF_POLL_PROPERTIES, DEF_WINDOW_PROPERTIES,
+        FUNCTIONS, LITERAL_PROPERTIES, MAGIC_VARIABLES,
+    },
+    symbols::{self, DefinitionKind},
+    Workspace,
+};
+
+const TOP_LEVEL_KEYWORDS: &[&str] = &[
+    "defwidget",
+    "defwindow",
+    "defvar",
+    "defpoll",
+    "deflisten",
+    "include",
+    "literal",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionKind {
+    Keyword,
+    Widget,
+    Property,
+    Variable,
+    Function,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+}
+
+impl Completion {
+    pub fn new(label: impl Into<String>, kind: CompletionKind) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+        }
+    }
+}
+
+/// The candidates for the partially typed word covered by `span`, which is empty if nothing has
+/// been typed yet. Every candidate starts with the typed prefix.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Completions {
+    pub span: Range<usize>,
+    pub items: Vec<Completion>,
+}
+
+/// Returns the completions at `offset` in the file at `path`, using the definitions of its unit.
+pub fn completions(workspace: &Workspace, path: &Path, offset: usize) -> Completions {
+    let Some(file) = workspace.get(path) else {
+        return Completions::default();
+    };
+    let Some(source) = file.source.get(..offset) else {
+        return Completions::default();
+    };
+    let nothing = || Completions {
+        span: offset..offset,
+        items: Vec::new(),
+    };
+    let Some(cursor) = Cursor::analyze(source) else {
+        return nothing();
+    };
+    let repaired = cursor.repair(source);
+    let (ast, _) = parse::parse_top_level(&repaired);
+    let nodes = lookup::node_path_at(&ast, cursor.cut..cursor.cut);
+    let Some(context) = cursor.context(&nodes) else {
+        return nothing();
+    };
+
+    let mut items = Vec::new();
+    let mut push_all = |labels: &mut dyn Iterator<Item = &str>, kind| {
+        items.extend(labels.map(|label| Completion::new(label, kind)));
+    };
+
+    let with_functions = context == Context::Expression;
+    match context {
+        Context::TopLevel => push_all(
+            &mut TOP_LEVEL_KEYWORDS.iter().copied(),
+            CompletionKind::Keyword,
+        ),
+        Context::Widget { in_def_widget } => {
+            let definitions = symbols::definitions(workspace, path);
+            push_all(
+                &mut definitions
+                    .iter()
+                    .filter(|definition| definition.kind == DefinitionKind::DefWidget)
+                    .map(|definition| definition.name.1 .0.as_ref()),
+                CompletionKind::Widget,
+            );
+            push_all(
+                &mut schema::WIDGETS.iter().map(|widget| widget.name),
+                CompletionKind::Widget,
+            );
+            if in_def_widget {
+                push_all(&mut ["children"].into_iter(), CompletionKind::Widget);
+            }
+            push_all(&mut ["for"].into_iter(), CompletionKind::Keyword);
+        }
+        Context::Property(NodeRef::WidgetCall(widget_call)) => {
+            let name = &widget_call.name.1;
+            if let Some(widget) = WidgetSchema::find(&name.0) {
+                push_all(&mut widget.all_properties(), CompletionKind::Property);
+            } else if let Some(def_widget) = symbols::find_widget(workspace, path, name) {
+                push_all(
+                    &mut def_widget
+                        .params
+                        .iter()
+                        .map(|param| param.1.name.1 .0.as_ref()),
+                    CompletionKind::Property,
+                );
+            }
+        }
+        Context::Property(node) => {
+            let properties = match node {
+                NodeRef::DefListen(_) => DEF_LISTEN_PROPERTIES,
+                NodeRef::DefPoll(_) => DEF_POLL_PROPERTIES,
+                NodeRef::DefWindow(_) => DEF_WINDOW_PROPERTIES,
+                NodeRef::Literal(_) => LITERAL_PROPERTIES,
+                _ => &[],
+            };
+            push_all(&mut properties.iter().copied(), CompletionKind::Property);
+        }
+        Context::Variable | Context::Expression => {
+            let definitions = symbols::definitions(workspace, path);
+            push_all(
+                &mut locals(&nodes, cursor.cut).into_iter().rev(),
+                CompletionKind::Variable,
+            );
+            push_all(
+                &mut definitions
+                    .iter()
+                    .filter(|definition| definition.kind.is_variable())
+                    .map(|definition| definition.name.1 .0.as_ref()),
+                CompletionKind::Variable,
+            );
+            push_all(
+                &mut MAGIC_VARIABLES.iter().copied(),
+                CompletionKind::Variable,
+            );
+            if with_functions {
+                push_all(&mut FUNCTIONS.iter().copied(), CompletionKind::Function);
+            }
+        }
+    }
+
+    let prefix = &source[cursor.prefix_start..];
+    let mut completions = Completions {
+        span: cursor.prefix_start..offset,
+        items: Vec::new(),
+    };
+    for item in items {
+        let is_duplicate = completions
+            .items
+            .iter()
+            .any(|other| other.label == item.label);
+        if item.label.starts_with(prefix) && !is_duplicate {
+            completions.items.push(item);
+        }
+    }
+
+    completions
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Context<'ast> {
+    /// The keyword of a top level form, after `(`.
+    TopLevel,
+    /// A widget in a widget tree, after `(`.
+    Widget { in_def_widget: bool },
+    /// The name of a property of the form, after `:`.
+    Property(NodeRef<'ast>),
+    /// A value that can be a variable but no other expression, like widget arguments.
+    Variable,
+    /// A full expression, in `{}` or in a `${}` interpolation.
+    Expression,
+}
+
+/// What is being typed at the cursor, as far as the tokens in front of it tell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Slot {
+    /// The first word of a form, after `(`.
+    Head,
+    /// The name of an argument, after `:`.
+    ArgName,
+    /// Anything else in a form, e.g. the value of an argument.
+    Value,
+    /// An expression in `{}` or in a `${}` interpolation.
+    Expression,
+}
+
+struct Cursor {
+    slot: Slot,
+    prefix_start: usize,
+    /// Where the repaired source replaces the rest of `source`: the start of the word being typed,
+    /// or of the outermost `{}` or string the cursor is in.
+    cut: usize,
+    /// The symbol, `{}` or string that stands in for the construct at `cut` in the repaired source.
+    placeholder: &'static str,
+    /// The closing delimiters of the delimiters still open at `cut`, innermost first.
+    closers: String,
+}
+
+impl Cursor {
+    /// Determines the slot at the end of `source`. Returns `None` if nothing can be completed
+    /// there.
+    fn analyze(source: &str) -> Option<Self> {
+        let mut tokens = Vec::new();
+        let mut open_string = None;
+
+        for result in Lexer::new(source) {
+            match result {
+                Ok((_, Tok::Comment(_), _)) => {}
+                Ok(token) => tokens.push(token),
+                // The cursor is inside a string literal.
+                Err(LexicalError::UnterminatedLiteralString { span, .. }) => {
+                    open_string = Some(span.offset());
+                    break;
+                }
+                Err(err) => {
+                    log::debug!("no completions after lexical error: {:?}", err);
+                    return None;
+                }
+            }
+        }
+
+        // A symbol or keyword touching the cursor is the prefix being typed.
+        let mut prefix_start = source.len();
+        if let (None, Some((l, tok, r))) = (open_string, tokens.last()) {
+            let is_word = matches!(
+                tok,
+                Tok::LiteralSymbol(_)
+                    | Tok::KeywordDefListen
+                    | Tok::KeywordDefPoll
+                    | Tok::KeywordDefVar
+                    | Tok::KeywordDefWidget
+                    | Tok::KeywordDefWindow
+                    | Tok::KeywordFalse
+                    | Tok::KeywordFor
+                    | Tok::KeywordInclude
+                    | Tok::KeywordLiteral
+                    | Tok::KeywordTrue
+            );
+            if is_word && *r == source.len() {
+                prefix_start = *l;
+                tokens.pop();
+            }
+        }
+
+        let mut open = Vec::new();
+        for (l, tok, _) in &tokens {
+            match tok {
+                Tok::DelimiterLeftParen | Tok::DelimiterLeftBracket | Tok::DelimiterLeftBrace => {
+                    open.push((*l, tok));
+                }
+                Tok::DelimiterRightParen
+                | Tok::DelimiterRightBracket
+                | Tok::DelimiterRightBrace => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+        let outermost_block = open
+            .iter()
+            .find(|(_, tok)| **tok == Tok::DelimiterLeftBrace)
+            .map(|(l, _)| *l);
+
+        let (slot, cut, placeholder) = if let Some(string_start) = open_string {
+            // Only `${}` interpolations in strings are completed.
+            let content = &source[string_start + 1..];
+            let interpolation = content.rfind("${")?;
+            if content[interpolation..].contains('}') {
+                return None;
+            }
+            let prefix_len = content
+                .chars()
+                .rev()
+                .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                .map(char::len_utf8)
+                .sum::<usize>()
+                .min(content.len() - interpolation - 2);
+            prefix_start = source.len() - prefix_len;
+
+            match outermost_block {
+                Some(block_start) => (Slot::Expression, block_start, "{cursor}"),
+                None => (Slot::Expression, string_start, "\"\""),
+            }
+        } else if let Some(block_start) = outermost_block {
+            (Slot::Expression, block_start, "{cursor}")
+        } else {
+            match tokens.last().map(|(_, tok, _)| tok) {
+                Some(Tok::DelimiterLeftParen) => (Slot::Head, prefix_start, "cursor"),
+                // The argument needs a value to parse.
+                Some(Tok::PunctuationColon) => (Slot::ArgName, prefix_start, "cursor \"\""),
+                _ => (Slot::Value, prefix_start, "cursor"),
+            }
+        };
+
+        let closers: String = open
+            .iter()
+            .rev()
+            .filter(|(l, _)| *l < cut)
+            .map(|(_, tok)| match tok {
+                Tok::DelimiterLeftParen => ')',
+                Tok::DelimiterLeftBracket => ']',
+                _ => '}',
+            })
+            .collect();
+        if closers.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            slot,
+            prefix_start,
+            cut,
+            placeholder,
+            closers,
+        })
+    }
+
+    /// Returns `source` up to the cursor with a placeholder at the cursor and the open delimiters
+    /// closed, so that the forms around the cursor parse. An empty string is added in front of the
+    /// closing delimiters if the placeholder is not in the AST otherwise, because the innermost
+    /// form lacks a required part like the script of a `defpoll` or the body of a `for` loop.
+    fn repair(&self, source: &str) -> String {
+        let repaired = |filler: &str| {
+            format!(
+                "{}{}{}{}",
+                &source[..self.cut],
+                self.placeholder,
+                filler,
+                self.closers
+            )
+        };
+
+        let repaired_source = repaired("");
+        let (ast, _) = parse::parse_top_level(&repaired_source);
+        let nodes = lookup::node_path_at(&ast, self.cut..self.cut);
+        if nodes.last().is_some_and(|node| node.0 == self.cut) {
+            repaired_source
+        } else {
+            repaired(" \"\"")
+        }
+    }
+
+    /// Determines the context of the placeholder from the `nodes` enclosing it in the repaired
+    /// source.
+    fn context<'ast>(&self, nodes: &[Spanned<NodeRef<'ast>>]) -> Option<Context<'ast>> {
+        if self.slot == Slot::Expression {
+            return Some(Context::Expression);
+        }
+        if self.slot == Slot::Head && self.closers.len() == 1 {
+            return Some(Context::TopLevel);
+        }
+
+        let Some(Spanned(l, NodeRef::Symbol(_), _)) = nodes.last() else {
+            return None;
+        };
+        if *l != self.cut {
+            return None;
+        }
+        let mut ancestors = nodes.iter().rev().skip(1).map(|node| node.1);
+        let parent = ancestors.next()?;
+
+        match (self.slot, parent) {
+            (Slot::Head, NodeRef::WidgetCall(_)) => match ancestors.next()? {
+                NodeRef::DefWidget(_)
+                | NodeRef::DefWindow(_)
+                | NodeRef::ForLoop(_)
+                | NodeRef::WidgetCall(_) => Some(Context::Widget {
+                    in_def_widget: nodes
+                        .iter()
+                        .any(|node| matches!(node.1, NodeRef::DefWidget(_))),
+                }),
+                _ => None,
+            },
+            (
+                Slot::ArgName,
+                NodeRef::WidgetCallArg(_)
+                | NodeRef::DefWindowArg(_)
+                | NodeRef::DefPollArg(_)
+                | NodeRef::DefListenArg(_)
+                | NodeRef::LiteralArg(_),
+            ) => Some(Context::Property(ancestors.next()?)),
+            (Slot::Value, NodeRef::WidgetCallArg(_)) => Some(Context::Variable),
+            (Slot::Value, NodeRef::ForLoop(for_loop)) if for_loop.iterable.0 == self.cut => {
+                Some(Context::Variable)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the parameters and `for` loop bindings in scope at `offset`, innermost last.
+fn locals<'ast>(nodes: &[Spanned<NodeRef<'ast>>], offset: usize) -> Vec<&'ast str> {
+    let mut locals = Vec::new();
+    for node in nodes {
+        match node.1 {
+            NodeRef::DefWidget(DefWidget { params, .. })
+            | NodeRef::DefWindow(DefWindow { params, .. }) => {
+                locals.extend(params.iter().map(|param| param.1.name.1 .0.as_ref()));
+            }
+            NodeRef::ForLoop(for_loop) if for_loop.iterable.2 <= offset => {
+                locals.push(for_loop.binding.1 .0.as_ref());
+            }
+            _ => {}
+        }
+    }
+
+    locals
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Completes at the `|` in `inp`.
+    fn test(inp: &str) -> Completions {
+        test_with(Workspace::new(), inp)
+    }
+
+    fn test_with(mut workspace: Workspace, inp: &str) -> Completions {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let offset = inp.find('|').expect("the input should contain a cursor");
+        let source = inp.replacen('|', "", 1);
+        workspace.insert("/main.yuck", source);
+
+        completions(&workspace, Path::new("/main.yuck"), offset)
+    }
+
+    fn labels(completions: &Completions) -> Vec<&str> {
+        completions
+            .items
+            .iter()
+            .map(|item| item.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn top_level_keywords() {
+        let completions = test("(defvar foo 1)\n(def|");
+
+        assert_eq!(completions.span, 16..19);
+        assert_eq!(
+            labels(&completions),
+            vec!["defwidget", "defwindow", "defvar", "defpoll", "deflisten"]
+        );
+        assert!(completions
+            .items
+            .iter()
+            .all(|item| item.kind == CompletionKind::Keyword));
+    }
+
+    #[test]
+    fn widgets() {
+        let completions = test("(defwidget bar [] (b|))\n(defwidget baz [] (box))");
+
+        assert_eq!(completions.span, 19..20);
+        assert_eq!(labels(&completions), vec!["bar", "baz", "box", "button"]);
+    }
+
+    #[test]
+    fn widgets_in_for_loops() {
+        let completions = test("(defwindow main (for x in xs (|");
+
+        assert!(labels(&completions).contains(&"label"));
+        assert!(labels(&completions).contains(&"for"));
+        assert!(!labels(&completions).contains(&"children"));
+    }
+
+    #[test]
+    fn no_widgets_in_param_list() {
+        assert_eq!(test("(defwidget bar [(|").items, Vec::new());
+    }
+
+    #[test]
+    fn builtin_properties() {
+        let completions = test("(defwidget bar [] (label :te|))");
+
+        assert_eq!(completions.span, 26..28);
+        assert_eq!(
+            completions.items,
+            vec![Completion::new("text", CompletionKind::Property)]
+        );
+    }
+
+    #[test]
+    fn param_properties() {
+        let completions =
+            test("(defwidget icon [name ?size] (image :icon name))\n(defwindow main (icon :|");
+
+        assert_eq!(labels(&completions), vec!["name", "size"]);
+    }
+
+    #[test]
+    fn definition_properties() {
+        assert_eq!(
+            labels(&test("(defpoll time :i|")),
+            vec!["interval", "initial"]
+        );
+        assert_eq!(labels(&test("(defwindow main :mon|")), vec!["monitor"]);
+    }
+
+    #[test]
+    fn forms_missing_required_parts() {
+        assert_eq!(labels(&test("(deflisten music :|")), vec!["initial"]);
+        assert_eq!(labels(&test("(literal :|")), vec!["content"]);
+
+        let completions = test("(defvar volumes \"[]\")\n(defwindow main (for v in vol|");
+        assert_eq!(labels(&completions), vec!["volumes"]);
+    }
+
+    #[test]
+    fn variables_in_arguments() {
+        let completions =
+            test("(defvar volume 50)\n(defwidget bar [vol] (for v in volumes (label :text v|");
+
+        assert_eq!(labels(&completions), vec!["v", "vol", "volume"]);
+        assert!(completions
+            .items
+            .iter()
+            .all(|item| item.kind == CompletionKind::Variable));
+    }
+
+    #[test]
+    fn expressions() {
+        let completions = test("(defvar volume 50)\n(defwidget bar [] { ro|");
+
+        assert_eq!(
+            completions.items,
+            vec![Completion::new("round", CompletionKind::Function)]
+        );
+
+        let completions = test("(defvar volume 50)\n(defwidget bar [] {volume > 1 ? v| : 0})");
+        assert_eq!(labels(&completions), vec!["volume"]);
+    }
+
+    #[test]
+    fn interpolations() {
+        let completions = test("(defvar volume 50)\n(defwidget bar [] (label :text \"${vo|");
+
+        assert_eq!(completions.span, 53..55);
+        assert_eq!(labels(&completions), vec!["volume"]);
+        assert_eq!(
+            test("(defvar volume 50)\n(defwidget bar [] (label :text \"${volume} v|\"))").items,
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn definitions_after_errors() {
+        let completions = test("(defwidget bar [] (label :text {vol|\n(defvar volume 50)");
+
+        assert_eq!(labels(&completions), vec!["volume"]);
+    }
+
+    #[test]
+    fn included_definitions() {
+        let mut workspace = Workspace::new();
+        workspace.insert("/vars.yuck", "(defvar volume 50)");
+
+        let completions = test_with(
+            workspace,
+            "(include \"vars.yuck\")\n(defwidget bar [] (label :text v|",
+        );
+        assert_eq!(labels(&completions), vec!["volume"]);
+    }
+
+    #[test]
+    fn nothing_to_complete() {
+        assert_eq!(test("(defvar foo |").items, Vec::new());
+        assert_eq!(test("(defwindow main :geometry (|").items, Vec::new());
+        assert_eq!(test("|").items, Vec::new());
+        assert_eq!(test("(defvar foo \"b|").items, Vec::new());
+    }
+}
@@ -0,0 +1,196 @@
+//! Checks that go beyond parsing, like references to widgets or variables that do not exist.
+
+use std::path::Path;
+
+use crate::{
+    ast::{atom::Atom, lookup, lookup::NodeRef, symbol::Symbol, top_level_expr::TopLevelExpr},
+    spanned::Spanned,
+};
+
+use super::{
+    references::is_in_widget_tree,
+    schema::{WidgetSchema, MAGIC_VARIABLES},
+    symbols::{self, SymbolRef},
+    Diagnostic, Workspace,
+};
+
+/// Returns the lint diagnostics of the file at `path`, ordered by position. Names are resolved in
+/// the unit of the file, so its includes should be loaded first.
+pub fn lint(workspace: &Workspace, path: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some((path, file)) = workspace.files.get_key_value(path) else {
+        return diagnostics;
+    };
+
+    for (include, resolved) in file.include_paths().zip(workspace.includes(path)) {
+        if workspace.get(&resolved).is_none() {
+            let Spanned(l, name, r) = include;
+            diagnostics.push(Diagnostic::new(
+                *l,
+                r - l,
+                format!("included file `{}` does not exist", name),
+            ));
+        }
+    }
+
+    // Eww keeps malformed JSON as a plain string, so it is only a warning.
+    for node in &file.ast {
+        let TopLevelExpr::DefVar(def_var) = &node.1 else {
+            continue;
+        };
+        if let Some(Err(err)) = def_var.json_value() {
+            diagnostics.push(Diagnostic::warning(
+                err.span().offset(),
+                err.span().len(),
+                err.to_string(),
+            ));
+        }
+    }
+
+    lookup::for_each_node_path(&file.ast, |nodes| {
+        // Window arguments like `:stacking fg` and `(geometry)` are configuration, not widgets.
+        if nodes
+            .iter()
+            .any(|node| matches!(node.1, NodeRef::DefWindowArg(_)))
+        {
+            return;
+        }
+
+        match nodes.last() {
+            Some(Spanned(_, NodeRef::Symbol(_), _)) => {
+                let Some(symbol_ref) = symbols::classify(path, nodes) else {
+                    return;
+                };
+                let Spanned(l, name, r) = symbol_ref.name();
+                let message = match &symbol_ref {
+                    SymbolRef::Widget(_) if WidgetSchema::find(&name.0).is_some() => return,
+                    SymbolRef::Widget(_) => format!("unknown widget `{}`", name.0),
+                    SymbolRef::Variable { .. } if MAGIC_VARIABLES.contains(&name.0.as_ref()) => {
+                        return
+                    }
+                    SymbolRef::Variable { .. } => format!("unknown variable `{}`", name.0),
+                    _ => return,
+                };
+
+                if symbols::definition_of(workspace, path, &symbol_ref).is_none() {
+                    diagnostics.push(Diagnostic::new(l, r - l, message));
+                }
+            }
+            Some(Spanned(l, NodeRef::Atom(Atom::StrLit(_)), r)) if is_in_widget_tree(nodes) => {
+                for Spanned(l, name, r) in symbols::interpolated_symbols(&file.source, *l..*r) {
+                    let is_defined = MAGIC_VARIABLES.contains(&name)
+                        || symbols::enclosing_binding(path, nodes, name).is_some()
+                        || symbols::find_variable(workspace, path, &Symbol::new(name)).is_some();
+                    if !is_defined {
+                        diagnostics.push(Diagnostic::new(
+                            l,
+                            r - l,
+                            format!("unknown variable `{}`", name),
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    });
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn unknown_names() {
+        let source = r#"(defvar volume 50)
+(defwidget bar [icon]
+  (box
+    (label :text "${volume} ${icon} ${EWW_TIME} ${muted}")
+    (for item in items (sidebar :item item))))"#;
+
+        assert_eq!(
+            test(source),
+            vec![
+                "unknown variable `muted`",
+                "unknown variable `items`",
+                "unknown widget `sidebar`",
+            ]
+        );
+    }
+
+    #[test]
+    fn window_arguments() {
+        let source = r#"(defwindow main
+  :stacking fg
+  :geometry (geometry :x "0%" :width "100%")
+  (box))"#;
+
+        assert_eq!(test(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn missing_include() {
+        let mut workspace = Workspace::new();
+        workspace.insert("/main.yuck", r#"(include "a.yuck") (include "b.yuck")"#);
+        workspace.insert("/a.yuck", "");
+
+        let diagnostics = lint(&workspace, Path::new("/main.yuck"));
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::new(
+                28,
+                8,
+                "included file `b.yuck` does not exist"
+            )]
+        );
+    }
+
+    #[test]
+    fn malformed_json() {
+        let mut workspace = Workspace::new();
+        workspace.insert(
+            "/main.yuck",
+            r#"(defvar items "[1, 2") (defvar names "[1, 2]") (defvar text "plain [text")"#,
+        );
+
+        let diagnostics = lint(&workspace, Path::new("/main.yuck"));
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::warning(
+                20,
+                0,
+                "malformed JSON: expected ']' or ','"
+            )]
+        );
+    }
+
+    /// Returns the messages, after checking that the span of each diagnostic is the name it
+    /// mentions.
+    fn test(source: &str) -> Vec<String> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut workspace = Workspace::new();
+        workspace.insert("/main.yuck", source);
+        assert_eq!(
+            workspace.get(Path::new("/main.yuck")).unwrap().diagnostics,
+            Vec::new()
+        );
+
+        lint(&workspace, Path::new("/main.yuck"))
+            .into_iter()
+            .map(|diagnostic| {
+                let name = format!("`{}`", &source[diagnostic.span]);
+                assert!(
+                    diagnostic.message.ends_with(&name),
+                    "{}",
+                    diagnostic.message
+                );
+                diagnostic.message
+            })
+            .collect()
+    }
+}
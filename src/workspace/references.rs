@@ -0,0 +1,249 @@
+//! Lookup of every occurrence of a definition across the files of its unit.
+
+use std::{ops::Range, path::Path};
+
+use crate::{
+    ast::{atom::Atom, lookup, lookup::NodeRef, symbol::Symbol},
+    spanned::Spanned,
+};
+
+use super::{
+    symbols::{self, Definition, SymbolRef},
+    Workspace,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// The name in the definition itself.
+    Definition,
+    /// The name of a widget call, `bar` in `(bar)`.
+    WidgetCall,
+    /// The name of an argument passed to a widget, `icon` in `(bar :icon "")`.
+    ArgName,
+    /// A variable in an expression or a widget argument.
+    Variable,
+    /// A variable in a `${}` interpolation of a string literal.
+    Interpolation,
+}
+
+/// An occurrence of the name of a definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reference<'ws> {
+    pub kind: ReferenceKind,
+    pub path: &'ws Path,
+    pub span: Range<usize>,
+}
+
+/// Returns every occurrence of the name of `definition` in its unit, including the definition
+/// itself, ordered by path and position.
+pub fn references<'ws>(
+    workspace: &'ws Workspace,
+    definition: &Definition<'ws>,
+) -> Vec<Reference<'ws>> {
+    let name = &definition.name.1;
+    let mut references = Vec::new();
+
+    for path in workspace.unit(definition.path) {
+        let file = &workspace.files[path];
+
+        lookup::for_each_node_path(&file.ast, |nodes| {
+            let Some(Spanned(l, node, r)) = nodes.last() else {
+                return;
+            };
+
+            match node {
+                // Only symbols with the same name can refer to the definition, checking the name
+                // first avoids resolving every symbol.
+                NodeRef::Symbol(symbol) if *symbol == name => {
+                    let Some(symbol_ref) = symbols::classify(path, nodes) else {
+                        return;
+                    };
+                    let kind = match symbol_ref {
+                        SymbolRef::Definition(_) => ReferenceKind::Definition,
+                        SymbolRef::Widget(_) => ReferenceKind::WidgetCall,
+                        SymbolRef::ArgName { .. } => ReferenceKind::ArgName,
+                        SymbolRef::Variable { .. } => ReferenceKind::Variable,
+                        SymbolRef::Function(_) | SymbolRef::Property(_) => return,
+                    };
+
+                    let resolved = symbols::definition_of(workspace, path, &symbol_ref);
+                    if resolved.is_some_and(|resolved| is_same(&resolved, definition)) {
+                        references.push(Reference {
+                            kind,
+                            path,
+                            span: *l..*r,
+                        });
+                    }
+                }
+                NodeRef::Atom(Atom::StrLit(_)) if is_in_widget_tree(nodes) => {
+                    for Spanned(l, symbol, r) in symbols::interpolated_symbols(&file.source, *l..*r)
+                    {
+                        if symbol != name.0 {
+                            continue;
+                        }
+
+                        let resolved =
+                            symbols::enclosing_binding(path, nodes, symbol).or_else(|| {
+                                symbols::find_variable(workspace, path, &Symbol::new(symbol))
+                            });
+                        if resolved.is_some_and(|resolved| is_same(&resolved, definition)) {
+                            references.push(Reference {
+                                kind: ReferenceKind::Interpolation,
+                                path,
+                                span: l..r,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        });
+    }
+
+    references
+}
+
+/// Returns the references of the definition of the symbol at `offset`, see [`references`].
+pub fn references_at<'ws>(
+    workspace: &'ws Workspace,
+    path: &Path,
+    offset: usize,
+    include_definition: bool,
+) -> Vec<Reference<'ws>> {
+    let Some(definition) = symbols::symbol_at(workspace, path, offset)
+        .and_then(|symbol_ref| symbols::definition_of(workspace, path, &symbol_ref))
+    else {
+        return Vec::new();
+    };
+
+    let mut references = references(workspace, &definition);
+    if !include_definition {
+        references.retain(|reference| reference.kind != ReferenceKind::Definition);
+    }
+    references
+}
+
+/// Whether `a` and `b` are the same definition. Compares locations, so that definitions of
+/// different versions of a workspace can be compared.
+pub(super) fn is_same(a: &Definition, b: &Definition) -> bool {
+    a.path == b.path && a.name.0 == b.name.0 && a.name.2 == b.name.2
+}
+
+/// Interpolations are only evaluated in widgets.
+pub(super) fn is_in_widget_tree(nodes: &[Spanned<NodeRef>]) -> bool {
+    matches!(
+        nodes.first(),
+        Some(Spanned(_, NodeRef::DefWidget(_) | NodeRef::DefWindow(_), _))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::workspace::fixture::{workspace, MAIN, WIDGETS};
+
+    #[test]
+    fn global_variable() {
+        let workspace = workspace();
+
+        assert_eq!(
+            locations(&workspace, "/main.yuck", MAIN, "volume", 0),
+            vec![
+                (ReferenceKind::Definition, "/main.yuck", "volume", 33),
+                (ReferenceKind::Variable, "/main.yuck", "volume", 103),
+                (ReferenceKind::Interpolation, "/widgets.yuck", "volume", 58),
+                (ReferenceKind::Variable, "/widgets.yuck", "volume", 127),
+                (ReferenceKind::Variable, "/widgets.yuck", "volume", 194),
+                (ReferenceKind::Variable, "/widgets.yuck", "volume", 214),
+            ]
+        );
+    }
+
+    #[test]
+    fn widget_and_params() {
+        let workspace = workspace();
+
+        assert_eq!(
+            locations(&workspace, "/widgets.yuck", WIDGETS, "bar", 0),
+            vec![
+                (ReferenceKind::WidgetCall, "/main.yuck", "bar", 93),
+                (ReferenceKind::Definition, "/widgets.yuck", "bar", 11),
+            ]
+        );
+        assert_eq!(
+            locations(&workspace, "/widgets.yuck", WIDGETS, "icon", 0),
+            vec![
+                (ReferenceKind::ArgName, "/main.yuck", "icon", 98),
+                (ReferenceKind::Definition, "/widgets.yuck", "icon", 17),
+                (ReferenceKind::Interpolation, "/widgets.yuck", "icon", 69),
+                (ReferenceKind::Variable, "/widgets.yuck", "icon", 227),
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowed_variable() {
+        let workspace = workspace();
+        let binding = WIDGETS.find("muted in").unwrap();
+
+        assert_eq!(
+            locations(&workspace, "/widgets.yuck", WIDGETS, "muted in", 0),
+            vec![
+                (ReferenceKind::Definition, "/widgets.yuck", "muted", binding),
+                (ReferenceKind::Variable, "/widgets.yuck", "muted", 181),
+            ]
+        );
+    }
+
+    #[test]
+    fn at_offset() {
+        let workspace = workspace();
+        let offset = WIDGETS.find("{volume >").unwrap() + 1;
+
+        let references = references_at(&workspace, Path::new("/widgets.yuck"), offset, false);
+        assert_eq!(
+            references
+                .iter()
+                .map(|reference| (reference.kind, reference.path.to_str().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ReferenceKind::Variable, "/main.yuck"),
+                (ReferenceKind::Interpolation, "/widgets.yuck"),
+                (ReferenceKind::Variable, "/widgets.yuck"),
+                (ReferenceKind::Variable, "/widgets.yuck"),
+                (ReferenceKind::Variable, "/widgets.yuck"),
+            ]
+        );
+        assert_eq!(
+            references_at(&workspace, Path::new("/widgets.yuck"), 0, true),
+            Vec::new()
+        );
+    }
+
+    fn locations<'ws>(
+        workspace: &'ws Workspace,
+        path: &str,
+        source: &str,
+        needle: &str,
+        skip: usize,
+    ) -> Vec<(ReferenceKind, &'ws str, &'ws str, usize)> {
+        let offset = source.find(needle).unwrap() + skip;
+        let symbol_ref = symbols::symbol_at(workspace, Path::new(path), offset).unwrap();
+        let definition = symbols::definition_of(workspace, Path::new(path), &symbol_ref).unwrap();
+
+        references(workspace, &definition)
+            .into_iter()
+            .map(|reference| {
+                let source = &workspace.get(reference.path).unwrap().source;
+                (
+                    reference.kind,
+                    reference.path.to_str().unwrap(),
+                    &source[reference.span.clone()],
+                    reference.span.start,
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,350 @@
+//! Renaming of a definition together with all of its references in the unit.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    ast::lookup,
+    lexer::{Lexer, Mode, Tok},
+};
+
+use super::{
+    references::{self, Reference},
+    schema::WidgetSchema,
+    symbols::{self, Definition, DefinitionKind},
+    Workspace,
+};
+
+/// A replacement of the text at `span` in the file at `path`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub path: PathBuf,
+    pub span: Range<usize>,
+    pub new_text: String,
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum RenameError {
+    #[error("there is no widget or variable to rename at this position")]
+    NoDefinition,
+    #[error("`{0}` is not a valid name")]
+    InvalidName(String),
+    #[error("`{0}` is the name of a built-in widget")]
+    BuiltinWidget(String),
+    #[error("`{name}` is already defined")]
+    AlreadyDefined {
+        name: String,
+        path: PathBuf,
+        span: Range<usize>,
+    },
+    #[error("renaming to `{name}` would change what this reference refers to")]
+    ChangesMeaning {
+        name: String,
+        path: PathBuf,
+        span: Range<usize>,
+    },
+}
+
+impl RenameError {
+    /// Returns the location in the unit the error was caused by, if any.
+    pub fn location(&self) -> Option<(&Path, Range<usize>)> {
+        match self {
+            Self::AlreadyDefined { path, span, .. } | Self::ChangesMeaning { path, span, .. } => {
+                Some((path, span.clone()))
+            }
+            Self::NoDefinition | Self::InvalidName(_) | Self::BuiltinWidget(_) => None,
+        }
+    }
+}
+
+/// Returns the edits that rename the definition of the symbol at `offset`, and all of its
+/// references, to `new_name`. Refuses renames that would clash with another definition or make
+/// any reference resolve differently.
+pub fn rename(
+    workspace: &Workspace,
+    path: &Path,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<TextEdit>, RenameError> {
+    let definition = symbols::symbol_at(workspace, path, offset)
+        .and_then(|symbol_ref| symbols::definition_of(workspace, path, &symbol_ref))
+        .ok_or(RenameError::NoDefinition)?;
+
+    // Variables are referenced from expressions, where e.g. `a-b` is a subtraction.
+    let mode = if definition.kind.is_variable() {
+        Mode::Expr
+    } else {
+        Mode::SExpr
+    };
+    let tokens: Vec<_> = Lexer::with_mode(new_name, mode).collect();
+    if tokens != [Ok((0, Tok::LiteralSymbol(new_name), new_name.len()))] {
+        return Err(RenameError::InvalidName(new_name.to_owned()));
+    }
+    if definition.name.1 .0 == new_name {
+        return Ok(Vec::new());
+    }
+
+    check_conflicts(workspace, &definition, new_name)?;
+
+    let references = references::references(workspace, &definition);
+    let edits: Vec<_> = references
+        .iter()
+        .map(|reference| TextEdit {
+            path: reference.path.to_owned(),
+            span: reference.span.clone(),
+            new_text: new_name.to_owned(),
+        })
+        .collect();
+
+    check_meaning(workspace, &definition, &references, &edits, new_name)?;
+
+    Ok(edits)
+}
+
+/// Checks that no other definition is already named `new_name`.
+fn check_conflicts(
+    workspace: &Workspace,
+    definition: &Definition,
+    new_name: &str,
+) -> Result<(), RenameError> {
+    let already_defined = |other: &Definition| RenameError::AlreadyDefined {
+        name: new_name.to_owned(),
+        path: other.path.to_owned(),
+        span: other.name.0..other.name.2,
+    };
+
+    match definition.kind {
+        DefinitionKind::DefWidget if WidgetSchema::find(new_name).is_some() => {
+            Err(RenameError::BuiltinWidget(new_name.to_owned()))
+        }
+        DefinitionKind::Param => {
+            let file = &workspace.files[definition.path];
+            let nodes = lookup::node_path_at(&file.ast, definition.node.0..definition.node.2);
+
+            match symbols::enclosing_binding(definition.path, &nodes, new_name) {
+                Some(other) => Err(already_defined(&other)),
+                None => Ok(()),
+            }
+        }
+        DefinitionKind::ForLoopBinding => Ok(()),
+        kind => {
+            // Widgets, windows and variables have separate namespaces.
+            let namespace = |kind: DefinitionKind| match kind {
+                DefinitionKind::DefWidget | DefinitionKind::DefWindow => Some(kind),
+                kind => kind.is_variable().then_some(DefinitionKind::DefVar),
+            };
+
+            match symbols::definitions(workspace, definition.path)
+                .iter()
+                .find(|other| {
+                    namespace(other.kind) == namespace(kind) && other.name.1 .0 == new_name
+                }) {
+                Some(other) => Err(already_defined(other)),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Applies the edits to a copy of the workspace and checks that exactly the renamed references
+/// resolve to the renamed definition.
+fn check_meaning(
+    workspace: &Workspace,
+    definition: &Definition,
+    references: &[Reference],
+    edits: &[TextEdit],
+    new_name: &str,
+) -> Result<(), RenameError> {
+    let delta = new_name.len() as isize - definition.name.1 .0.len() as isize;
+    // The edits of a file are sorted, so every edit moves the text behind it by `delta`.
+    let edits_before = |path: &Path, offset: usize| {
+        edits
+            .iter()
+            .filter(|edit| edit.path == path && edit.span.start < offset)
+            .count() as isize
+    };
+    let to_new = |path: &Path, offset: usize| {
+        (offset as isize + delta * edits_before(path, offset)) as usize
+    };
+
+    let mut renamed = workspace.clone();
+    for path in workspace.unit(definition.path) {
+        let mut source = workspace.files[path].source.clone();
+        for edit in edits.iter().rev().filter(|edit| edit.path == path) {
+            source.replace_range(edit.span.clone(), &edit.new_text);
+        }
+        renamed.insert(path, source);
+    }
+
+    let expected: Vec<_> = references
+        .iter()
+        .map(|reference| {
+            let start = to_new(reference.path, reference.span.start);
+            (reference.path, start..start + new_name.len())
+        })
+        .collect();
+
+    let name_start = to_new(definition.path, definition.name.0);
+    let actual: Vec<_> = symbols::symbol_at(&renamed, definition.path, name_start)
+        .and_then(|symbol_ref| symbols::definition_of(&renamed, definition.path, &symbol_ref))
+        .map(|definition| references::references(&renamed, &definition))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|reference| (reference.path, reference.span))
+        .collect();
+
+    // A reference that no longer resolves to the definition.
+    if let Some(reference) = references
+        .iter()
+        .zip(&expected)
+        .find(|(_, expected)| !actual.contains(expected))
+        .map(|(reference, _)| reference)
+    {
+        return Err(RenameError::ChangesMeaning {
+            name: new_name.to_owned(),
+            path: reference.path.to_owned(),
+            span: reference.span.clone(),
+        });
+    }
+
+    // A reference to something else that would now resolve to the definition. It was not edited,
+    // so it is found in the original source by undoing the shift of the edits in front of it.
+    if let Some((path, span)) = actual.iter().find(|actual| !expected.contains(actual)) {
+        let shifted = expected
+            .iter()
+            .filter(|(other, other_span)| other == path && other_span.start < span.start)
+            .count() as isize;
+        let start = (span.start as isize - delta * shifted) as usize;
+
+        return Err(RenameError::ChangesMeaning {
+            name: new_name.to_owned(),
+            path: path.to_path_buf(),
+            span: start..start + new_name.len(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::workspace::fixture::{workspace, MAIN, WIDGETS};
+
+    #[test]
+    fn variable_across_files() {
+        let (workspace, edits) = test("/main.yuck", MAIN, "volume", "level");
+
+        assert_eq!(
+            apply(&workspace, &edits),
+            vec![
+                MAIN.replace("volume", "level"),
+                WIDGETS.replace("volume", "level"),
+            ]
+        );
+    }
+
+    #[test]
+    fn widget_and_param() {
+        let (workspace, edits) = test("/main.yuck", MAIN, "bar", "status-bar");
+        assert_eq!(
+            apply(&workspace, &edits),
+            vec![
+                MAIN.replace("bar", "status-bar"),
+                WIDGETS.replace("bar", "status-bar"),
+            ]
+        );
+
+        let (workspace, edits) = test("/main.yuck", MAIN, ":icon", "symbol");
+        assert_eq!(
+            apply(&workspace, &edits),
+            vec![
+                MAIN.replace("icon", "symbol"),
+                WIDGETS.replace("icon", "symbol"),
+            ]
+        );
+    }
+
+    #[test]
+    fn already_defined() {
+        let err = test_err("/main.yuck", MAIN, "volume", "muted");
+        assert_eq!(err.to_string(), "`muted` is already defined");
+        assert_eq!(err.location(), Some((Path::new("/main.yuck"), 52..57)));
+
+        assert_eq!(
+            test_err("/widgets.yuck", WIDGETS, "bar", "label"),
+            RenameError::BuiltinWidget("label".to_owned())
+        );
+    }
+
+    #[test]
+    fn captured_reference() {
+        // `icon` would refer to the parameter instead of the global variable.
+        let err = test_err("/main.yuck", MAIN, "volume", "icon");
+        let start = WIDGETS.find("${volume").unwrap() + 2;
+        assert_eq!(
+            err.location(),
+            Some((Path::new("/widgets.yuck"), start..start + 6))
+        );
+
+        // `volume` would refer to the renamed binding instead of the global variable.
+        let err = test_err("/widgets.yuck", WIDGETS, "item ", "volume");
+        let start = WIDGETS.find(":tooltip volume").unwrap() + 9;
+        assert_eq!(
+            err.location(),
+            Some((Path::new("/widgets.yuck"), start..start + 6))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            test_err("/main.yuck", MAIN, "volume", "a b"),
+            RenameError::InvalidName("a b".to_owned())
+        );
+        // A hyphen would be a subtraction in the expressions referencing the variable.
+        assert_eq!(
+            test_err("/main.yuck", MAIN, "volume", "main-volume"),
+            RenameError::InvalidName("main-volume".to_owned())
+        );
+        assert_eq!(
+            test_err("/main.yuck", MAIN, "defvar", "foo"),
+            RenameError::NoDefinition
+        );
+    }
+
+    fn test(path: &str, source: &str, needle: &str, new_name: &str) -> (Workspace, Vec<TextEdit>) {
+        let workspace = workspace();
+        let offset = source.find(needle).unwrap() + needle.starts_with(':') as usize;
+        let edits = rename(&workspace, Path::new(path), offset, new_name).unwrap();
+
+        (workspace, edits)
+    }
+
+    fn test_err(path: &str, source: &str, needle: &str, new_name: &str) -> RenameError {
+        let workspace = workspace();
+        let offset = source.find(needle).unwrap();
+
+        rename(&workspace, Path::new(path), offset, new_name).unwrap_err()
+    }
+
+    /// Returns the sources of all files after applying `edits`.
+    fn apply(workspace: &Workspace, edits: &[TextEdit]) -> Vec<String> {
+        workspace
+            .files()
+            .map(|(path, file)| {
+                let mut source = file.source.clone();
+                for edit in edits.iter().rev().filter(|edit| edit.path == path) {
+                    source.replace_range(edit.span.clone(), &edit.new_text);
+                }
+                source
+            })
+            .collect()
+    }
+}
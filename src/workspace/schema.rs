@@ -0,0 +1,264 @@
+//! The names Eww provides without a definition: built-in widgets and their properties, the
+//! properties of the definition forms, expression functions and magic variables.
+
+/// A built-in widget and the properties specific to it. Every widget additionally accepts the
+/// [`COMMON_PROPERTIES`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WidgetSchema {
+    pub name: &'static str,
+    pub properties: &'static [&'static str],
+}
+
+impl WidgetSchema {
+    pub fn find(name: &str) -> Option<&'static Self> {
+        WIDGETS.iter().find(|widget| widget.name == name)
+    }
+
+    pub fn all_properties(&self) -> impl Iterator<Item = &'static str> {
+        self.properties.iter().chain(COMMON_PROPERTIES).copied()
+    }
+}
+
+pub const COMMON_PROPERTIES: &[&str] = &[
+    "class", "valign", "halign", "vexpand", "hexpand", "width", "height", "active", "tooltip",
+    "visible", "style", "css",
+];
+
+pub const WIDGETS: &[WidgetSchema] = &[
+    WidgetSchema {
+        name: "box",
+        properties: &["spacing", "orientation", "space-evenly"],
+    },
+    WidgetSchema {
+        name: "button",
+        properties: &["timeout", "onclick", "onmiddleclick", "onrightclick"],
+    },
+    WidgetSchema {
+        name: "calendar",
+        properties: &[
+            "day",
+            "month",
+            "year",
+            "show-details",
+            "show-heading",
+            "show-day-names",
+            "show-week-numbers",
+            "onclick",
+            "timeout",
+        ],
+    },
+    WidgetSchema {
+        name: "centerbox",
+        properties: &["orientation"],
+    },
+    WidgetSchema {
+        name: "checkbox",
+        properties: &["checked", "timeout", "onchecked", "onunchecked"],
+    },
+    WidgetSchema {
+        name: "circular-progress",
+        properties: &["value", "start-at", "thickness", "clockwise"],
+    },
+    WidgetSchema {
+        name: "color-button",
+        properties: &["use-alpha", "onchange", "timeout"],
+    },
+    WidgetSchema {
+        name: "color-chooser",
+        properties: &["use-alpha", "onchange", "timeout"],
+    },
+    WidgetSchema {
+        name: "combo-box-text",
+        properties: &["items", "timeout", "onchange"],
+    },
+    WidgetSchema {
+        name: "eventbox",
+        properties: &[
+            "timeout",
+            "onscroll",
+            "onhover",
+            "onhoverlost",
+            "cursor",
+            "ondropped",
+            "dragvalue",
+            "dragtype",
+            "onclick",
+            "onmiddleclick",
+            "onrightclick",
+        ],
+    },
+    WidgetSchema {
+        name: "expander",
+        properties: &["name", "expanded"],
+    },
+    WidgetSchema {
+        name: "graph",
+        properties: &[
+            "value",
+            "thickness",
+            "time-range",
+            "min",
+            "max",
+            "dynamic",
+            "line-style",
+            "flip-x",
+            "flip-y",
+            "vertical",
+        ],
+    },
+    WidgetSchema {
+        name: "image",
+        properties: &[
+            "path",
+            "image-width",
+            "image-height",
+            "preserve-aspect-ratio",
+            "fill-svg",
+            "icon",
+            "icon-size",
+        ],
+    },
+    WidgetSchema {
+        name: "input",
+        properties: &["value", "onchange", "timeout", "onaccept", "password"],
+    },
+    WidgetSchema {
+        name: "label",
+        properties: &[
+            "text",
+            "truncate",
+            "limit-width",
+            "truncate-left",
+            "show-truncated",
+            "unindent",
+            "markup",
+            "wrap",
+            "angle",
+            "gravity",
+            "xalign",
+            "yalign",
+            "justify",
+            "wrap-mode",
+            "lines",
+        ],
+    },
+    WidgetSchema {
+        name: "overlay",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "progress",
+        properties: &["flipped", "value", "orientation"],
+    },
+    WidgetSchema {
+        name: "revealer",
+        properties: &["transition", "reveal", "duration"],
+    },
+    WidgetSchema {
+        name: "scale",
+        properties: &[
+            "flipped",
+            "marks",
+            "draw-value",
+            "value-pos",
+            "round-digits",
+            "value",
+            "min",
+            "max",
+            "timeout",
+            "onchange",
+            "orientation",
+        ],
+    },
+    WidgetSchema {
+        name: "scroll",
+        properties: &["hscroll", "vscroll"],
+    },
+    WidgetSchema {
+        name: "stack",
+        properties: &["selected", "transition", "same-size"],
+    },
+    WidgetSchema {
+        name: "systray",
+        properties: &[
+            "spacing",
+            "orientation",
+            "space-evenly",
+            "icon-size",
+            "prepend-new",
+        ],
+    },
+    WidgetSchema {
+        name: "tooltip",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "transform",
+        properties: &[
+            "rotate",
+            "transform-origin-x",
+            "transform-origin-y",
+            "translate-x",
+            "translate-y",
+            "scale-x",
+            "scale-y",
+        ],
+    },
+];
+
+pub const DEF_LISTEN_PROPERTIES: &[&str] = &["initial"];
+
+pub const DEF_POLL_PROPERTIES: &[&str] = &["interval", "initial", "run-while"];
+
+pub const DEF_WINDOW_PROPERTIES: &[&str] = &[
+    "monitor",
+    "geometry",
+    "stacking",
+    "wm-ignore",
+    "reserve",
+    "windowtype",
+    "exclusive",
+    "focusable",
+    "namespace",
+];
+
+pub const LITERAL_PROPERTIES: &[&str] = &["content"];
+
+pub const FUNCTIONS: &[&str] = &[
+    "round",
+    "min",
+    "max",
+    "powi",
+    "powf",
+    "sin",
+    "cos",
+    "tan",
+    "cot",
+    "degtorad",
+    "radtodeg",
+    "replace",
+    "search",
+    "matches",
+    "captures",
+    "strlength",
+    "substring",
+    "arraylength",
+    "objectlength",
+    "jq",
+    "get_env",
+    "formattime",
+    "formatbytes",
+];
+
+pub const MAGIC_VARIABLES: &[&str] = &[
+    "EWW_TEMPS",
+    "EWW_RAM",
+    "EWW_DISK",
+    "EWW_BATTERY",
+    "EWW_CPU",
+    "EWW_NET",
+    "EWW_TIME",
+    "EWW_CONFIG_DIR",
+    "EWW_CMD",
+    "EWW_EXECUTABLE",
+];
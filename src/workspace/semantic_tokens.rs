@@ -0,0 +1,334 @@
+//! Classification of every token of a file for syntax highlighting.
+
+use std::{collections::HashMap, ops::Range, path::Path};
+
+use crate::{
+    ast::lookup,
+    lexer::{Lexer, Mode, Tok},
+    spanned::Spanned,
+};
+
+use super::{
+    schema::WidgetSchema,
+    symbols::{self, DefinitionKind, SymbolRef},
+    Workspace,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Keyword,
+    /// A user defined widget or window.
+    Widget,
+    BuiltinWidget,
+    Property,
+    Variable,
+    Function,
+    String,
+    Number,
+    Operator,
+    Comment,
+    /// The `${` and `}` delimiting an interpolation in a string literal.
+    Interpolation,
+    /// Delimiters, commas and the colons in front of property names.
+    Punctuation,
+}
+
+impl TokenKind {
+    pub const ALL: &'static [Self] = &[
+        Self::Keyword,
+        Self::Widget,
+        Self::BuiltinWidget,
+        Self::Property,
+        Self::Variable,
+        Self::Function,
+        Self::String,
+        Self::Number,
+        Self::Operator,
+        Self::Comment,
+        Self::Interpolation,
+        Self::Punctuation,
+    ];
+
+    /// A kebab-case name, e.g. for CSS classes.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Widget => "widget",
+            Self::BuiltinWidget => "builtin-widget",
+            Self::Property => "property",
+            Self::Variable => "variable",
+            Self::Function => "function",
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Operator => "operator",
+            Self::Comment => "comment",
+            Self::Interpolation => "interpolation",
+            Self::Punctuation => "punctuation",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub span: Range<usize>,
+    pub kind: TokenKind,
+}
+
+/// Classifies the tokens of the file at `path` in source order. String literals are split into
+/// string and interpolation segments, with the tokens inside interpolations classified like
+/// expressions. Classification stops at the first lexical error.
+pub fn semantic_tokens(workspace: &Workspace, path: &Path) -> Vec<SemanticToken> {
+    let Some((path, file)) = workspace.files.get_key_value(path) else {
+        return Vec::new();
+    };
+
+    let mut symbol_kinds = HashMap::new();
+    lookup::for_each_node_path(&file.ast, |nodes| {
+        if let Some(symbol_ref) = symbols::classify(path, nodes) {
+            let Spanned(l, name, _) = symbol_ref.name();
+            symbol_kinds.insert(l, symbol_kind(&symbol_ref, &name.0));
+        }
+    });
+
+    let mut tokens = Vec::new();
+    let lexed: Vec<_> = Lexer::new(&file.source).map_while(Result::ok).collect();
+
+    for (idx, (l, tok, r)) in lexed.iter().enumerate() {
+        let kind = match tok {
+            Tok::LiteralString(_) => {
+                string_tokens(&file.source, *l..*r, &mut tokens);
+                continue;
+            }
+            // `in` is a symbol to the lexer, the parser only checks its name.
+            Tok::LiteralSymbol("in")
+                if matches!(
+                    idx.checked_sub(2).map(|idx| &lexed[idx].1),
+                    Some(Tok::KeywordFor)
+                ) =>
+            {
+                TokenKind::Keyword
+            }
+            Tok::LiteralSymbol(_) => symbol_kinds.get(l).copied().unwrap_or(TokenKind::Variable),
+            tok => token_kind(tok),
+        };
+
+        tokens.push(SemanticToken { span: *l..*r, kind });
+    }
+
+    tokens
+}
+
+fn symbol_kind(symbol_ref: &SymbolRef, name: &str) -> TokenKind {
+    match symbol_ref {
+        SymbolRef::Definition(definition) => match definition.kind {
+            DefinitionKind::DefWidget | DefinitionKind::DefWindow => TokenKind::Widget,
+            _ => TokenKind::Variable,
+        },
+        SymbolRef::Widget(_) if WidgetSchema::find(name).is_some() => TokenKind::BuiltinWidget,
+        SymbolRef::Widget(_) => TokenKind::Widget,
+        SymbolRef::ArgName { .. } | SymbolRef::Property(_) => TokenKind::Property,
+        SymbolRef::Function(_) => TokenKind::Function,
+        SymbolRef::Variable { .. } => TokenKind::Variable,
+    }
+}
+
+/// Classifies a token without semantic information.
+fn token_kind(tok: &Tok) -> TokenKind {
+    match tok {
+        Tok::Comment(_) => TokenKind::Comment,
+        Tok::DelimiterComma
+        | Tok::DelimiterLeftBrace
+        | Tok::DelimiterLeftBracket
+        | Tok::DelimiterLeftParen
+        | Tok::DelimiterRightBrace
+        | Tok::DelimiterRightBracket
+        | Tok::DelimiterRightParen
+        | Tok::PunctuationColon => TokenKind::Punctuation,
+        Tok::KeywordDefListen
+        | Tok::KeywordDefPoll
+        | Tok::KeywordDefVar
+        | Tok::KeywordDefWidget
+        | Tok::KeywordDefWindow
+        | Tok::KeywordFalse
+        | Tok::KeywordFor
+        | Tok::KeywordInclude
+        | Tok::KeywordLiteral
+        | Tok::KeywordTrue => TokenKind::Keyword,
+        Tok::LiteralNumber(_) => TokenKind::Number,
+        Tok::LiteralString(_) => TokenKind::String,
+        Tok::LiteralSymbol(_) => TokenKind::Variable,
+        Tok::OperatorComparisonEqual
+        | Tok::OperatorComparisonGreaterThan
+        | Tok::OperatorComparisonGreaterThanOrEquals
+        | Tok::OperatorComparisonLessThan
+        | Tok::OperatorComparisonLessThanOrEquals
+        | Tok::OperatorComparisonNotEqual
+        | Tok::OperatorLogicalAnd
+        | Tok::OperatorLogicalNot
+        | Tok::OperatorLogicalOr
+        | Tok::OperatorMathAddition
+        | Tok::OperatorMathDivision
+        | Tok::OperatorMathModulo
+        | Tok::OperatorMathMultiplication
+        | Tok::OperatorMathSubtraction
+        | Tok::PunctuationQuestionMark => TokenKind::Operator,
+    }
+}
+
+/// Splits the string literal spanning `string` into string segments and interpolations.
+fn string_tokens(source: &str, string: Range<usize>, tokens: &mut Vec<SemanticToken>) {
+    let literal = &source[string.clone()];
+    let mut push = |span: Range<usize>, kind| {
+        if !span.is_empty() {
+            tokens.push(SemanticToken {
+                span: string.start + span.start..string.start + span.end,
+                kind,
+            });
+        }
+    };
+
+    let mut rest = 0;
+    while let Some(open) = literal[rest..].find("${").map(|idx| rest + idx) {
+        let Some(close) = literal[open..].find('}').map(|idx| open + idx) else {
+            break;
+        };
+
+        push(rest..open, TokenKind::String);
+        push(open..open + 2, TokenKind::Interpolation);
+
+        let inner: Vec<_> = Lexer::with_mode(&literal[open + 2..close], Mode::Expr)
+            .map_while(Result::ok)
+            .collect();
+        for (idx, (l, tok, r)) in inner.iter().enumerate() {
+            let kind = match tok {
+                Tok::LiteralSymbol(_)
+                    if matches!(inner.get(idx + 1), Some((_, Tok::DelimiterLeftParen, _))) =>
+                {
+                    TokenKind::Function
+                }
+                tok => token_kind(tok),
+            };
+            push(open + 2 + l..open + 2 + r, kind);
+        }
+
+        push(close..close + 1, TokenKind::Interpolation);
+        rest = close + 1;
+    }
+
+    push(rest..literal.len(), TokenKind::String);
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn widgets_and_properties() {
+        assert_eq!(
+            test("(defwidget bar [icon] (label :text icon) (clock))"),
+            vec![
+                ("(", TokenKind::Punctuation),
+                ("defwidget", TokenKind::Keyword),
+                ("bar", TokenKind::Widget),
+                ("[", TokenKind::Punctuation),
+                ("icon", TokenKind::Variable),
+                ("]", TokenKind::Punctuation),
+                ("(", TokenKind::Punctuation),
+                ("label", TokenKind::BuiltinWidget),
+                (":", TokenKind::Punctuation),
+                ("text", TokenKind::Property),
+                ("icon", TokenKind::Variable),
+                (")", TokenKind::Punctuation),
+                ("(", TokenKind::Punctuation),
+                ("clock", TokenKind::Widget),
+                (")", TokenKind::Punctuation),
+                (")", TokenKind::Punctuation),
+            ]
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(
+            test("; volume\n(defpoll volume :interval \"1s\" \"amixer\")\n(defwindow main (for v in vs { round(v, 0) > 1 }))"),
+            vec![
+                ("; volume", TokenKind::Comment),
+                ("(", TokenKind::Punctuation),
+                ("defpoll", TokenKind::Keyword),
+                ("volume", TokenKind::Variable),
+                (":", TokenKind::Punctuation),
+                ("interval", TokenKind::Property),
+                ("\"1s\"", TokenKind::String),
+                ("\"amixer\"", TokenKind::String),
+                (")", TokenKind::Punctuation),
+                ("(", TokenKind::Punctuation),
+                ("defwindow", TokenKind::Keyword),
+                ("main", TokenKind::Widget),
+                ("(", TokenKind::Punctuation),
+                ("for", TokenKind::Keyword),
+                ("v", TokenKind::Variable),
+                ("in", TokenKind::Keyword),
+                ("vs", TokenKind::Variable),
+                ("{", TokenKind::Punctuation),
+                ("round", TokenKind::Function),
+                ("(", TokenKind::Punctuation),
+                ("v", TokenKind::Variable),
+                (",", TokenKind::Punctuation),
+                ("0", TokenKind::Number),
+                (")", TokenKind::Punctuation),
+                (">", TokenKind::Operator),
+                ("1", TokenKind::Number),
+                ("}", TokenKind::Punctuation),
+                (")", TokenKind::Punctuation),
+                (")", TokenKind::Punctuation),
+            ]
+        );
+    }
+
+    #[test]
+    fn interpolations() {
+        assert_eq!(
+            test(r#"(defwidget bar [] (label :text "${round(volume, 0)}%"))"#)[9..],
+            [
+                ("\"", TokenKind::String),
+                ("${", TokenKind::Interpolation),
+                ("round", TokenKind::Function),
+                ("(", TokenKind::Punctuation),
+                ("volume", TokenKind::Variable),
+                (",", TokenKind::Punctuation),
+                ("0", TokenKind::Number),
+                (")", TokenKind::Punctuation),
+                ("}", TokenKind::Interpolation),
+                ("%\"", TokenKind::String),
+                (")", TokenKind::Punctuation),
+                (")", TokenKind::Punctuation),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_lexical_error() {
+        assert_eq!(
+            test("(defvar a \"b"),
+            vec![
+                ("(", TokenKind::Punctuation),
+                ("defvar", TokenKind::Keyword),
+                ("a", TokenKind::Variable),
+            ]
+        );
+    }
+
+    fn test(inp: &str) -> Vec<(&str, TokenKind)> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut workspace = Workspace::new();
+        workspace.insert("/main.yuck", inp);
+
+        semantic_tokens(&workspace, Path::new("/main.yuck"))
+            .into_iter()
+            .map(|token| (&inp[token.span], token.kind))
+            .collect()
+    }
+}
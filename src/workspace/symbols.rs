@@ -0,0 +1,521 @@
+//! Classification of the symbols in a [`Workspace`] and resolution to their definitions.
+
+use std::{ops::Range, path::Path};
+
+use crate::{
+    ast::{
+        atom,
+        def_widget::DefWidget,
+        def_window::DefWindow,
+        lookup::{self, NodeRef},
+        scope::Resolution,
+        symbol::Symbol,
+        top_level_expr::TopLevelExpr,
+    },
+    spanned::Spanned,
+};
+
+use super::Workspace;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefinitionKind {
+    DefListen,
+    DefPoll,
+    DefVar,
+    DefWidget,
+    DefWindow,
+    ForLoopBinding,
+    Param,
+}
+
+impl DefinitionKind {
+    /// Whether the definition can be referenced from expressions.
+    pub fn is_variable(self) -> bool {
+        matches!(
+            self,
+            Self::DefListen | Self::DefPoll | Self::DefVar | Self::ForLoopBinding | Self::Param
+        )
+    }
+}
+
+/// A name introduced by the source. `node` is the whole definition, e.g. the `defwidget` or the
+/// parameter, whereas `name` only covers the introduced symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Definition<'ws> {
+    pub kind: DefinitionKind,
+    pub path: &'ws Path,
+    pub name: &'ws Spanned<Symbol<'ws>>,
+    pub node: Spanned<NodeRef<'ws>>,
+}
+
+/// The role of a symbol in the source.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SymbolRef<'ws> {
+    /// The name of a definition.
+    Definition(Definition<'ws>),
+    /// The name of a called widget, `box` in `(box)`.
+    Widget(Spanned<&'ws Symbol<'ws>>),
+    /// The name of an argument passed to a widget, `text` in `(label :text "")`.
+    ArgName {
+        widget: &'ws Symbol<'ws>,
+        name: Spanned<&'ws Symbol<'ws>>,
+    },
+    /// The name of a called function, `round` in `round(volume, 0)`.
+    Function(Spanned<&'ws Symbol<'ws>>),
+    /// The name of a property of a definition, `interval` in `(defpoll time :interval "1s" "")`.
+    Property(Spanned<&'ws Symbol<'ws>>),
+    /// A reference to a variable. `binding` is the enclosing parameter or `for` loop binding the
+    /// reference resolves to, `None` for references to global variables.
+    Variable {
+        name: Spanned<&'ws Symbol<'ws>>,
+        binding: Option<Definition<'ws>>,
+    },
+}
+
+impl<'ws> SymbolRef<'ws> {
+    pub fn name(&self) -> Spanned<&'ws Symbol<'ws>> {
+        match self {
+            Self::Definition(definition) => {
+                let Spanned(l, name, r) = definition.name;
+                (*l, name, *r).into()
+            }
+            Self::Widget(name)
+            | Self::ArgName { name, .. }
+            | Self::Function(name)
+            | Self::Property(name)
+            | Self::Variable { name, .. } => name.clone(),
+        }
+    }
+}
+
+/// Returns the top level definitions of every file in the unit of `path`.
+pub fn definitions<'ws>(workspace: &'ws Workspace, path: &Path) -> Vec<Definition<'ws>> {
+    workspace
+        .unit(path)
+        .into_iter()
+        .flat_map(|path| {
+            workspace.files[path]
+                .ast
+                .iter()
+                .filter_map(move |node| top_level_definition(path, node))
+        })
+        .collect()
+}
+
+/// Returns the role of the symbol at `offset`, if there is a symbol.
+pub fn symbol_at<'ws>(
+    workspace: &'ws Workspace,
+    path: &Path,
+    offset: usize,
+) -> Option<SymbolRef<'ws>> {
+    let (path, file) = workspace.files.get_key_value(path)?;
+    let nodes = lookup::node_path_at(&file.ast, offset..offset);
+
+    classify(path, &nodes)
+}
+
+/// Returns the role of the innermost node of `nodes` if it is a symbol. `nodes` is a path from a
+/// top level node of the file at `path`, as returned by [`lookup::node_path_at`].
+pub fn classify<'ws>(path: &'ws Path, nodes: &[Spanned<NodeRef<'ws>>]) -> Option<SymbolRef<'ws>> {
+    let Some(Spanned(l, NodeRef::Symbol(symbol), r)) = nodes.last().cloned() else {
+        return None;
+    };
+    let name = Spanned::from((l, symbol, r));
+    let parent = nodes.len().checked_sub(2).map(|idx| &nodes[idx]);
+    let is_name = |candidate: &Spanned<Symbol>| std::ptr::eq(&candidate.1, symbol);
+
+    let definition = |kind, name_node: &'ws Spanned<Symbol>, node: &Spanned<NodeRef<'ws>>| {
+        SymbolRef::Definition(Definition {
+            kind,
+            path,
+            name: name_node,
+            node: node.clone(),
+        })
+    };
+
+    let symbol_ref = match parent.map(|parent| (parent, parent.1)) {
+        Some((parent, NodeRef::DefListen(def_listen))) if is_name(&def_listen.name) => {
+            definition(DefinitionKind::DefListen, &def_listen.name, parent)
+        }
+        Some((parent, NodeRef::DefPoll(def_poll))) if is_name(&def_poll.name) => {
+            definition(DefinitionKind::DefPoll, &def_poll.name, parent)
+        }
+        Some((parent, NodeRef::DefVar(def_var))) if is_name(&def_var.name) => {
+            definition(DefinitionKind::DefVar, &def_var.name, parent)
+        }
+        Some((parent, NodeRef::DefWidget(def_widget))) if is_name(&def_widget.name) => {
+            definition(DefinitionKind::DefWidget, &def_widget.name, parent)
+        }
+        Some((parent, NodeRef::DefWindow(def_window))) if is_name(&def_window.name) => {
+            definition(DefinitionKind::DefWindow, &def_window.name, parent)
+        }
+        Some((parent, NodeRef::DefWidgetParam(param))) => {
+            definition(DefinitionKind::Param, &param.name, parent)
+        }
+        Some((_, NodeRef::ForLoop(for_loop))) if is_name(&for_loop.binding) => {
+            let Spanned(l, binding, r) = &for_loop.binding;
+            definition(
+                DefinitionKind::ForLoopBinding,
+                &for_loop.binding,
+                &(*l, NodeRef::Symbol(binding), *r).into(),
+            )
+        }
+        Some((_, NodeRef::WidgetCall(widget_call))) if is_name(&widget_call.name) => {
+            SymbolRef::Widget(name)
+        }
+        Some((_, NodeRef::WidgetCallArg(arg))) if is_name(&arg.name) => {
+            let Some(NodeRef::WidgetCall(widget_call)) = nodes.get(nodes.len() - 3).map(|n| n.1)
+            else {
+                return None;
+            };
+            SymbolRef::ArgName {
+                widget: &widget_call.name.1,
+                name,
+            }
+        }
+        Some((_, NodeRef::FunctionCall(function_call))) if is_name(&function_call.name) => {
+            SymbolRef::Function(name)
+        }
+        Some((_, NodeRef::DefListenArg(arg))) if is_name(&arg.name) => SymbolRef::Property(name),
+        Some((_, NodeRef::DefPollArg(arg))) if is_name(&arg.name) => SymbolRef::Property(name),
+        Some((_, NodeRef::DefWindowArg(arg))) if is_name(&arg.name) => SymbolRef::Property(name),
+        Some((_, NodeRef::LiteralArg(arg))) if is_name(&arg.name) => SymbolRef::Property(name),
+        _ => SymbolRef::Variable {
+            binding: local_binding(path, &nodes[0], &name),
+            name,
+        },
+    };
+
+    Some(symbol_ref)
+}
+
+/// Returns the definition `symbol_ref` refers to. Definitions resolve to themselves, functions
+/// and properties never resolve.
+pub fn definition_of<'ws>(
+    workspace: &'ws Workspace,
+    path: &Path,
+    symbol_ref: &SymbolRef<'ws>,
+) -> Option<Definition<'ws>> {
+    match symbol_ref {
+        SymbolRef::Definition(definition) => Some(definition.clone()),
+        SymbolRef::Widget(name) => find_global(workspace, path, name.1, |kind| {
+            kind == DefinitionKind::DefWidget
+        }),
+        SymbolRef::ArgName { widget, name } => {
+            let definition = find_global(workspace, path, widget, |kind| {
+                kind == DefinitionKind::DefWidget
+            })?;
+            let NodeRef::DefWidget(def_widget) = definition.node.1 else {
+                return None;
+            };
+
+            def_widget
+                .params
+                .iter()
+                .find(|param| param.1.name.1 == *name.1)
+                .map(|param| param_definition(definition.path, param))
+        }
+        SymbolRef::Function(_) | SymbolRef::Property(_) => None,
+        SymbolRef::Variable {
+            binding: Some(binding),
+            ..
+        } => Some(binding.clone()),
+        SymbolRef::Variable {
+            name,
+            binding: None,
+        } => find_global(workspace, path, name.1, DefinitionKind::is_variable),
+    }
+}
+
+fn find_global<'ws>(
+    workspace: &'ws Workspace,
+    path: &Path,
+    name: &Symbol,
+    is_kind: impl Fn(DefinitionKind) -> bool,
+) -> Option<Definition<'ws>> {
+    definitions(workspace, path)
+        .into_iter()
+        .find(|definition| is_kind(definition.kind) && definition.name.1 == *name)
+}
+
+fn top_level_definition<'ws>(
+    path: &'ws Path,
+    node: &'ws Spanned<TopLevelExpr>,
+) -> Option<Definition<'ws>> {
+    let (kind, name) = match &node.1 {
+        TopLevelExpr::DefListen(def_listen) => (DefinitionKind::DefListen, &def_listen.name),
+        TopLevelExpr::DefPoll(def_poll) => (DefinitionKind::DefPoll, &def_poll.name),
+        TopLevelExpr::DefVar(def_var) => (DefinitionKind::DefVar, &def_var.name),
+        TopLevelExpr::DefWidget(def_widget) => (DefinitionKind::DefWidget, &def_widget.name),
+        TopLevelExpr::DefWindow(def_window) => (DefinitionKind::DefWindow, &def_window.name),
+        TopLevelExpr::Err | TopLevelExpr::Include(_) | TopLevelExpr::Literal(_) => return None,
+    };
+
+    Some(Definition {
+        kind,
+        path,
+        name,
+        node: NodeRef::from_top_level_expr(node)?,
+    })
+}
+
+fn param_definition<'ws>(
+    path: &'ws Path,
+    param: &'ws Spanned<crate::ast::def_widget::DefWidgetParam>,
+) -> Definition<'ws> {
+    let Spanned(l, param, r) = param;
+
+    Definition {
+        kind: DefinitionKind::Param,
+        path,
+        name: &param.name,
+        node: (*l, NodeRef::DefWidgetParam(param), *r).into(),
+    }
+}
+
+/// Resolves `name` against the parameters and `for` loops of the enclosing top level node.
+fn local_binding<'ws>(
+    path: &'ws Path,
+    top_level: &Spanned<NodeRef<'ws>>,
+    name: &Spanned<&'ws Symbol<'ws>>,
+) -> Option<Definition<'ws>> {
+    let (params, resolutions): (&[_], Vec<Resolution>) = match top_level.1 {
+        NodeRef::DefWidget(def_widget) => (&def_widget.params, def_widget.resolutions()),
+        NodeRef::DefWindow(def_window) => (&def_window.params, def_window.resolutions()),
+        _ => return None,
+    };
+
+    let binding = resolutions
+        .into_iter()
+        .find(|resolution| {
+            resolution.reference.0 == name.0
+                && resolution.reference.2 == name.2
+                && resolution.reference.1 == *name.1
+        })?
+        .binding?;
+
+    if let Some(param) = params
+        .iter()
+        .find(|param| std::ptr::eq(&param.1.name, binding))
+    {
+        return Some(param_definition(path, param));
+    }
+
+    let Spanned(l, symbol, r) = binding;
+    Some(Definition {
+        kind: DefinitionKind::ForLoopBinding,
+        path,
+        name: binding,
+        node: (*l, NodeRef::Symbol(symbol), *r).into(),
+    })
+}
+
+/// Returns the `defwidget` named `name` in the unit of `path`.
+pub fn find_widget<'ws>(
+    workspace: &'ws Workspace,
+    path: &Path,
+    name: &Symbol,
+) -> Option<&'ws DefWidget<'ws>> {
+    match find_global(workspace, path, name, |kind| {
+        kind == DefinitionKind::DefWidget
+    })?
+    .node
+    .1
+    {
+        NodeRef::DefWidget(def_widget) => Some(def_widget),
+        _ => None,
+    }
+}
+
+/// Returns the global variable named `name` in the unit of `path`.
+pub fn find_variable<'ws>(
+    workspace: &'ws Workspace,
+    path: &Path,
+    name: &Symbol,
+) -> Option<Definition<'ws>> {
+    find_global(workspace, path, name, DefinitionKind::is_variable)
+}
+
+/// Returns the parameter or `for` loop binding named `name` that is in scope at the innermost
+/// node of `nodes`. Used for references that are not part of the AST, like interpolations.
+pub fn enclosing_binding<'ws>(
+    path: &'ws Path,
+    nodes: &[Spanned<NodeRef<'ws>>],
+    name: &str,
+) -> Option<Definition<'ws>> {
+    nodes.iter().rev().find_map(|node| match node.1 {
+        NodeRef::ForLoop(for_loop) if for_loop.binding.1 .0 == name => {
+            let Spanned(l, symbol, r) = &for_loop.binding;
+            Some(Definition {
+                kind: DefinitionKind::ForLoopBinding,
+                path,
+                name: &for_loop.binding,
+                node: (*l, NodeRef::Symbol(symbol), *r).into(),
+            })
+        }
+        NodeRef::DefWidget(DefWidget { params, .. })
+        | NodeRef::DefWindow(DefWindow { params, .. }) => params
+            .iter()
+            .find(|param| param.1.name.1 .0 == name)
+            .map(|param| param_definition(path, param)),
+        _ => None,
+    })
+}
+
+/// Returns the variables referenced in the `${}` interpolations of the string literal spanning
+/// `string` in `source`, e.g. `volume` in `"${volume}%"`. Function names are skipped.
+pub fn interpolated_symbols(source: &str, string: Range<usize>) -> Vec<Spanned<&str>> {
+    match source.get(string.clone()) {
+        Some(literal) => atom::interpolated_symbols(literal, string.start),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::workspace::fixture::{workspace, MAIN, WIDGETS};
+
+    #[test]
+    fn definitions_of_unit() {
+        let workspace = workspace();
+
+        let names: Vec<_> = definitions(&workspace, Path::new("/main.yuck"))
+            .into_iter()
+            .map(|d| (d.kind, d.path.to_str().unwrap(), d.name.1 .0.as_ref()))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                (DefinitionKind::DefVar, "/main.yuck", "volume"),
+                (DefinitionKind::DefVar, "/main.yuck", "muted"),
+                (DefinitionKind::DefWindow, "/main.yuck", "main"),
+                (DefinitionKind::DefWidget, "/widgets.yuck", "bar"),
+            ]
+        );
+    }
+
+    #[test]
+    fn widget_reference() {
+        let workspace = workspace();
+        let (offset, definition) = resolve(&workspace, "/main.yuck", MAIN, "(bar", 1);
+
+        assert_eq!(definition.kind, DefinitionKind::DefWidget, "at {}", offset);
+        assert_eq!(definition.path, Path::new("/widgets.yuck"));
+        assert_eq!(definition.name.0, 11);
+        assert_eq!((definition.node.0, definition.node.2), (0, WIDGETS.len()));
+    }
+
+    #[test]
+    fn arg_name() {
+        let workspace = workspace();
+        let (_, definition) = resolve(&workspace, "/main.yuck", MAIN, ":icon", 2);
+
+        assert_eq!(definition.kind, DefinitionKind::Param);
+        assert_eq!(definition.path, Path::new("/widgets.yuck"));
+        assert_eq!((definition.node.0, definition.node.2), (16, 21));
+    }
+
+    #[test]
+    fn local_variables() {
+        let workspace = workspace();
+
+        let (_, definition) = resolve(&workspace, "/main.yuck", MAIN, ":screen screen", 8);
+        assert_eq!(definition.kind, DefinitionKind::Param);
+        assert_eq!(definition.name.0, MAIN.find("screen]").unwrap());
+
+        let (_, definition) = resolve(&workspace, "/widgets.yuck", WIDGETS, ": icon", 2);
+        assert_eq!(definition.kind, DefinitionKind::Param);
+        assert_eq!(definition.name.0, 17);
+
+        let (_, definition) = resolve(&workspace, "/widgets.yuck", WIDGETS, "text item", 6);
+        assert_eq!(definition.kind, DefinitionKind::ForLoopBinding);
+        assert_eq!(definition.name.0, WIDGETS.find("item").unwrap());
+    }
+
+    #[test]
+    fn global_variable() {
+        let workspace = workspace();
+        let (_, definition) = resolve(&workspace, "/widgets.yuck", WIDGETS, "{volume >", 1);
+
+        assert_eq!(definition.kind, DefinitionKind::DefVar);
+        assert_eq!(definition.path, Path::new("/main.yuck"));
+    }
+
+    #[test]
+    fn unresolved() {
+        let workspace = workspace();
+
+        let offset = WIDGETS.find("round").unwrap();
+        let symbol_ref = symbol_at(&workspace, Path::new("/widgets.yuck"), offset).unwrap();
+        assert!(matches!(symbol_ref, SymbolRef::Function(_)));
+        assert_eq!(
+            definition_of(&workspace, Path::new("/widgets.yuck"), &symbol_ref),
+            None
+        );
+
+        let offset = WIDGETS.find("items").unwrap();
+        let symbol_ref = symbol_at(&workspace, Path::new("/widgets.yuck"), offset).unwrap();
+        assert!(matches!(
+            symbol_ref,
+            SymbolRef::Variable { binding: None, .. }
+        ));
+        assert_eq!(
+            definition_of(&workspace, Path::new("/widgets.yuck"), &symbol_ref),
+            None
+        );
+    }
+
+    #[test]
+    fn definitions_resolve_to_themselves() {
+        let workspace = workspace();
+        let (_, definition) = resolve(&workspace, "/main.yuck", MAIN, "volume", 0);
+
+        assert_eq!(definition.kind, DefinitionKind::DefVar);
+        assert_eq!(definition.name.0, MAIN.find("volume").unwrap());
+    }
+
+    #[test]
+    fn interpolations() {
+        let source = r#"(label :text "${volume}% ${round(cpu, 0)} ${"#;
+        let string = source.find('"').unwrap()..source.len();
+
+        assert_eq!(
+            interpolated_symbols(source, string),
+            vec![(16, "volume", 22).into(), (33, "cpu", 36).into()]
+        );
+    }
+
+    #[test]
+    fn enclosing_bindings() {
+        let workspace = workspace();
+        let file = workspace.get(Path::new("/widgets.yuck")).unwrap();
+        let offset = WIDGETS.find("text item").unwrap();
+        let nodes = lookup::node_path_at(&file.ast, offset..offset);
+        let path = Path::new("/widgets.yuck");
+
+        let binding = enclosing_binding(path, &nodes, "item").unwrap();
+        assert_eq!(binding.kind, DefinitionKind::ForLoopBinding);
+        let binding = enclosing_binding(path, &nodes, "icon").unwrap();
+        assert_eq!(binding.kind, DefinitionKind::Param);
+        assert_eq!(enclosing_binding(path, &nodes, "volume"), None);
+    }
+
+    fn resolve<'ws>(
+        workspace: &'ws Workspace,
+        path: &str,
+        source: &str,
+        needle: &str,
+        skip: usize,
+    ) -> (usize, Definition<'ws>) {
+        let offset = source.find(needle).unwrap() + skip;
+        let symbol_ref = symbol_at(workspace, Path::new(path), offset).unwrap();
+        let definition = definition_of(workspace, Path::new(path), &symbol_ref)
+            .unwrap_or_else(|| panic!("{:?} did not resolve", symbol_ref));
+
+        (offset, definition)
+    }
+}